@@ -0,0 +1,177 @@
+use crate::json_value::first_str;
+use crate::term::{self, TermDescriptor};
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Identifies a past term by 学在浙大's own ids.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveTermKey {
+    pub academic_year_id: i64,
+    pub semester_id: i64,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchivedCourse {
+    pub id: i64,
+    pub name: String,
+    pub course_code: String,
+    pub teacher: String,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveTerm {
+    #[serde(flatten)]
+    pub key: ArchiveTermKey,
+    /// Derived from the courses' start dates, since the ids are opaque.
+    pub term: Option<TermDescriptor>,
+    pub courses: Vec<ArchivedCourse>,
+}
+
+pub fn term_key(course: &Value) -> Option<ArchiveTermKey> {
+    Some(ArchiveTermKey {
+        academic_year_id: course.get("academic_year_id").and_then(Value::as_i64)?,
+        semester_id: course.get("semester_id").and_then(Value::as_i64)?,
+    })
+}
+
+fn term_of_start(date: &str) -> Option<TermDescriptor> {
+    let date = NaiveDate::parse_from_str(date.get(..10)?, "%Y-%m-%d").ok()?;
+    let (year, semester) = match date.month() {
+        1 => (date.year() - 1, "1"),
+        2..=7 => (date.year() - 1, "2"),
+        _ => (date.year(), "1"),
+    };
+    Some(term::descriptor_from_parts(year.to_string(), semester))
+}
+
+/// Groups closed courses by academic year and semester, newest term first.
+/// Courses without term ids are left out.
+pub fn group_by_term(courses: &[Value]) -> Vec<ArchiveTerm> {
+    let mut terms = Vec::<ArchiveTerm>::new();
+    for course in courses {
+        let (Some(key), Some(id)) = (term_key(course), course.get("id").and_then(Value::as_i64))
+        else {
+            continue;
+        };
+        let archived = ArchivedCourse {
+            id,
+            name: first_str(course, &["display_name", "name", "second_name"])
+                .unwrap_or_else(|| "未命名课程".to_string()),
+            course_code: first_str(course, &["course_code"]).unwrap_or_default(),
+            teacher: course
+                .get("instructors")
+                .and_then(Value::as_array)
+                .and_then(|items| items.first())
+                .and_then(|item| first_str(item, &["name"]))
+                .unwrap_or_default(),
+            start_date: first_str(course, &["start_date"]),
+            end_date: first_str(course, &["end_date"]),
+        };
+        match terms.iter_mut().find(|term| term.key == key) {
+            Some(term) => term.courses.push(archived),
+            None => terms.push(ArchiveTerm {
+                key,
+                term: None,
+                courses: vec![archived],
+            }),
+        }
+    }
+
+    for term in &mut terms {
+        term.courses
+            .sort_by(|left, right| left.name.cmp(&right.name));
+        term.term = term
+            .courses
+            .iter()
+            .filter_map(|course| course.start_date.as_deref())
+            .min()
+            .and_then(term_of_start);
+    }
+    terms.sort_by(|left, right| {
+        (right.key.academic_year_id, right.key.semester_id)
+            .cmp(&(left.key.academic_year_id, left.key.semester_id))
+    });
+    terms
+}
+
+/// Closed courses of a past term given as a zdbk term: those whose course code
+/// carries the term's xkkh prefix, or, without a prefix, whose start date
+/// falls in the term.
+pub fn courses_in_term(courses: Vec<Value>, term: &TermDescriptor) -> Vec<Value> {
+    let prefix = term::build_xkkh_prefix(&term.year, &term.academic_semester);
+    courses
+        .into_iter()
+        .filter(|course| {
+            let code = first_str(course, &["course_code"]).unwrap_or_default();
+            if code.starts_with('(') {
+                return code.starts_with(&prefix);
+            }
+            first_str(course, &["start_date"])
+                .as_deref()
+                .and_then(term_of_start)
+                .is_some_and(|start| start.name == term.name)
+        })
+        .collect()
+}
+
+/// Closed courses belonging to one archived term.
+pub fn term_courses(courses: Vec<Value>, key: &ArchiveTermKey) -> Vec<Value> {
+    courses
+        .into_iter()
+        .filter(|course| term_key(course).as_ref() == Some(key))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{courses_in_term, group_by_term, term_courses, ArchiveTermKey};
+    use crate::term::descriptor_from_parts;
+    use serde_json::json;
+
+    #[test]
+    fn archive_groups_closed_courses_by_term() {
+        let courses = vec![
+            json!({ "id": 1, "display_name": "数据结构", "academic_year_id": 20, "semester_id": 40, "start_date": "2023-09-11" }),
+            json!({ "id": 2, "name": "微积分", "academic_year_id": 20, "semester_id": 41, "start_date": "2024-02-26",
+                    "instructors": [{ "name": "王老师" }] }),
+            json!({ "id": 3, "display_name": "离散数学", "academic_year_id": 20, "semester_id": 40, "start_date": "2023-09-12" }),
+            json!({ "id": 4, "display_name": "无学期" }),
+        ];
+        let terms = group_by_term(&courses);
+
+        assert_eq!(terms.len(), 2);
+        assert_eq!(terms[0].key.semester_id, 41);
+        assert_eq!(terms[0].courses[0].teacher, "王老师");
+        assert_eq!(terms[0].term.as_ref().unwrap().name, "2023-2024-2");
+        assert_eq!(terms[1].courses[0].name, "数据结构");
+        assert_eq!(terms[1].term.as_ref().unwrap().name, "2023-2024-1");
+
+        let key = ArchiveTermKey {
+            academic_year_id: 20,
+            semester_id: 40,
+        };
+        assert_eq!(term_courses(courses, &key).len(), 2);
+    }
+
+    #[test]
+    fn past_term_courses_match_by_xkkh_prefix_or_start_date() {
+        let courses = vec![
+            json!({ "id": 1, "course_code": "(2023-2024-1)-211C0010-0001", "start_date": "2023-09-11" }),
+            json!({ "id": 2, "course_code": "(2023-2024-2)-211C0020-0001", "start_date": "2024-02-26" }),
+            json!({ "id": 3, "course_code": "PE001", "start_date": "2023-09-18" }),
+            json!({ "id": 4, "course_code": "PE002", "start_date": "2024-03-04" }),
+            json!({ "id": 5, "course_code": "PE003" }),
+        ];
+        let ids = courses_in_term(courses, &descriptor_from_parts("2023", "1"))
+            .iter()
+            .filter_map(|course| course["id"].as_i64())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, [1, 3]);
+    }
+}
@@ -0,0 +1,225 @@
+use crate::courses;
+use crate::zjuam::AppState;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+
+/// Attendance rate below which a course is flagged when none is configured.
+pub const DEFAULT_THRESHOLD: f64 = 0.8;
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum RollcallStatus {
+    Present,
+    Late,
+    /// Excused absence (请假), not counted against the rate.
+    Leave,
+    Absent,
+    Unknown,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RollcallRecord {
+    pub id: Option<i64>,
+    pub date: String,
+    /// `number`, `qr`, `radar`… as reported by 学在浙大.
+    pub method: String,
+    pub status: RollcallStatus,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CourseAttendance {
+    pub course_id: i64,
+    pub course_name: String,
+    pub records: Vec<RollcallRecord>,
+    pub present: usize,
+    pub late: usize,
+    pub leave: usize,
+    pub absent: usize,
+    /// Share of counted roll calls attended, late included; `None` before
+    /// the first roll call.
+    pub rate: Option<f64>,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AttendanceReport {
+    pub threshold: f64,
+    pub courses: Vec<CourseAttendance>,
+    /// Courses whose rate is below the threshold, lowest first.
+    pub at_risk: Vec<i64>,
+    pub warnings: Vec<String>,
+}
+
+fn rollcall_status(raw: &Value) -> RollcallStatus {
+    let status = ["status", "rollcall_status", "student_status"]
+        .iter()
+        .find_map(|key| raw.get(*key).and_then(Value::as_str))
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    match status.as_str() {
+        "on_call" | "on_call_fine" | "present" | "attended" | "normal" => RollcallStatus::Present,
+        "late" => RollcallStatus::Late,
+        "leave" | "excused" | "asked_for_leave" => RollcallStatus::Leave,
+        "absent" | "absence" | "missed" => RollcallStatus::Absent,
+        _ => RollcallStatus::Unknown,
+    }
+}
+
+fn rollcall_record(raw: &Value) -> RollcallRecord {
+    RollcallRecord {
+        id: raw
+            .get("rollcall_id")
+            .or_else(|| raw.get("id"))
+            .and_then(Value::as_i64),
+        date: ["rollcall_time", "created_at", "date"]
+            .iter()
+            .find_map(|key| raw.get(*key).and_then(Value::as_str))
+            .unwrap_or_default()
+            .to_string(),
+        method: ["source", "method", "type"]
+            .iter()
+            .find_map(|key| raw.get(*key).and_then(Value::as_str))
+            .unwrap_or_default()
+            .to_string(),
+        status: rollcall_status(raw),
+    }
+}
+
+pub fn course_attendance(course_id: i64, course_name: &str, raw: &[Value]) -> CourseAttendance {
+    let mut records = raw.iter().map(rollcall_record).collect::<Vec<_>>();
+    records.sort_by(|left, right| right.date.cmp(&left.date));
+    let count = |status| {
+        records
+            .iter()
+            .filter(|record| record.status == status)
+            .count()
+    };
+    let (present, late, leave, absent) = (
+        count(RollcallStatus::Present),
+        count(RollcallStatus::Late),
+        count(RollcallStatus::Leave),
+        count(RollcallStatus::Absent),
+    );
+    let counted = present + late + absent;
+    CourseAttendance {
+        course_id,
+        course_name: course_name.to_string(),
+        present,
+        late,
+        leave,
+        absent,
+        rate: (counted > 0).then(|| (present + late) as f64 / counted as f64),
+        records,
+    }
+}
+
+pub fn report(
+    courses: Vec<CourseAttendance>,
+    threshold: f64,
+    warnings: Vec<String>,
+) -> AttendanceReport {
+    let mut at_risk = courses
+        .iter()
+        .filter_map(|course| Some((course.course_id, course.rate?)))
+        .filter(|(_, rate)| *rate < threshold)
+        .collect::<Vec<_>>();
+    at_risk.sort_by(|left, right| left.1.total_cmp(&right.1));
+    AttendanceReport {
+        threshold,
+        at_risk: at_risk
+            .into_iter()
+            .map(|(course_id, _)| course_id)
+            .collect(),
+        courses,
+        warnings,
+    }
+}
+
+/// Fills in the previously cached attendance of listed courses that failed
+/// this time, so a partial sync does not drop their roll-call history.
+pub fn merge_cached(
+    mut fetched: Vec<CourseAttendance>,
+    cached: Vec<CourseAttendance>,
+    course_ids: &[i64],
+) -> Vec<CourseAttendance> {
+    for course in cached {
+        if course_ids.contains(&course.course_id)
+            && !fetched
+                .iter()
+                .any(|known| known.course_id == course.course_id)
+        {
+            fetched.push(course);
+        }
+    }
+    fetched
+}
+
+/// Fetches roll calls of every given `(course id, course name)`. Courses that
+/// fail are reported in the returned warnings.
+pub async fn fetch_attendance(
+    state: Arc<AppState>,
+    courses: Vec<(i64, String)>,
+) -> (Vec<CourseAttendance>, Vec<String>) {
+    courses::for_each_course(
+        &state,
+        courses,
+        |state, course_id, course_name| async move {
+            let raw = courses::get_course_rollcalls(&state, course_id).await?;
+            Ok(course_attendance(course_id, &course_name, &raw))
+        },
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{course_attendance, merge_cached, report, RollcallStatus};
+    use serde_json::json;
+
+    #[test]
+    fn attendance_rate_counts_late_and_ignores_leave() {
+        let raw = [
+            json!({ "rollcall_id": 1, "rollcall_time": "2024-03-01T08:00:00Z", "source": "number", "status": "on_call_fine" }),
+            json!({ "rollcall_id": 2, "rollcall_time": "2024-03-08T08:00:00Z", "source": "qr", "status": "late" }),
+            json!({ "rollcall_id": 3, "rollcall_time": "2024-03-15T08:00:00Z", "source": "radar", "status": "absent" }),
+            json!({ "rollcall_id": 4, "rollcall_time": "2024-03-22T08:00:00Z", "status": "leave" }),
+        ];
+        let course = course_attendance(5, "操作系统", &raw);
+        assert_eq!(course.records[0].status, RollcallStatus::Leave);
+        assert_eq!(
+            (course.present, course.late, course.leave, course.absent),
+            (1, 1, 1, 1)
+        );
+        assert!((course.rate.unwrap() - 2.0 / 3.0).abs() < 1e-9);
+
+        let perfect = course_attendance(6, "数据结构", &raw[..1]);
+        let empty = course_attendance(7, "体育", &[]);
+        assert!(empty.rate.is_none());
+        let result = report(vec![perfect, course, empty], 0.8, Vec::new());
+        assert_eq!(result.at_risk, vec![5]);
+    }
+
+    #[test]
+    fn failed_courses_keep_their_cached_attendance() {
+        let raw = [
+            json!({ "rollcall_id": 1, "rollcall_time": "2024-03-01T08:00:00Z", "status": "absent" }),
+        ];
+        let cached = vec![
+            course_attendance(5, "操作系统", &raw),
+            course_attendance(6, "数据结构", &raw),
+            course_attendance(7, "体育", &raw),
+        ];
+        let fetched = vec![course_attendance(5, "操作系统", &[])];
+        let merged = merge_cached(fetched, cached, &[5, 6]);
+        let ids = merged
+            .iter()
+            .map(|course| course.course_id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, [5, 6]);
+        assert!(merged[0].records.is_empty());
+        assert_eq!(merged[1].absent, 1);
+    }
+}
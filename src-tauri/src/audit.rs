@@ -0,0 +1,371 @@
+use crate::gpa::{
+    enrich_grade, extract_semester_name, interpret_grade_text, normalize_semester_code,
+    to_five_point, GradeText,
+};
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// zdbk rounds `jd` to one decimal; anything further off is a real mismatch.
+pub(crate) const JD_TOLERANCE: f64 = 0.05;
+/// No single ZJU course carries more credits than this.
+const MAX_PLAUSIBLE_CREDIT: f64 = 20.0;
+
+#[derive(Clone, Copy, Debug, Serialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum AuditKind {
+    UnknownGrade,
+    EmptyGrade,
+    ScoreOutOfRange,
+    Credit,
+    Semester,
+    JdMismatch,
+    DuplicateRow,
+    DuplicateCourse,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum AuditSeverity {
+    /// The row is almost certainly counted wrong.
+    Error,
+    /// The row may be counted wrong; worth a look.
+    Warning,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditFinding {
+    pub index: usize,
+    pub xkkh: String,
+    pub course_name: String,
+    pub kind: AuditKind,
+    pub severity: AuditSeverity,
+    pub field: String,
+    pub value: String,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptAudit {
+    pub total_rows: usize,
+    pub flagged_rows: usize,
+    pub findings: Vec<AuditFinding>,
+}
+
+fn text_of(grade: &Value, key: &str) -> Option<String> {
+    grade.get(key).and_then(|value| match value {
+        Value::String(text) => Some(text.trim().to_string()),
+        Value::Number(number) => Some(number.to_string()),
+        _ => None,
+    })
+}
+
+struct RowAudit<'a> {
+    index: usize,
+    xkkh: &'a str,
+    course_name: &'a str,
+    findings: Vec<AuditFinding>,
+}
+
+impl RowAudit<'_> {
+    fn push(
+        &mut self,
+        kind: AuditKind,
+        severity: AuditSeverity,
+        field: &str,
+        value: &str,
+        message: String,
+    ) {
+        self.findings.push(AuditFinding {
+            index: self.index,
+            xkkh: self.xkkh.to_string(),
+            course_name: self.course_name.to_string(),
+            kind,
+            severity,
+            field: field.to_string(),
+            value: value.to_string(),
+            message,
+        });
+    }
+}
+
+fn audit_row(index: usize, grade: &Value) -> Vec<AuditFinding> {
+    let xkkh = grade
+        .get("xkkh")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let course_name = grade
+        .get("kcmc")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let mut row = RowAudit {
+        index,
+        xkkh,
+        course_name,
+        findings: Vec::new(),
+    };
+
+    let cj = text_of(grade, "cj").unwrap_or_default();
+    let interpreted = interpret_grade_text(&cj);
+    match interpreted {
+        GradeText::Empty => row.push(
+            AuditKind::EmptyGrade,
+            AuditSeverity::Error,
+            "cj",
+            &cj,
+            "成绩为空，按 0 分计入；若课程已出分，请刷新成绩或检查教务网数据".to_string(),
+        ),
+        GradeText::Unrecognized => row.push(
+            AuditKind::UnknownGrade,
+            AuditSeverity::Error,
+            "cj",
+            &cj,
+            format!("无法识别成绩「{cj}」，当前按 0 分、不及格处理，会拉低绩点"),
+        ),
+        GradeText::Extracted(value) => row.push(
+            AuditKind::UnknownGrade,
+            AuditSeverity::Warning,
+            "cj",
+            &cj,
+            format!("成绩「{cj}」不是已知的等级或分数，只能从中截取数字 {value} 当作百分制"),
+        ),
+        GradeText::Numeric(value) if !(0.0..=100.0).contains(&value) => row.push(
+            AuditKind::ScoreOutOfRange,
+            AuditSeverity::Error,
+            "cj",
+            &cj,
+            format!("百分制成绩 {value} 不在 0-100 之间"),
+        ),
+        _ => {}
+    }
+
+    let credit_field = if grade.get("xf").is_some() {
+        "xf"
+    } else {
+        "credit"
+    };
+    let credit_text = text_of(grade, credit_field);
+    match credit_text
+        .as_deref()
+        .map(|text| (text, text.parse::<f64>()))
+    {
+        None => row.push(
+            AuditKind::Credit,
+            AuditSeverity::Error,
+            "xf",
+            "",
+            "缺少学分，按 0 学分计，这门课不会影响绩点".to_string(),
+        ),
+        Some((text, Err(_))) => row.push(
+            AuditKind::Credit,
+            AuditSeverity::Error,
+            credit_field,
+            text,
+            format!("学分「{text}」不是数字，按 0 学分计"),
+        ),
+        Some((text, Ok(credit))) if !credit.is_finite() || credit < 0.0 => row.push(
+            AuditKind::Credit,
+            AuditSeverity::Error,
+            credit_field,
+            text,
+            format!("学分 {text} 为负数或无效"),
+        ),
+        Some((text, Ok(credit))) if credit > MAX_PLAUSIBLE_CREDIT => row.push(
+            AuditKind::Credit,
+            AuditSeverity::Warning,
+            credit_field,
+            text,
+            format!("学分 {text} 异常偏大，可能是数据错误"),
+        ),
+        _ => {}
+    }
+
+    let xkkh_term = Regex::new(r"\((\d{4})-(\d{4})-(\d+)\)")
+        .ok()
+        .and_then(|re| re.captures(xkkh))
+        .and_then(|caps| caps.get(3).map(|code| code.as_str().to_string()));
+    let from_xkkh = xkkh_term
+        .as_deref()
+        .is_some_and(|code| normalize_semester_code(code).is_some());
+    let xqm = text_of(grade, "xqm").unwrap_or_default();
+    if extract_semester_name(grade).is_none() {
+        row.push(
+            AuditKind::Semester,
+            AuditSeverity::Warning,
+            "xkkh",
+            xkkh,
+            "无法从选课课号或学年字段判断学期，已归入「其他/认定」".to_string(),
+        );
+    } else if let Some(code) = xkkh_term.filter(|code| normalize_semester_code(code).is_none()) {
+        row.push(
+            AuditKind::Semester,
+            AuditSeverity::Warning,
+            "xkkh",
+            xkkh,
+            format!("选课课号中的学期代码 {code} 无法识别，改用 xnm/xqm 推断学期"),
+        );
+    } else if !from_xkkh && !xqm.is_empty() && normalize_semester_code(&xqm).is_none() {
+        row.push(
+            AuditKind::Semester,
+            AuditSeverity::Warning,
+            "xqm",
+            &xqm,
+            format!("学期代码 xqm={xqm} 无法识别，按秋冬学期处理"),
+        );
+    }
+
+    let enriched = enrich_grade(grade);
+    let gpa_included = enriched
+        .get("gpaIncluded")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    if let (Some(jd_text), GradeText::Known(score) | GradeText::Numeric(score)) =
+        (text_of(grade, "jd"), interpreted)
+    {
+        let expected = to_five_point(score);
+        if let Ok(jd) = jd_text.parse::<f64>() {
+            if gpa_included && (jd - expected).abs() > JD_TOLERANCE {
+                row.push(
+                    AuditKind::JdMismatch,
+                    AuditSeverity::Warning,
+                    "jd",
+                    &jd_text,
+                    format!(
+                        "教务网绩点 jd={jd_text}，按成绩「{cj}」换算应为 {expected:.1}；当前计算采用教务网的 jd"
+                    ),
+                );
+            }
+        }
+    }
+
+    row.findings
+}
+
+pub fn audit_transcript(grades: &[Value]) -> TranscriptAudit {
+    let mut findings = grades
+        .iter()
+        .enumerate()
+        .flat_map(|(index, grade)| audit_row(index, grade))
+        .collect::<Vec<_>>();
+
+    let mut by_xkkh = HashMap::<&str, Vec<usize>>::new();
+    let mut by_course = HashMap::<(String, String), Vec<usize>>::new();
+    for (index, grade) in grades.iter().enumerate() {
+        let xkkh = grade
+            .get("xkkh")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .trim();
+        if !xkkh.is_empty() {
+            by_xkkh.entry(xkkh).or_default().push(index);
+        }
+        let kcdm = text_of(grade, "kcdm").unwrap_or_default();
+        if let (false, Some(semester)) = (kcdm.is_empty(), extract_semester_name(grade)) {
+            by_course.entry((kcdm, semester)).or_default().push(index);
+        }
+    }
+
+    let duplicate =
+        |index: usize, kind: AuditKind, field: &str, value: &str, message: String| AuditFinding {
+            index,
+            xkkh: text_of(&grades[index], "xkkh").unwrap_or_default(),
+            course_name: text_of(&grades[index], "kcmc").unwrap_or_default(),
+            kind,
+            severity: AuditSeverity::Warning,
+            field: field.to_string(),
+            value: value.to_string(),
+            message,
+        };
+    for (xkkh, rows) in &by_xkkh {
+        for index in rows.iter().skip(1) {
+            findings.push(duplicate(
+                *index,
+                AuditKind::DuplicateRow,
+                "xkkh",
+                xkkh,
+                format!(
+                    "选课课号重复出现 {} 次，与第 {} 行重复；重复行会按重修处理",
+                    rows.len(),
+                    rows[0] + 1
+                ),
+            ));
+        }
+    }
+    for ((kcdm, semester), rows) in &by_course {
+        let distinct = rows
+            .iter()
+            .filter_map(|index| text_of(&grades[*index], "xkkh"))
+            .collect::<std::collections::HashSet<_>>();
+        if distinct.len() < 2 {
+            continue;
+        }
+        for index in rows.iter().skip(1) {
+            findings.push(duplicate(
+                *index,
+                AuditKind::DuplicateCourse,
+                "kcdm",
+                kcdm,
+                format!(
+                    "课程 {kcdm} 在 {semester} 出现了 {} 条不同课号的记录，可能是重复导入",
+                    rows.len()
+                ),
+            ));
+        }
+    }
+
+    findings.sort_by(|left, right| {
+        left.index
+            .cmp(&right.index)
+            .then_with(|| (left.kind as u8).cmp(&(right.kind as u8)))
+    });
+    let mut flagged = findings
+        .iter()
+        .map(|finding| finding.index)
+        .collect::<Vec<_>>();
+    flagged.dedup();
+
+    TranscriptAudit {
+        total_rows: grades.len(),
+        flagged_rows: flagged.len(),
+        findings,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{audit_transcript, AuditKind};
+    use serde_json::json;
+
+    #[test]
+    fn audit_flags_unknown_grades_credits_jd_and_duplicates() {
+        let grades = vec![
+            json!({ "xkkh": "(2023-2024-1)-A-1", "kcdm": "A", "cj": "88", "xf": "2.0", "jd": "4.5" }),
+            json!({ "xkkh": "(2023-2024-1)-B-1", "kcdm": "B", "cj": "良好（补）", "xf": "2" }),
+            json!({ "xkkh": "(2023-2024-1)-C-1", "kcdm": "C", "cj": "P", "xf": "两" }),
+            json!({ "xkkh": "(2023-2024-1)-A-1", "kcdm": "A", "cj": "88", "xf": "2.0" }),
+            json!({ "kcdm": "D", "cj": "90", "xf": 1.0 }),
+            json!({ "xkkh": "(2023-2024-1)-E-1", "kcdm": "E", "cj": "92", "xf": 2.0, "jd": "4.8" }),
+        ];
+        let audit = audit_transcript(&grades);
+        let kinds = |index: usize| {
+            audit
+                .findings
+                .iter()
+                .filter(|finding| finding.index == index)
+                .map(|finding| finding.kind)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(kinds(0), vec![AuditKind::JdMismatch]);
+        assert_eq!(kinds(1), vec![AuditKind::UnknownGrade]);
+        assert_eq!(kinds(2), vec![AuditKind::UnknownGrade, AuditKind::Credit]);
+        assert_eq!(kinds(3), vec![AuditKind::DuplicateRow]);
+        assert_eq!(kinds(4), vec![AuditKind::Semester]);
+        assert!(kinds(5).is_empty());
+        assert_eq!(audit.flagged_rows, 5);
+        assert!(audit.findings[0].message.contains("4.2"));
+    }
+}
@@ -3,8 +3,8 @@ use chrono::{Datelike, Duration, Local, NaiveDate, Weekday};
 use percent_encoding::percent_decode_str;
 use regex::Regex;
 use reqwest::cookie::{CookieStore, Jar};
-use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, USER_AGENT};
-use reqwest::{Client, Url};
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, RANGE, USER_AGENT};
+use reqwest::{Client, Response, Url};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::{HashMap, HashSet};
@@ -45,6 +45,30 @@ pub struct ClassroomFetchResult {
     pub query_summary: ClassroomQuerySummary,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum LectureTrack {
+    /// Camera recording of the lecturer.
+    Teacher,
+    /// Capture of the classroom computer or projector.
+    Screen,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum LectureStreamFormat {
+    Hls,
+    Mp4,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LectureStream {
+    pub track: LectureTrack,
+    pub format: LectureStreamFormat,
+    pub url: String,
+}
+
 #[derive(Clone)]
 pub struct ClassroomSession {
     client: Client,
@@ -67,6 +91,86 @@ fn clean_label(input: &str) -> String {
     input.trim().replace('/', "_")
 }
 
+fn lecture_track(entry: &Value, index: usize) -> LectureTrack {
+    let kind = ["type", "video_type"]
+        .iter()
+        .find_map(|key| entry.get(*key))
+        .map(|value| match value {
+            Value::String(text) => text.trim().to_ascii_lowercase(),
+            other => other.to_string(),
+        })
+        .unwrap_or_default();
+    let name = ["name", "title"]
+        .iter()
+        .find_map(|key| entry.get(*key).and_then(Value::as_str))
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    match kind.as_str() {
+        "1" | "teacher" | "camera" => LectureTrack::Teacher,
+        "2" | "ppt" | "screen" | "vga" => LectureTrack::Screen,
+        _ if name.contains("教师") || name.contains("teacher") => LectureTrack::Teacher,
+        _ if ["屏幕", "电脑", "ppt", "screen"]
+            .iter()
+            .any(|label| name.contains(label)) =>
+        {
+            LectureTrack::Screen
+        }
+        _ if index == 0 => LectureTrack::Teacher,
+        _ => LectureTrack::Screen,
+    }
+}
+
+/// Reads the replay streams of a lecture from the sub-info payload: entries
+/// of `video_list`, or the playback url in `content` for older recordings.
+pub fn parse_lecture_streams(payload: &Value) -> Vec<LectureStream> {
+    let data = payload.get("data").unwrap_or(payload);
+    let mut entries = match data.get("video_list") {
+        Some(Value::Array(list)) => list.clone(),
+        Some(Value::Object(map)) => map.values().cloned().collect(),
+        _ => Vec::new(),
+    };
+    if entries.is_empty() {
+        let content = match data.get("content") {
+            Some(Value::String(text)) => serde_json::from_str(text).unwrap_or(Value::Null),
+            Some(other) => other.clone(),
+            None => Value::Null,
+        };
+        if let Some(playback) = content.get("playback") {
+            entries.push(playback.clone());
+        }
+    }
+
+    let mut streams = Vec::<LectureStream>::new();
+    for (index, entry) in entries.iter().enumerate() {
+        let Some(url) = ["preview_url", "url", "video_url", "playback_url"]
+            .iter()
+            .find_map(|key| entry.get(*key).and_then(Value::as_str))
+            .map(str::trim)
+            .filter(|url| url.starts_with("http://") || url.starts_with("https://"))
+        else {
+            continue;
+        };
+        if streams.iter().any(|stream| stream.url == url) {
+            continue;
+        }
+        let path = url
+            .split(['?', '#'])
+            .next()
+            .unwrap_or(url)
+            .to_ascii_lowercase();
+        streams.push(LectureStream {
+            track: lecture_track(entry, index),
+            format: if path.ends_with(".m3u8") {
+                LectureStreamFormat::Hls
+            } else {
+                LectureStreamFormat::Mp4
+            },
+            url: url.to_string(),
+        });
+    }
+    streams
+}
+
 fn auth_headers(token: &str) -> Result<HeaderMap, String> {
     let mut headers = HeaderMap::new();
     headers.insert(USER_AGENT, HeaderValue::from_static(USER_AGENT_VALUE));
@@ -350,7 +454,7 @@ impl ClassroomSession {
         }
 
         let mut merged = HashMap::<(i64, i64), ClassroomSubject>::new();
-        for subject in all_subjects.into_iter().chain(current_items.into_iter()) {
+        for subject in all_subjects.into_iter().chain(current_items) {
             let key = (subject.course_id, subject.sub_id);
             let entry = merged.entry(key).or_insert_with(|| ClassroomSubject {
                 week_bucket: if current_ids.contains(&key) {
@@ -581,6 +685,48 @@ impl ClassroomSession {
         Ok((bytes.to_vec(), mime))
     }
 
+    /// Resolves the replay video streams of a lecture.
+    pub async fn get_lecture_streams(
+        &self,
+        course_id: i64,
+        sub_id: i64,
+    ) -> Result<Vec<LectureStream>, String> {
+        let headers = auth_headers(&self.token)?;
+        let payload: Value = self
+            .client
+            .get(format!(
+                "https://yjapi.cmc.zju.edu.cn/courseapi/v3/portal-home-setting/get-sub-info?course_id={course_id}&sub_id={sub_id}"
+            ))
+            .headers(headers)
+            .send()
+            .await
+            .map_err(|error| format!("拉取智云课堂回放信息失败: {error}"))?
+            .json()
+            .await
+            .map_err(|error| format!("解析智云课堂回放信息失败: {error}"))?;
+        Ok(parse_lecture_streams(&payload))
+    }
+
+    /// Requests `url` from byte `offset` onwards. Servers that ignore the
+    /// range answer `200` with the whole body.
+    pub async fn get_from(&self, url: &str, offset: u64) -> Result<Response, String> {
+        let mut request = self.client.get(url);
+        if offset > 0 {
+            request = request.header(RANGE, format!("bytes={offset}-"));
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|error| format!("下载课程回放失败: {error}"))?;
+        if !response.status().is_success() {
+            return Err(format!(
+                "下载课程回放失败: HTTP {}",
+                response.status().as_u16()
+            ));
+        }
+        Ok(response)
+    }
+
     fn subject_from_day(&self, course: &Value) -> Option<ClassroomSubject> {
         let course_id = parse_i64(course.get("id"));
         let sub_id = parse_i64(course.get("sub_id"));
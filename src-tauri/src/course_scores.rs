@@ -0,0 +1,157 @@
+use crate::courses;
+use crate::json_value::first_f64;
+use crate::zjuam::AppState;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityScore {
+    pub activity_id: Option<i64>,
+    pub title: String,
+    /// `homework`, `exam`, `quiz`… as reported by 学在浙大.
+    pub kind: String,
+    /// `None` until the score is published.
+    pub score: Option<f64>,
+    pub full_score: f64,
+    /// Share of the course grade in percent, when exposed.
+    pub weight: Option<f64>,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum EstimateMethod {
+    /// Every graded activity has a weight: weighted mean of score ratios.
+    Weighted,
+    /// Weights missing: total points earned over total points possible.
+    Points,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CourseScoreProgress {
+    pub items: Vec<ActivityScore>,
+    pub graded: usize,
+    /// Running estimate on a 100-point scale over graded activities only.
+    pub estimate: Option<f64>,
+    pub method: Option<EstimateMethod>,
+    /// Total weight of the graded activities, in percent.
+    pub weight_covered: Option<f64>,
+}
+
+fn activity_score(raw: &Value) -> Option<ActivityScore> {
+    let title = ["title", "activity_title", "name"]
+        .iter()
+        .find_map(|key| raw.get(*key).and_then(Value::as_str))?
+        .trim()
+        .to_string();
+    let published = raw
+        .get("is_published")
+        .or_else(|| raw.get("published"))
+        .and_then(Value::as_bool)
+        .unwrap_or(true);
+    Some(ActivityScore {
+        activity_id: raw
+            .get("activity_id")
+            .or_else(|| raw.get("id"))
+            .and_then(Value::as_i64),
+        title,
+        kind: ["activity_type", "type"]
+            .iter()
+            .find_map(|key| raw.get(*key).and_then(Value::as_str))
+            .unwrap_or_default()
+            .to_string(),
+        score: first_f64(raw, &["score", "final_score"]).filter(|_| published),
+        full_score: first_f64(raw, &["full_score", "total_score", "max_score"]).unwrap_or(100.0),
+        weight: first_f64(raw, &["score_percentage", "weight", "percentage"])
+            .filter(|weight| *weight > 0.0),
+    })
+}
+
+pub fn score_progress(raw: &[Value]) -> CourseScoreProgress {
+    let items = raw.iter().filter_map(activity_score).collect::<Vec<_>>();
+    let graded = items
+        .iter()
+        .filter_map(|item| Some((item.score?, item.full_score, item.weight)))
+        .filter(|(_, full, _)| *full > 0.0)
+        .collect::<Vec<_>>();
+
+    let (estimate, method, weight_covered) = if graded.is_empty() {
+        (None, None, None)
+    } else if graded.iter().all(|(_, _, weight)| weight.is_some()) {
+        let covered = graded
+            .iter()
+            .map(|(_, _, weight)| weight.unwrap_or(0.0))
+            .sum::<f64>();
+        let weighted = graded
+            .iter()
+            .map(|(score, full, weight)| weight.unwrap_or(0.0) * score / full)
+            .sum::<f64>();
+        (
+            Some(weighted / covered * 100.0),
+            Some(EstimateMethod::Weighted),
+            Some(covered),
+        )
+    } else {
+        let earned = graded.iter().map(|(score, _, _)| score).sum::<f64>();
+        let possible = graded.iter().map(|(_, full, _)| full).sum::<f64>();
+        (
+            Some(earned / possible * 100.0),
+            Some(EstimateMethod::Points),
+            None,
+        )
+    };
+
+    CourseScoreProgress {
+        graded: graded.len(),
+        items,
+        estimate,
+        method,
+        weight_covered,
+    }
+}
+
+/// Fetches score progress of every given `(course id, course name)`.
+/// Courses whose scores cannot be read are reported in the returned warnings.
+pub async fn fetch_progress(
+    state: Arc<AppState>,
+    courses: Vec<(i64, String)>,
+) -> (HashMap<i64, CourseScoreProgress>, Vec<String>) {
+    let (progress, warnings) =
+        courses::for_each_course(&state, courses, |state, course_id, _| async move {
+            let raw = courses::get_course_activity_scores(&state, course_id).await?;
+            Ok((course_id, score_progress(&raw)))
+        })
+        .await;
+    (progress.into_iter().collect(), warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{score_progress, EstimateMethod};
+    use serde_json::json;
+
+    #[test]
+    fn score_progress_weights_when_exposed_and_falls_back_to_points() {
+        let weighted = score_progress(&[
+            json!({ "activity_id": 1, "title": "作业一", "activity_type": "homework", "score": 9, "full_score": 10, "score_percentage": 10 }),
+            json!({ "activity_id": 2, "title": "期中", "activity_type": "exam", "score": "70", "full_score": 100, "score_percentage": 30 }),
+            json!({ "activity_id": 3, "title": "期末", "activity_type": "exam", "full_score": 100, "score_percentage": 60 }),
+        ]);
+        assert_eq!(weighted.graded, 2);
+        assert_eq!(weighted.method, Some(EstimateMethod::Weighted));
+        assert_eq!(weighted.weight_covered, Some(40.0));
+        assert!((weighted.estimate.unwrap() - 75.0).abs() < 1e-9);
+
+        let points = score_progress(&[
+            json!({ "title": "测验", "score": 8, "full_score": 10 }),
+            json!({ "title": "作业", "score": 27, "full_score": 30, "score_percentage": 20 }),
+            json!({ "title": "未公布", "score": 5, "full_score": 10, "is_published": false }),
+        ]);
+        assert_eq!(points.method, Some(EstimateMethod::Points));
+        assert!((points.estimate.unwrap() - 87.5).abs() < 1e-9);
+        assert!(score_progress(&[]).estimate.is_none());
+    }
+}
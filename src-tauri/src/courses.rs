@@ -1,13 +1,22 @@
 use crate::zjuam::AppState;
+use reqwest::multipart::{Form, Part};
 use reqwest::Response;
-use serde_json::Value;
+use serde_json::{json, Value};
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+
+/// Per-course requests in flight at once when fanning out over courses.
+const COURSE_FETCH_CONCURRENCY: usize = 6;
 
 fn todo_endpoint() -> &'static str {
     "https://courses.zju.edu.cn/api/todos"
 }
 
-fn my_courses_base() -> &'static str {
-    "https://courses.zju.edu.cn/api/my-courses?conditions=%7B%22status%22:%5B%22ongoing%22,%22notStarted%22%5D,%22keyword%22:%22%22,%22classify_type%22:%22recently_started%22,%22display_studio_list%22:false%7D&fields=id,name,course_code,department(id,name),grade(id,name),klass(id,name),course_type,cover,small_cover,start_date,end_date,is_started,is_closed,academic_year_id,semester_id,credit,compulsory,second_name,display_name,created_user(id,name),org(is_enterprise_or_organization),org_id,public_scope,audit_status,audit_remark,can_withdraw_course,imported_from,allow_clone,is_instructor,is_team_teaching,is_default_course_cover,instructors(id,name,email,avatar_small_url),course_attributes(teaching_class_name,is_during_publish_period,copy_status,tip,data),user_stick_course_record(id),classroom_schedule"
+/// `statuses` is the URL-encoded status list of the my-courses query,
+/// e.g. `%22closed%22`.
+fn my_courses_base(statuses: &str) -> String {
+    format!("https://courses.zju.edu.cn/api/my-courses?conditions=%7B%22status%22:%5B{statuses}%5D,%22keyword%22:%22%22,%22classify_type%22:%22recently_started%22,%22display_studio_list%22:false%7D&fields=id,name,course_code,department(id,name),grade(id,name),klass(id,name),course_type,cover,small_cover,start_date,end_date,is_started,is_closed,academic_year_id,semester_id,credit,compulsory,second_name,display_name,created_user(id,name),org(is_enterprise_or_organization),org_id,public_scope,audit_status,audit_remark,can_withdraw_course,imported_from,allow_clone,is_instructor,is_team_teaching,is_default_course_cover,instructors(id,name,email,avatar_small_url),course_attributes(teaching_class_name,is_during_publish_period,copy_status,tip,data),user_stick_course_record(id),classroom_schedule")
 }
 
 async fn courses_cookie(state: &AppState) -> Result<String, String> {
@@ -40,6 +49,37 @@ async fn courses_get_json(state: &AppState, url: &str) -> Result<Value, String>
         .map_err(|e| format!("学在浙大 JSON 解析失败: {}", e))
 }
 
+async fn courses_post_json(state: &AppState, url: &str, body: &Value) -> Result<Value, String> {
+    let cookie = courses_cookie(state).await?;
+    let res = state
+        .client
+        .post(url)
+        .header("Cookie", cookie)
+        .json(body)
+        .send()
+        .await
+        .map_err(|e| format!("学在浙大请求失败: {}", e))?;
+
+    if !res.status().is_success() {
+        let status = res.status().as_u16();
+        let message = res
+            .json::<Value>()
+            .await
+            .ok()
+            .and_then(|body| {
+                body.get("message")
+                    .and_then(Value::as_str)
+                    .map(str::to_string)
+            })
+            .unwrap_or_default();
+        return Err(format!("学在浙大接口异常: HTTP {status} {message} ({url})"));
+    }
+
+    res.json::<Value>()
+        .await
+        .map_err(|e| format!("学在浙大 JSON 解析失败: {}", e))
+}
+
 /// Login to courses.zju.edu.cn by following CAS SSO redirects.
 pub async fn login_courses(state: &AppState) -> Result<(), String> {
     let client = &state.client;
@@ -90,13 +130,22 @@ pub async fn get_todos(state: &AppState) -> Result<Value, String> {
 
 /// Fetch current learning courses from 学在浙大.
 pub async fn get_learning_courses(state: &AppState) -> Result<Vec<Value>, String> {
+    get_my_courses(state, "%22ongoing%22,%22notStarted%22").await
+}
+
+/// Fetch closed courses of past terms from 学在浙大.
+pub async fn get_archived_courses(state: &AppState) -> Result<Vec<Value>, String> {
+    get_my_courses(state, "%22closed%22").await
+}
+
+async fn get_my_courses(state: &AppState, statuses: &str) -> Result<Vec<Value>, String> {
     let mut page = 1_i64;
     let mut items = Vec::new();
 
     loop {
         let url = format!(
             "{}&page={page}&page_size=100&showScorePassedStatus=false",
-            my_courses_base()
+            my_courses_base(statuses)
         );
         let body = courses_get_json(state, &url).await?;
         if let Some(courses) = body.get("courses").and_then(Value::as_array) {
@@ -112,18 +161,33 @@ pub async fn get_learning_courses(state: &AppState) -> Result<Vec<Value>, String
     Ok(items)
 }
 
+/// Fetch every learning activity of a course (materials, videos, homework…).
+pub async fn get_course_activities(state: &AppState, course_id: i64) -> Result<Vec<Value>, String> {
+    let url = format!("https://courses.zju.edu.cn/api/courses/{course_id}/activities");
+    let body = courses_get_json(state, &url).await?;
+    Ok(body
+        .get("activities")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default())
+}
+
+/// Fetch the current user's progress on a course's activities, e.g. how much
+/// of each video was watched.
+pub async fn get_activity_reads(state: &AppState, course_id: i64) -> Result<Vec<Value>, String> {
+    let url = format!("https://courses.zju.edu.cn/api/course/{course_id}/activity-reads-for-user");
+    let body = courses_get_json(state, &url).await?;
+    Ok(paged_items(&body, &["activity_reads", "items"]).0)
+}
+
 pub async fn get_course_activity_uploads(
     state: &AppState,
     course_id: i64,
 ) -> Result<Vec<Value>, String> {
-    let url = format!("https://courses.zju.edu.cn/api/courses/{course_id}/activities");
-    let body = courses_get_json(state, &url).await?;
     let mut uploads = Vec::new();
-    if let Some(activities) = body.get("activities").and_then(Value::as_array) {
-        for activity in activities {
-            if let Some(activity_uploads) = activity.get("uploads").and_then(Value::as_array) {
-                uploads.extend(activity_uploads.iter().cloned());
-            }
+    for activity in get_course_activities(state, course_id).await? {
+        if let Some(activity_uploads) = activity.get("uploads").and_then(Value::as_array) {
+            uploads.extend(activity_uploads.iter().cloned());
         }
     }
     Ok(uploads)
@@ -195,3 +259,201 @@ pub async fn get_upload_download_response(
         Err(format!("拉取资料失败: HTTP {}", fallback.status().as_u16()))
     }
 }
+
+fn paged_items(body: &Value, keys: &[&str]) -> (Vec<Value>, i64) {
+    let items = keys
+        .iter()
+        .find_map(|key| body.get(*key).and_then(Value::as_array))
+        .cloned()
+        .unwrap_or_default();
+    let pages = body.get("pages").and_then(Value::as_i64).unwrap_or(1);
+    (items, pages)
+}
+
+/// Runs `fetch` for every `(course id, course name)`, a few courses at a
+/// time. Results keep the input order; courses that fail are reported as
+/// `课程名: 错误` warnings instead.
+pub async fn for_each_course<T, F, Fut>(
+    state: &Arc<AppState>,
+    courses: Vec<(i64, String)>,
+    fetch: F,
+) -> (Vec<T>, Vec<String>)
+where
+    F: Fn(Arc<AppState>, i64, String) -> Fut,
+    Fut: Future<Output = Result<T, String>> + Send + 'static,
+    T: Send + 'static,
+{
+    let limit = Arc::new(Semaphore::new(COURSE_FETCH_CONCURRENCY));
+    let handles = courses
+        .into_iter()
+        .map(|(course_id, course_name)| {
+            let task = fetch(state.clone(), course_id, course_name.clone());
+            let limit = limit.clone();
+            let handle = tokio::spawn(async move {
+                let _permit = limit.acquire_owned().await;
+                task.await
+            });
+            (course_name, handle)
+        })
+        .collect::<Vec<_>>();
+
+    let mut results = Vec::new();
+    let mut warnings = Vec::new();
+    for (course_name, handle) in handles {
+        match handle.await {
+            Ok(Ok(result)) => results.push(result),
+            Ok(Err(error)) => warnings.push(format!("{course_name}: {error}")),
+            Err(error) => warnings.push(format!("{course_name}: {error}")),
+        }
+    }
+    (results, warnings)
+}
+
+/// Fetch one page of a course's announcements, newest first. Returns the
+/// items and the total page count.
+pub async fn get_course_announcements(
+    state: &AppState,
+    course_id: i64,
+    page: i64,
+) -> Result<(Vec<Value>, i64), String> {
+    let url = format!(
+        "https://courses.zju.edu.cn/api/courses/{course_id}/announcements?page={page}&page_size=20"
+    );
+    let body = courses_get_json(state, &url).await?;
+    Ok(paged_items(&body, &["announcements", "items"]))
+}
+
+/// Fetch one page of the user's notification inbox, newest first.
+pub async fn get_notifications(state: &AppState, page: i64) -> Result<(Vec<Value>, i64), String> {
+    let url = format!("https://courses.zju.edu.cn/api/notifications?page={page}&page_size=50");
+    let body = courses_get_json(state, &url).await?;
+    Ok(paged_items(&body, &["notifications", "items", "list"]))
+}
+
+/// Fetch a course's quizzes and online exams with their open window, time
+/// limit and the user's attempts.
+pub async fn get_course_exams(state: &AppState, course_id: i64) -> Result<Vec<Value>, String> {
+    let url = format!("https://courses.zju.edu.cn/api/courses/{course_id}/exams");
+    let body = courses_get_json(state, &url).await?;
+    Ok(paged_items(&body, &["exams", "items"]).0)
+}
+
+/// Fetch the current user's roll-call records in a course.
+pub async fn get_course_rollcalls(state: &AppState, course_id: i64) -> Result<Vec<Value>, String> {
+    let url = format!("https://courses.zju.edu.cn/api/courses/{course_id}/student-rollcalls");
+    let body = courses_get_json(state, &url).await?;
+    Ok(paged_items(&body, &["rollcalls", "rollcall_records", "items"]).0)
+}
+
+/// Fetch the current user's scored activities in a course (homework,
+/// quizzes, exams), including weights when the teacher published them.
+pub async fn get_course_activity_scores(
+    state: &AppState,
+    course_id: i64,
+) -> Result<Vec<Value>, String> {
+    let url = format!("https://courses.zju.edu.cn/api/courses/{course_id}/activity-scores");
+    let body = courses_get_json(state, &url).await?;
+    Ok(["activity_scores", "scores", "items"]
+        .iter()
+        .find_map(|key| body.get(*key).and_then(Value::as_array))
+        .cloned()
+        .unwrap_or_default())
+}
+
+/// Fetch a single activity (homework, exam, material page) with its
+/// description and attachments.
+pub async fn get_activity(state: &AppState, activity_id: i64) -> Result<Value, String> {
+    let url = format!("https://courses.zju.edu.cn/api/activities/{activity_id}");
+    courses_get_json(state, &url).await
+}
+
+/// Fetch the current user's submissions for a homework activity, newest
+/// first as returned by the server.
+pub async fn get_activity_submissions(
+    state: &AppState,
+    activity_id: i64,
+) -> Result<Vec<Value>, String> {
+    let url = format!("https://courses.zju.edu.cn/api/activities/{activity_id}/submissions");
+    let body = courses_get_json(state, &url).await?;
+    Ok(["submissions", "list", "items"]
+        .iter()
+        .find_map(|key| body.get(*key).and_then(Value::as_array))
+        .or_else(|| body.as_array())
+        .cloned()
+        .unwrap_or_default())
+}
+
+/// Upload a file as a homework attachment: register the upload, then send the
+/// bytes to the returned `upload_url`. Returns the upload record.
+pub async fn upload_attachment(
+    state: &AppState,
+    file_name: &str,
+    data: Vec<u8>,
+) -> Result<Value, String> {
+    let created = courses_post_json(
+        state,
+        "https://courses.zju.edu.cn/api/uploads",
+        &json!({
+            "name": file_name,
+            "size": data.len(),
+            "parent_type": null,
+            "parent_id": 0,
+            "is_scorm": false,
+            "is_wmpkg": false,
+            "source": "",
+            "is_marked_attachment": false,
+            "embed_material_type": "",
+        }),
+    )
+    .await?;
+    let upload_url = created
+        .get("upload_url")
+        .and_then(Value::as_str)
+        .ok_or("学在浙大未返回上传地址")?;
+    let upload_url = if upload_url.starts_with('/') {
+        format!("https://courses.zju.edu.cn{upload_url}")
+    } else {
+        upload_url.to_string()
+    };
+
+    let cookie = courses_cookie(state).await?;
+    let form = Form::new().part("file", Part::bytes(data).file_name(file_name.to_string()));
+    let res = state
+        .client
+        .put(&upload_url)
+        .header("Cookie", cookie)
+        .multipart(form)
+        .send()
+        .await
+        .map_err(|e| format!("上传附件失败: {}", e))?;
+    if !res.status().is_success() {
+        return Err(format!("上传附件失败: HTTP {}", res.status().as_u16()));
+    }
+    Ok(created)
+}
+
+/// Submit (or save as draft) a homework activity with already uploaded
+/// attachments. Returns the submission record.
+pub async fn submit_homework(
+    state: &AppState,
+    activity_id: i64,
+    upload_ids: &[i64],
+    comment: &str,
+    draft: bool,
+) -> Result<Value, String> {
+    let url = format!("https://courses.zju.edu.cn/api/course/activities/{activity_id}/submissions");
+    courses_post_json(
+        state,
+        &url,
+        &json!({
+            "comment": comment,
+            "uploads": upload_ids,
+            "slides": [],
+            "is_draft": draft,
+            "mode": "normal",
+            "other_resources": [],
+            "uploads_in_rich_text": [],
+        }),
+    )
+    .await
+}
@@ -0,0 +1,516 @@
+use crate::gpa::{compute_gpa_by_policy, semester_rank, RetakeSelection};
+use crate::gpa_scale::GpaScale;
+use crate::preset_store::{self, Preset, PresetOrigin};
+use crate::zdbk::PracticeScores;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use tauri::AppHandle;
+
+const PRESET_SOURCES: &[&str] = &[
+    include_str!("../presets/evaluation_rules/zju-comprehensive.json"),
+    include_str!("../presets/evaluation_rules/academic-only.json"),
+];
+
+pub type RuleOrigin = PresetOrigin;
+
+/// Which semesters count. An explicit `terms` list wins; otherwise `from` and
+/// `to` bound the range and `recent` keeps only the latest N of what is left.
+/// An empty window covers the whole transcript.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TermWindow {
+    pub terms: Vec<String>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub recent: Option<usize>,
+}
+
+/// Which GPA the academic part uses. Part of the rule file format, so it is
+/// kept apart from the planner's scope.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum AcademicScope {
+    #[default]
+    Overall,
+    Major,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AcademicRule {
+    pub weight: f64,
+    #[serde(default)]
+    pub scope: AcademicScope,
+}
+
+/// A non-academic item. `pt2`/`pt3`/`pt4` read the fetched practice scores;
+/// any other key is looked up in the bonus items supplied with the request.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentRule {
+    pub key: String,
+    #[serde(default)]
+    pub label: String,
+    pub weight: f64,
+    pub cap: Option<f64>,
+    /// Raw value that maps to 100; without it the raw value is used directly.
+    pub full_score: Option<f64>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TierRule {
+    pub id: String,
+    pub name: String,
+    pub min_score: Option<f64>,
+    /// Minimum GPA on the rule's scale.
+    pub min_gpa: Option<f64>,
+    pub max_failed: Option<usize>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EvaluationRule {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub term_window: TermWindow,
+    pub scale_id: String,
+    pub academic: AcademicRule,
+    #[serde(default)]
+    pub components: Vec<ComponentRule>,
+    pub total_cap: Option<f64>,
+    #[serde(default)]
+    pub min_credits: f64,
+    #[serde(default)]
+    pub exclude_failed: bool,
+    #[serde(default)]
+    pub tiers: Vec<TierRule>,
+    #[serde(default, skip_deserializing)]
+    pub origin: RuleOrigin,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvaluationRuleImportInput {
+    pub path: Option<String>,
+    pub content: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EvaluationItem {
+    pub key: String,
+    pub label: String,
+    pub raw: f64,
+    pub capped: f64,
+    pub normalized: f64,
+    pub weight: f64,
+    pub score: f64,
+    pub note: String,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TierResult {
+    pub id: String,
+    pub name: String,
+    pub eligible: bool,
+    pub reasons: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EvaluationResult {
+    pub rule_id: String,
+    pub rule_name: String,
+    pub terms: Vec<String>,
+    pub scale_id: String,
+    pub scale_name: String,
+    pub gpa: f64,
+    pub max_points: f64,
+    pub credits: f64,
+    pub failed_courses: Vec<String>,
+    pub items: Vec<EvaluationItem>,
+    pub total: f64,
+    pub tiers: Vec<TierResult>,
+    pub warnings: Vec<String>,
+}
+
+impl Preset for EvaluationRule {
+    const LABEL: &'static str = "测评规则";
+    const USER_DIR: &'static str = "evaluation_rules";
+    const SOURCES: &'static [&'static str] = PRESET_SOURCES;
+
+    fn parse(content: &str, origin: PresetOrigin) -> Result<Self, String> {
+        parse_rule(content, origin)
+    }
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+fn valid_weight(value: f64) -> bool {
+    value.is_finite() && value >= 0.0
+}
+
+pub fn parse_rule(content: &str, origin: RuleOrigin) -> Result<EvaluationRule, String> {
+    let mut rule = serde_json::from_str::<EvaluationRule>(content)
+        .map_err(|error| format!("测评规则格式错误: {error}"))?;
+    rule.id = rule.id.trim().to_string();
+    rule.name = rule.name.trim().to_string();
+    rule.origin = origin;
+
+    preset_store::check_id::<EvaluationRule>(&rule.id)?;
+    if rule.name.is_empty() {
+        return Err(format!("测评规则 {} 缺少名称", rule.id));
+    }
+    if !valid_weight(rule.academic.weight) {
+        return Err(format!("测评规则 {} 的学业权重无效", rule.id));
+    }
+    let mut keys = HashSet::new();
+    for component in &mut rule.components {
+        component.key = component.key.trim().to_string();
+        if component.key.is_empty() || component.key == "academic" {
+            return Err(format!("测评规则 {} 含有无效的加分项 key", rule.id));
+        }
+        if !keys.insert(component.key.clone()) {
+            return Err(format!(
+                "测评规则 {} 的加分项重复: {}",
+                rule.id, component.key
+            ));
+        }
+        if !valid_weight(component.weight)
+            || component.cap.is_some_and(|cap| !valid_weight(cap))
+            || component
+                .full_score
+                .is_some_and(|full| !full.is_finite() || full <= 0.0)
+        {
+            return Err(format!(
+                "测评规则 {} 的加分项 {} 权重、上限或满分无效",
+                rule.id, component.key
+            ));
+        }
+        if component.label.trim().is_empty() {
+            component.label = component.key.clone();
+        }
+    }
+    if rule.total_cap.is_some_and(|cap| !valid_weight(cap)) || !valid_weight(rule.min_credits) {
+        return Err(format!("测评规则 {} 的总分上限或最低学分无效", rule.id));
+    }
+    if rule
+        .tiers
+        .iter()
+        .any(|tier| tier.id.trim().is_empty() || tier.name.trim().is_empty())
+    {
+        return Err(format!("测评规则 {} 的奖项缺少 id 或名称", rule.id));
+    }
+    Ok(rule)
+}
+
+/// Presets followed by every readable user rule, with unreadable files
+/// reported rather than dropped silently.
+pub fn load_rules(app: &AppHandle) -> (Vec<EvaluationRule>, Vec<String>) {
+    preset_store::load(app)
+}
+
+pub fn import_rule(
+    app: &AppHandle,
+    input: EvaluationRuleImportInput,
+) -> Result<EvaluationRule, String> {
+    preset_store::import(app, input.content, input.path)
+}
+
+pub fn delete_rule(app: &AppHandle, id: &str) -> Result<(), String> {
+    preset_store::delete::<EvaluationRule>(app, id)
+}
+
+fn semester_of(grade: &Value) -> &str {
+    grade
+        .get("semesterName")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+}
+
+/// Regular semesters selected by the window, oldest first. 其他/认定 rows
+/// never fall inside a window.
+pub fn select_terms(grades: &[Value], window: &TermWindow) -> Vec<String> {
+    let available = grades
+        .iter()
+        .map(semester_of)
+        .filter(|name| semester_rank(name) < 9_999_999)
+        .map(|name| (semester_rank(name), name.to_string()))
+        .collect::<BTreeSet<_>>();
+
+    if !window.terms.is_empty() {
+        return available
+            .into_iter()
+            .filter(|(_, name)| window.terms.contains(name))
+            .map(|(_, name)| name)
+            .collect();
+    }
+
+    let from = window.from.as_deref().map(semester_rank);
+    let to = window.to.as_deref().map(semester_rank);
+    let mut terms = available
+        .into_iter()
+        .filter(|(rank, _)| from.is_none_or(|from| *rank >= from))
+        .filter(|(rank, _)| to.is_none_or(|to| *rank <= to))
+        .map(|(_, name)| name)
+        .collect::<Vec<_>>();
+    if let Some(recent) = window.recent.filter(|recent| *recent > 0) {
+        let skip = terms.len().saturating_sub(recent);
+        terms.drain(..skip);
+    }
+    terms
+}
+
+fn is_failed(grade: &Value) -> bool {
+    grade
+        .get("creditIncluded")
+        .and_then(Value::as_bool)
+        .unwrap_or(false)
+        && grade.get("credit").and_then(Value::as_f64).unwrap_or(0.0) > 0.0
+        && grade
+            .get("earnedCredit")
+            .and_then(Value::as_f64)
+            .unwrap_or(0.0)
+            <= 0.0
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn evaluate(
+    rule: &EvaluationRule,
+    window: &TermWindow,
+    grades: &[Value],
+    scale: &GpaScale,
+    major_course_ids: &HashSet<String>,
+    selection: &RetakeSelection,
+    practice: Option<&PracticeScores>,
+    bonuses: &HashMap<String, f64>,
+) -> EvaluationResult {
+    let mut warnings = Vec::new();
+    let terms = select_terms(grades, window);
+    if terms.is_empty() {
+        warnings.push("所选学期范围内没有成绩".to_string());
+    }
+
+    let in_window = grades
+        .iter()
+        .filter(|grade| terms.iter().any(|term| term == semester_of(grade)))
+        .cloned()
+        .collect::<Vec<_>>();
+    let failed_courses = in_window
+        .iter()
+        .filter(|grade| is_failed(grade))
+        .map(|grade| {
+            grade
+                .get("kcmc")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string()
+        })
+        .collect::<Vec<_>>();
+    let counted = if rule.exclude_failed {
+        in_window
+            .iter()
+            .filter(|grade| !is_failed(grade))
+            .cloned()
+            .collect::<Vec<_>>()
+    } else {
+        in_window
+    };
+
+    let summary = compute_gpa_by_policy(
+        &counted,
+        major_course_ids,
+        selection,
+        std::slice::from_ref(scale),
+    );
+    let scale_gpa = summary.scales.first();
+    let gpa = match rule.academic.scope {
+        AcademicScope::Overall => scale_gpa.map(|item| item.gpa),
+        AcademicScope::Major => scale_gpa.map(|item| item.major_gpa),
+    }
+    .unwrap_or_default();
+    if rule.academic.scope == AcademicScope::Major && major_course_ids.is_empty() {
+        warnings.push("规则按主修绩点计算，但没有主修课程列表".to_string());
+    }
+
+    let academic_normalized = gpa / scale.max_points * 100.0;
+    let mut items = vec![EvaluationItem {
+        key: "academic".to_string(),
+        label: "学业成绩".to_string(),
+        raw: gpa,
+        capped: gpa,
+        normalized: round2(academic_normalized),
+        weight: rule.academic.weight,
+        score: round2(academic_normalized * rule.academic.weight),
+        note: format!("{} / {}", scale.name, scale.max_points),
+    }];
+
+    for component in &rule.components {
+        let practice_value = match component.key.as_str() {
+            "pt2" => practice.map(|scores| scores.pt2),
+            "pt3" => practice.map(|scores| scores.pt3),
+            "pt4" => practice.map(|scores| scores.pt4),
+            _ => None,
+        };
+        let (raw, mut note) = match practice_value.or_else(|| bonuses.get(&component.key).copied())
+        {
+            Some(value) => (value, String::new()),
+            None => {
+                warnings.push(format!("缺少「{}」的分数，按 0 计", component.label));
+                (0.0, "未提供".to_string())
+            }
+        };
+        let capped = component.cap.map_or(raw, |cap| raw.min(cap)).max(0.0);
+        if capped < raw {
+            note = format!("超出上限 {}", component.cap.unwrap_or_default());
+        }
+        let normalized = component
+            .full_score
+            .map_or(capped, |full| capped / full * 100.0);
+        items.push(EvaluationItem {
+            key: component.key.clone(),
+            label: component.label.clone(),
+            raw,
+            capped,
+            normalized: round2(normalized),
+            weight: component.weight,
+            score: round2(normalized * component.weight),
+            note,
+        });
+    }
+
+    let mut total = items.iter().map(|item| item.score).sum::<f64>();
+    if let Some(cap) = rule.total_cap.filter(|cap| total > *cap) {
+        warnings.push(format!("总分 {:.2} 超出上限，按 {cap} 计", total));
+        total = cap;
+    }
+    let total = round2(total);
+
+    let tiers = rule
+        .tiers
+        .iter()
+        .map(|tier| {
+            let mut reasons = Vec::new();
+            if summary.total_credits < rule.min_credits {
+                reasons.push(format!(
+                    "获得学分 {} 低于 {}",
+                    summary.total_credits, rule.min_credits
+                ));
+            }
+            if let Some(min) = tier.min_score.filter(|min| total < *min) {
+                reasons.push(format!("综合分 {total:.2} 低于 {min}"));
+            }
+            if let Some(min) = tier.min_gpa.filter(|min| gpa < *min) {
+                reasons.push(format!("绩点 {gpa:.2} 低于 {min}"));
+            }
+            if let Some(max) = tier.max_failed.filter(|max| failed_courses.len() > *max) {
+                reasons.push(format!(
+                    "不及格课程 {} 门，超过 {max} 门",
+                    failed_courses.len()
+                ));
+            }
+            TierResult {
+                id: tier.id.clone(),
+                name: tier.name.clone(),
+                eligible: reasons.is_empty(),
+                reasons,
+            }
+        })
+        .collect();
+
+    EvaluationResult {
+        rule_id: rule.id.clone(),
+        rule_name: rule.name.clone(),
+        terms,
+        scale_id: scale.id.clone(),
+        scale_name: scale.name.clone(),
+        gpa,
+        max_points: scale.max_points,
+        credits: summary.total_credits,
+        failed_courses,
+        items,
+        total,
+        tiers,
+        warnings,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{evaluate, select_terms, EvaluationRule, TermWindow};
+    use crate::gpa::{enrich_grade, RetakePolicy};
+    use crate::gpa_scale::GpaScale;
+    use crate::preset_store::presets;
+    use crate::zdbk::PracticeScores;
+    use serde_json::json;
+    use std::collections::{HashMap, HashSet};
+
+    fn grade(cj: &str, xf: f64, xkkh: &str) -> serde_json::Value {
+        enrich_grade(&json!({ "cj": cj, "xf": xf, "xkkh": xkkh, "kcmc": xkkh }))
+    }
+
+    #[test]
+    fn evaluation_weights_window_and_tiers() {
+        let grades = vec![
+            grade("95", 20.0, "(2022-2023-2)-A-1"),
+            grade("95", 16.0, "(2023-2024-1)-B-1"),
+            grade("85", 16.0, "(2023-2024-2)-C-1"),
+            grade("不及格", 2.0, "(2023-2024-2)-D-1"),
+        ];
+        let window = TermWindow {
+            recent: Some(2),
+            ..TermWindow::default()
+        };
+        assert_eq!(
+            select_terms(&grades, &window),
+            vec!["2023-2024-1", "2023-2024-2"]
+        );
+
+        let rule = presets::<EvaluationRule>()
+            .into_iter()
+            .find(|rule| rule.id == "zju-comprehensive")
+            .unwrap();
+        let scale = presets::<GpaScale>()
+            .into_iter()
+            .find(|scale| scale.id == "zju-5")
+            .unwrap();
+        let practice = PracticeScores {
+            pt2: 12.0,
+            pt3: 5.0,
+            pt4: 0.0,
+        };
+        let result = evaluate(
+            &rule,
+            &rule.term_window,
+            &grades,
+            &scale,
+            &HashSet::new(),
+            &RetakePolicy::First.into(),
+            Some(&practice),
+            &HashMap::new(),
+        );
+
+        assert_eq!(result.credits, 32.0);
+        assert_eq!(result.failed_courses, vec!["(2023-2024-2)-D-1"]);
+        let pt2 = result.items.iter().find(|item| item.key == "pt2").unwrap();
+        assert_eq!(pt2.capped, 10.0);
+        assert_eq!(pt2.score, 10.0);
+        assert!(result.tiers.iter().all(|tier| !tier.eligible));
+        assert!(result.tiers[2]
+            .reasons
+            .iter()
+            .any(|reason| reason.contains("不及格")));
+    }
+}
@@ -0,0 +1,272 @@
+use crate::term::{NormalizedTimetableSession, SessionTimeSlot, TermDescriptor};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+const SHARE_CODE_PREFIX: &str = "CLT1:";
+const TERM_WEEKS: u8 = 16;
+
+/// One busy block of a shared timetable. Course names, teachers, rooms and
+/// xkkh are deliberately dropped; only the occupied periods survive.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BusyBlock {
+    /// Day of week, 1 = Monday.
+    pub d: u8,
+    /// First and last occupied period.
+    pub p: (u8, u8),
+    /// Bit `n - 1` is set when the block meets in teaching week `n`.
+    /// Zero means "every week allowed by the flags".
+    pub w: u32,
+    /// Flags in the order odd, even, first half, second half.
+    pub f: (bool, bool, bool, bool),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TimetableShare {
+    pub term: String,
+    #[serde(default)]
+    pub label: String,
+    pub blocks: Vec<BusyBlock>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimetableShareExportInput {
+    pub term: TermDescriptor,
+    pub sessions: Vec<NormalizedTimetableSession>,
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommonFreeTimeInput {
+    pub codes: Vec<String>,
+    pub weeks: Option<Vec<u8>>,
+    pub include_weekends: Option<bool>,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FreeSlot {
+    pub start_period: u8,
+    pub end_period: u8,
+    pub start: String,
+    pub end: String,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct FreeDay {
+    pub week: u8,
+    pub day_of_week: u8,
+    pub date: Option<String>,
+    pub slots: Vec<FreeSlot>,
+}
+
+pub fn build_share(
+    term: &TermDescriptor,
+    sessions: &[NormalizedTimetableSession],
+    label: &str,
+) -> TimetableShare {
+    let mut blocks = sessions
+        .iter()
+        .map(|session| BusyBlock {
+            d: session.day_of_week,
+            p: (session.start_period, session.end_period),
+            w: session
+                .week_numbers
+                .iter()
+                .filter(|week| (1..=32).contains(*week))
+                .fold(0_u32, |mask, week| mask | (1 << (week - 1))),
+            f: (
+                session.odd_week,
+                session.even_week,
+                session.first_half,
+                session.second_half,
+            ),
+        })
+        .collect::<Vec<_>>();
+    blocks.sort_by_key(|block| (block.d, block.p, block.w));
+    blocks.dedup();
+
+    TimetableShare {
+        term: term.name.clone(),
+        label: label.trim().to_string(),
+        blocks,
+    }
+}
+
+pub fn encode_share(share: &TimetableShare) -> Result<String, String> {
+    let json = serde_json::to_vec(share).map_err(|error| format!("课表分享码生成失败: {error}"))?;
+    Ok(format!(
+        "{SHARE_CODE_PREFIX}{}",
+        URL_SAFE_NO_PAD.encode(json)
+    ))
+}
+
+pub fn decode_share(code: &str) -> Result<TimetableShare, String> {
+    let trimmed = code.trim();
+    let payload = trimmed
+        .strip_prefix(SHARE_CODE_PREFIX)
+        .ok_or_else(|| "无法识别的课表分享码".to_string())?;
+    let bytes = URL_SAFE_NO_PAD
+        .decode(payload.trim_end_matches('='))
+        .map_err(|error| format!("课表分享码已损坏: {error}"))?;
+    serde_json::from_slice::<TimetableShare>(&bytes)
+        .map_err(|error| format!("课表分享码内容无效: {error}"))
+}
+
+fn block_meets_in_week(block: &BusyBlock, week: u8) -> bool {
+    let (odd, even, first_half, second_half) = block.f;
+    if block.w != 0 && block.w & (1 << (week - 1)) == 0 {
+        return false;
+    }
+    if odd && week.is_multiple_of(2) {
+        return false;
+    }
+    if even && week % 2 == 1 {
+        return false;
+    }
+    if first_half != second_half {
+        let in_first_half = week <= TERM_WEEKS / 2;
+        if first_half != in_first_half {
+            return false;
+        }
+    }
+    true
+}
+
+/// Intersect the free periods of every share for each requested week and day.
+/// Periods come from the term's `session_times`, so a night-class-free term
+/// config also bounds the search.
+pub fn find_common_free_slots(
+    shares: &[TimetableShare],
+    session_times: &[SessionTimeSlot],
+    weeks: &[u8],
+    days: &[u8],
+    anchor: Option<NaiveDate>,
+) -> Vec<FreeDay> {
+    let mut result = Vec::new();
+
+    for week in weeks
+        .iter()
+        .copied()
+        .filter(|week| (1..=TERM_WEEKS).contains(week))
+    {
+        for day in days.iter().copied() {
+            let busy = |period: u8| {
+                shares.iter().any(|share| {
+                    share.blocks.iter().any(|block| {
+                        block.d == day
+                            && (block.p.0..=block.p.1).contains(&period)
+                            && block_meets_in_week(block, week)
+                    })
+                })
+            };
+
+            let mut slots = Vec::<FreeSlot>::new();
+            for slot in session_times {
+                if busy(slot.index) {
+                    continue;
+                }
+                match slots.last_mut() {
+                    Some(open) if open.end_period + 1 == slot.index => {
+                        open.end_period = slot.index;
+                        open.end = slot.end.clone();
+                    }
+                    _ => slots.push(FreeSlot {
+                        start_period: slot.index,
+                        end_period: slot.index,
+                        start: slot.start.clone(),
+                        end: slot.end.clone(),
+                    }),
+                }
+            }
+
+            result.push(FreeDay {
+                week,
+                day_of_week: day,
+                date: anchor.map(|monday| {
+                    (monday + Duration::days(i64::from(week - 1) * 7 + i64::from(day - 1)))
+                        .format("%Y-%m-%d")
+                        .to_string()
+                }),
+                slots,
+            });
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{build_share, decode_share, encode_share, find_common_free_slots};
+    use crate::term::{
+        default_term_time_config, descriptor_from_parts, normalize_timetable_sessions,
+    };
+    use serde_json::json;
+
+    #[test]
+    fn share_code_round_trips_without_course_details() {
+        let term = descriptor_from_parts("2024", "1");
+        let sessions = normalize_timetable_sessions(
+            &term,
+            &[json!({
+                "xkkh": "(2024-2025-1)-211G0001-01",
+                "kcmc": "程序设计",
+                "jsxm": "某老师",
+                "xqj": "1",
+                "jcs": "1-2",
+                "zcs": "1-8",
+                "xxq": "秋冬",
+            })],
+        );
+        let code = encode_share(&build_share(&term, &sessions, "小明")).unwrap();
+        let decoded = decode_share(&code).unwrap();
+        assert_eq!(decoded.term, "2024-2025-1");
+        assert_eq!(decoded.label, "小明");
+        assert_eq!(decoded.blocks.len(), 1);
+    }
+
+    #[test]
+    fn free_slots_honor_odd_weeks_and_halves() {
+        let term = descriptor_from_parts("2024", "1");
+        let alice = normalize_timetable_sessions(
+            &term,
+            &[json!({
+                "xkkh": "(2024-2025-1)-A-01",
+                "kcmc": "A",
+                "xqj": "1",
+                "jcs": "1-2",
+                "zcs": "1-8",
+                "xxq": "秋",
+                "dsz": "1",
+            })],
+        );
+        let bob = normalize_timetable_sessions(
+            &term,
+            &[json!({
+                "xkkh": "(2024-2025-1)-B-01",
+                "kcmc": "B",
+                "xqj": "1",
+                "jcs": "3-5",
+                "zcs": "1-8",
+                "xxq": "冬",
+            })],
+        );
+        let shares = vec![
+            build_share(&term, &alice, "alice"),
+            build_share(&term, &bob, "bob"),
+        ];
+        let config = default_term_time_config();
+        let days = find_common_free_slots(&shares, &config.session_times, &[1, 2, 9], &[1], None);
+
+        assert_eq!(days[0].slots[0].start_period, 3);
+        assert_eq!(days[1].slots[0].start_period, 1);
+        assert_eq!(days[1].slots[0].end_period, 14);
+        assert_eq!(days[2].slots[0].start_period, 1);
+        assert_eq!(days[2].slots[0].end_period, 2);
+        assert_eq!(days[2].slots[1].start_period, 6);
+    }
+}
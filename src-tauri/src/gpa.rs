@@ -1,23 +1,85 @@
+use crate::gpa_scale::{GpaScale, ScaleGpa};
 use regex::Regex;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub enum RetakePolicy {
+    #[default]
     First,
     Highest,
+    Latest,
+    Average,
 }
 
 impl RetakePolicy {
-    pub fn from_str(s: &str) -> Self {
-        match s {
-            "highest" | "best" => RetakePolicy::Highest,
-            _ => RetakePolicy::First,
+    fn describe(self) -> &'static str {
+        match self {
+            RetakePolicy::First => "按首次修读计入",
+            RetakePolicy::Highest => "按最高成绩计入",
+            RetakePolicy::Latest => "按最近一次修读计入",
+            RetakePolicy::Average => "按所有修读成绩平均计入",
+        }
+    }
+}
+
+impl std::str::FromStr for RetakePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "first" => Ok(RetakePolicy::First),
+            "highest" | "best" => Ok(RetakePolicy::Highest),
+            "latest" | "last" => Ok(RetakePolicy::Latest),
+            "average" | "avg" => Ok(RetakePolicy::Average),
+            other => Err(format!("不支持的重修计算方式: {other}")),
+        }
+    }
+}
+
+/// Per-`retakeKey` adjustment on top of the global policy.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetakeOverride {
+    /// Count exactly this attempt (by xkkh), whatever the policy says.
+    pub pick: Option<String>,
+    /// Attempts (by xkkh) that were officially voided and must not count.
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Use a different policy for this course only.
+    pub policy: Option<RetakePolicy>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct RetakeSelection {
+    pub policy: RetakePolicy,
+    pub overrides: HashMap<String, RetakeOverride>,
+}
+
+impl From<RetakePolicy> for RetakeSelection {
+    fn from(policy: RetakePolicy) -> Self {
+        Self {
+            policy,
+            overrides: HashMap::new(),
         }
     }
 }
 
+/// Which attempt of a repeated (or overridden) course ended up counting.
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RetakeChoice {
+    pub retake_key: String,
+    pub course_name: String,
+    pub policy: RetakePolicy,
+    pub attempts: Vec<String>,
+    pub chosen: Vec<String>,
+    pub excluded: Vec<String>,
+    pub reason: String,
+}
+
 #[derive(Clone, Debug, Serialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct GpaSummary {
@@ -29,6 +91,11 @@ pub struct GpaSummary {
     pub major_gpa: f64,
     pub major_gpa_legacy: f64,
     pub major_credits: f64,
+    /// One entry per requested named scale, in the order they were passed.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub scales: Vec<ScaleGpa>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub retake_choices: Vec<RetakeChoice>,
 }
 
 impl Default for GpaSummary {
@@ -42,14 +109,18 @@ impl Default for GpaSummary {
             major_gpa: 0.0,
             major_gpa_legacy: 0.0,
             major_credits: 0.0,
+            scales: Vec::new(),
+            retake_choices: Vec::new(),
         }
     }
 }
 
 #[derive(Clone, Debug)]
 struct GradeEntry {
+    index: usize,
     xkkh: String,
     kcdm: String,
+    kcmc: String,
     key: String,
     cj: String,
     credit: f64,
@@ -58,6 +129,8 @@ struct GradeEntry {
     four_point_legacy: f64,
     hundred_point: f64,
     sem_rank: i64,
+    earns_credit: bool,
+    counts_for_gpa: bool,
 }
 
 pub fn extract_semester_name(grade: &Value) -> Option<String> {
@@ -189,11 +262,16 @@ pub fn apply_simulated_score(grade: &mut Value, score: f64) {
 pub fn compute_gpa_by_policy(
     grades: &[Value],
     major_course_ids: &HashSet<String>,
-    policy: RetakePolicy,
+    selection: &RetakeSelection,
+    scales: &[GpaScale],
 ) -> GpaSummary {
-    let valid_entries = select_retake_entries(
-        grades.iter().map(grade_to_entry).collect::<Vec<_>>(),
-        policy,
+    let (valid_entries, retake_choices) = select_retake_entries(
+        grades
+            .iter()
+            .enumerate()
+            .map(grade_to_entry)
+            .collect::<Vec<_>>(),
+        selection,
     );
 
     let mut total_earned_credits = 0.0;
@@ -207,13 +285,14 @@ pub fn compute_gpa_by_policy(
     let mut major_gpa_credits = 0.0;
     let mut major_weighted_four = 0.0;
     let mut major_weighted_legacy = 0.0;
+    let mut scale_weighted = vec![(0.0, 0.0); scales.len()];
 
     for entry in valid_entries {
         if entry.credit <= 0.0 {
             continue;
         }
 
-        let (earns_credit, counts_for_gpa) = classify_grade(&entry);
+        let (earns_credit, counts_for_gpa) = (entry.earns_credit, entry.counts_for_gpa);
         let is_major =
             major_course_ids.contains(&entry.xkkh) || major_course_ids.contains(&entry.kcdm);
 
@@ -236,6 +315,14 @@ pub fn compute_gpa_by_policy(
                 major_weighted_four += entry.credit * entry.four_point;
                 major_weighted_legacy += entry.credit * entry.four_point_legacy;
             }
+
+            for (scale, (overall, major)) in scales.iter().zip(scale_weighted.iter_mut()) {
+                let points = scale.points_for(&entry.cj, entry.hundred_point, entry.five_point);
+                *overall += entry.credit * points;
+                if is_major {
+                    *major += entry.credit * points;
+                }
+            }
         }
     }
 
@@ -248,46 +335,197 @@ pub fn compute_gpa_by_policy(
         major_gpa: safe_div(major_weighted_four, major_gpa_credits),
         major_gpa_legacy: safe_div(major_weighted_legacy, major_gpa_credits),
         major_credits: major_earned_credits,
+        scales: scales
+            .iter()
+            .zip(scale_weighted)
+            .map(|(scale, (overall, major))| ScaleGpa {
+                id: scale.id.clone(),
+                name: scale.name.clone(),
+                max_points: scale.max_points,
+                gpa: safe_div(overall, gpa_credits),
+                major_gpa: safe_div(major, major_gpa_credits),
+            })
+            .collect(),
+        retake_choices,
     }
 }
 
-fn select_retake_entries(entries: Vec<GradeEntry>, policy: RetakePolicy) -> Vec<GradeEntry> {
-    let mut groups = std::collections::HashMap::<String, Vec<GradeEntry>>::new();
+/// A transcript row that survived retake selection, pointing back into the
+/// grade list by `index`. Averaged retakes point at their latest attempt.
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CountedGrade {
+    pub index: usize,
+    pub credit: f64,
+    pub five_point: f64,
+    pub hundred_point: f64,
+    pub earns_credit: bool,
+    pub counts_for_gpa: bool,
+}
+
+pub fn select_counted_grades(grades: &[Value], selection: &RetakeSelection) -> Vec<CountedGrade> {
+    let (entries, _) = select_retake_entries(
+        grades
+            .iter()
+            .enumerate()
+            .map(grade_to_entry)
+            .collect::<Vec<_>>(),
+        selection,
+    );
+    let mut counted = entries
+        .into_iter()
+        .map(|entry| CountedGrade {
+            index: entry.index,
+            credit: entry.credit,
+            five_point: entry.five_point,
+            hundred_point: entry.hundred_point,
+            earns_credit: entry.earns_credit,
+            counts_for_gpa: entry.counts_for_gpa,
+        })
+        .collect::<Vec<_>>();
+    counted.sort_by_key(|grade| grade.index);
+    counted
+}
+
+fn select_retake_entries(
+    entries: Vec<GradeEntry>,
+    selection: &RetakeSelection,
+) -> (Vec<GradeEntry>, Vec<RetakeChoice>) {
+    let mut groups = HashMap::<String, Vec<GradeEntry>>::new();
     for entry in entries {
         groups.entry(entry.key.clone()).or_default().push(entry);
     }
 
     let mut selected = Vec::new();
-    for (_, mut group) in groups {
-        if group.len() == 1 {
+    let mut choices = Vec::new();
+    for (key, mut group) in groups {
+        let rule = selection.overrides.get(&key);
+        if group.len() == 1 && rule.is_none() {
             selected.push(group.remove(0));
             continue;
         }
 
-        let picked = match policy {
-            RetakePolicy::First => group
-                .into_iter()
-                .min_by(|left, right| left.sem_rank.cmp(&right.sem_rank))
-                .unwrap(),
-            RetakePolicy::Highest => group
-                .into_iter()
-                .max_by(|left, right| {
-                    left.five_point
-                        .partial_cmp(&right.five_point)
-                        .unwrap_or(std::cmp::Ordering::Equal)
-                        .then_with(|| {
-                            left.hundred_point
-                                .partial_cmp(&right.hundred_point)
-                                .unwrap_or(std::cmp::Ordering::Equal)
-                        })
-                        .then_with(|| right.sem_rank.cmp(&left.sem_rank))
-                })
-                .unwrap(),
+        group.sort_by_key(|entry| entry.sem_rank);
+        let attempts = group
+            .iter()
+            .map(|entry| entry.xkkh.clone())
+            .collect::<Vec<_>>();
+        let course_name = group
+            .first()
+            .map(|entry| entry.kcmc.clone())
+            .unwrap_or_default();
+        let excluded = rule
+            .map(|rule| {
+                attempts
+                    .iter()
+                    .filter(|xkkh| rule.exclude.contains(xkkh))
+                    .cloned()
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        group.retain(|entry| !excluded.contains(&entry.xkkh));
+        let policy = rule
+            .and_then(|rule| rule.policy)
+            .unwrap_or(selection.policy);
+
+        let mut reasons = Vec::new();
+        if !excluded.is_empty() {
+            reasons.push(format!("已排除作废的修读 {}", excluded.join("、")));
+        }
+
+        let pinned = rule
+            .and_then(|rule| rule.pick.as_ref())
+            .and_then(|pick| group.iter().position(|entry| &entry.xkkh == pick));
+        let (picked, chosen) = if group.is_empty() {
+            reasons.push("所有修读均已排除，不计入".to_string());
+            (None, Vec::new())
+        } else if let Some(index) = pinned {
+            let entry = group.swap_remove(index);
+            reasons.push(format!("手动指定计入 {}", entry.xkkh));
+            let chosen = vec![entry.xkkh.clone()];
+            (Some(entry), chosen)
+        } else {
+            if let Some(pick) = rule.and_then(|rule| rule.pick.as_ref()) {
+                reasons.push(format!("指定的修读 {pick} 不存在"));
+            }
+            reasons.push(policy.describe().to_string());
+            let averaged = group
+                .iter()
+                .filter(|entry| entry.counts_for_gpa)
+                .map(|entry| entry.xkkh.clone())
+                .collect::<Vec<_>>();
+            let picked = pick_by_policy(group, policy);
+            let chosen = match (&picked, policy) {
+                (Some(_), RetakePolicy::Average) if !averaged.is_empty() => averaged,
+                (Some(entry), _) => vec![entry.xkkh.clone()],
+                (None, _) => Vec::new(),
+            };
+            (picked, chosen)
         };
-        selected.push(picked);
+
+        choices.push(RetakeChoice {
+            retake_key: key,
+            course_name,
+            policy,
+            attempts,
+            chosen,
+            excluded,
+            reason: reasons.join("；"),
+        });
+        selected.extend(picked);
     }
 
-    selected
+    choices.sort_by(|left, right| left.retake_key.cmp(&right.retake_key));
+    (selected, choices)
+}
+
+/// `group` is sorted by semester and never empty.
+fn pick_by_policy(mut group: Vec<GradeEntry>, policy: RetakePolicy) -> Option<GradeEntry> {
+    match policy {
+        RetakePolicy::First => Some(group.remove(0)),
+        RetakePolicy::Latest => group.pop(),
+        RetakePolicy::Highest => group.into_iter().max_by(|left, right| {
+            left.five_point
+                .partial_cmp(&right.five_point)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| {
+                    left.hundred_point
+                        .partial_cmp(&right.hundred_point)
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                })
+                .then_with(|| right.sem_rank.cmp(&left.sem_rank))
+        }),
+        RetakePolicy::Average => {
+            let counted = group
+                .iter()
+                .filter(|entry| entry.counts_for_gpa)
+                .collect::<Vec<_>>();
+            if counted.is_empty() {
+                // Nothing to average (e.g. pass/fail attempts): the latest result stands.
+                return group.pop();
+            }
+
+            let count = counted.len() as f64;
+            let mean = |value: fn(&GradeEntry) -> f64| {
+                counted.iter().map(|entry| value(entry)).sum::<f64>() / count
+            };
+            let five_point = mean(|entry| entry.five_point);
+            let four_point = mean(|entry| entry.four_point);
+            let four_point_legacy = mean(|entry| entry.four_point_legacy);
+            let hundred_point = mean(|entry| entry.hundred_point);
+            let earns_credit = group.iter().any(|entry| entry.earns_credit);
+
+            let mut averaged = group.pop()?;
+            averaged.five_point = five_point;
+            averaged.four_point = four_point;
+            averaged.four_point_legacy = four_point_legacy;
+            averaged.hundred_point = hundred_point;
+            averaged.cj = String::new();
+            averaged.earns_credit = earns_credit;
+            averaged.counts_for_gpa = true;
+            Some(averaged)
+        }
+    }
 }
 
 fn classify_grade(entry: &GradeEntry) -> (bool, bool) {
@@ -303,7 +541,7 @@ fn classify_grade(entry: &GradeEntry) -> (bool, bool) {
     })
 }
 
-fn grade_to_entry(grade: &Value) -> GradeEntry {
+fn grade_to_entry((index, grade): (usize, &Value)) -> GradeEntry {
     let xkkh = grade
         .get("xkkh")
         .and_then(Value::as_str)
@@ -326,9 +564,11 @@ fn grade_to_entry(grade: &Value) -> GradeEntry {
         .or_else(|| extract_semester_name(grade))
         .unwrap_or_else(|| "9999-9999-99".to_string());
 
-    GradeEntry {
+    let mut entry = GradeEntry {
+        index,
         xkkh: xkkh.clone(),
         kcdm: kcdm.clone(),
+        kcmc: kcmc.clone(),
         key: grade
             .get("retakeKey")
             .and_then(Value::as_str)
@@ -348,10 +588,14 @@ fn grade_to_entry(grade: &Value) -> GradeEntry {
         four_point_legacy: parse_f64(grade.get("fourPointLegacy")).unwrap_or(0.0),
         hundred_point: parse_f64(grade.get("hundredPoint")).unwrap_or(0.0),
         sem_rank: semester_rank(&sem_name),
-    }
+        earns_credit: false,
+        counts_for_gpa: false,
+    };
+    (entry.earns_credit, entry.counts_for_gpa) = classify_grade(&entry);
+    entry
 }
 
-fn normalize_semester_code(code: &str) -> Option<&'static str> {
+pub fn normalize_semester_code(code: &str) -> Option<&'static str> {
     match code.trim() {
         "1" | "3" => Some("1"),
         "2" | "12" => Some("2"),
@@ -443,7 +687,30 @@ fn parse_f64(value: Option<&Value>) -> Option<f64> {
     })
 }
 
-fn parse_score(s: &str) -> f64 {
+/// How a zdbk grade string was turned into a hundred-point score.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GradeText {
+    /// A letter, level or status grade from the known table.
+    Known(f64),
+    Numeric(f64),
+    /// Not a known label; a number was pulled out of the text.
+    Extracted(f64),
+    Empty,
+    Unrecognized,
+}
+
+impl GradeText {
+    pub fn score(self) -> f64 {
+        match self {
+            GradeText::Known(value) | GradeText::Numeric(value) | GradeText::Extracted(value) => {
+                value
+            }
+            GradeText::Empty | GradeText::Unrecognized => 0.0,
+        }
+    }
+}
+
+pub fn interpret_grade_text(s: &str) -> GradeText {
     let mapping = [
         ("A+", 95.0),
         ("A", 90.0),
@@ -471,14 +738,18 @@ fn parse_score(s: &str) -> f64 {
         ("无效", 0.0),
     ];
 
+    let s = s.trim();
+    if s.is_empty() {
+        return GradeText::Empty;
+    }
     for (key, value) in mapping {
         if s == key {
-            return value;
+            return GradeText::Known(value);
         }
     }
 
     if let Ok(value) = s.parse::<f64>() {
-        return value;
+        return GradeText::Numeric(value);
     }
 
     let re = Regex::new(r"\d+(?:\.\d+)?").unwrap();
@@ -487,10 +758,15 @@ fn parse_score(s: &str) -> f64 {
             caps.get(0)
                 .and_then(|value| value.as_str().parse::<f64>().ok())
         })
-        .unwrap_or(0.0)
+        .map(GradeText::Extracted)
+        .unwrap_or(GradeText::Unrecognized)
 }
 
-fn to_five_point(score: f64) -> f64 {
+fn parse_score(s: &str) -> f64 {
+    interpret_grade_text(s).score()
+}
+
+pub fn to_five_point(score: f64) -> f64 {
     if score >= 95.0 {
         5.0
     } else if score >= 92.0 {
@@ -546,7 +822,7 @@ fn to_four_point_legacy(five_point: f64) -> f64 {
     }
 }
 
-fn semester_rank(name: &str) -> i64 {
+pub fn semester_rank(name: &str) -> i64 {
     let re = Regex::new(r"^(\d{4})-(\d{4})-(\d+)$").unwrap();
     if let Some(caps) = re.captures(name) {
         let year = caps
@@ -581,8 +857,10 @@ impl<T> Pipe for T {}
 mod tests {
     use super::{
         canonical_course_key, compute_gpa_by_policy, enrich_grade, extract_semester_name,
-        GpaSummary, RetakePolicy,
+        GpaSummary, RetakeOverride, RetakePolicy, RetakeSelection,
     };
+    use crate::gpa_scale::GpaScale;
+    use crate::preset_store::presets;
     use serde_json::json;
     use std::collections::HashSet;
 
@@ -599,7 +877,7 @@ mod tests {
     }
 
     fn summary(grades: Vec<serde_json::Value>) -> GpaSummary {
-        compute_gpa_by_policy(&grades, &HashSet::new(), RetakePolicy::First)
+        compute_gpa_by_policy(&grades, &HashSet::new(), &RetakePolicy::First.into(), &[])
     }
 
     #[test]
@@ -625,9 +903,10 @@ mod tests {
         }));
 
         let grades = vec![first.clone(), retake.clone()];
-        let first_summary = compute_gpa_by_policy(&grades, &HashSet::new(), RetakePolicy::First);
+        let first_summary =
+            compute_gpa_by_policy(&grades, &HashSet::new(), &RetakePolicy::First.into(), &[]);
         let highest_summary =
-            compute_gpa_by_policy(&grades, &HashSet::new(), RetakePolicy::Highest);
+            compute_gpa_by_policy(&grades, &HashSet::new(), &RetakePolicy::Highest.into(), &[]);
 
         assert!(first_summary.hundred_point < highest_summary.hundred_point);
         assert_eq!(
@@ -648,8 +927,12 @@ mod tests {
             "xnm": "2024",
             "xqm": "12",
         }));
-        let sum =
-            compute_gpa_by_policy(&vec![fall, spring], &HashSet::new(), RetakePolicy::Highest);
+        let sum = compute_gpa_by_policy(
+            &[fall, spring],
+            &HashSet::new(),
+            &RetakePolicy::Highest.into(),
+            &[],
+        );
         assert_eq!(sum.total_credits, 2.0);
         assert_eq!(sum.five_point, 0.0);
     }
@@ -674,8 +957,83 @@ mod tests {
         let common = g("80", 2.0, "(2024-2025-1)-C-1", "C0000001");
         let mut major_ids = HashSet::new();
         major_ids.insert("M0000001".to_string());
-        let result = compute_gpa_by_policy(&vec![major, common], &major_ids, RetakePolicy::First);
+        let result = compute_gpa_by_policy(
+            &[major, common],
+            &major_ids,
+            &RetakePolicy::First.into(),
+            &[],
+        );
         assert!(result.major_gpa > result.four_point);
         assert_eq!(result.major_credits, 3.0);
     }
+
+    #[test]
+    fn named_scales_are_computed_in_one_pass() {
+        let grades = vec![
+            g("93", 2.0, "(2024-2025-1)-M-1", "M0000001"),
+            g("B+", 2.0, "(2024-2025-1)-C-1", "C0000001"),
+        ];
+        let mut major_ids = HashSet::new();
+        major_ids.insert("M0000001".to_string());
+        let result = compute_gpa_by_policy(
+            &grades,
+            &major_ids,
+            &RetakePolicy::First.into(),
+            &presets::<GpaScale>(),
+        );
+
+        let scale = |id: &str| result.scales.iter().find(|scale| scale.id == id).unwrap();
+        assert_eq!(scale("zju-5").gpa, result.five_point);
+        assert_eq!(scale("zju-4.3").gpa, result.four_point);
+        assert_eq!(scale("wes-4.0").gpa, 3.5);
+        assert_eq!(scale("wes-4.0").major_gpa, 4.0);
+        assert_eq!(scale("percentage").gpa, 88.0);
+    }
+
+    #[test]
+    fn latest_average_and_overrides_explain_their_choice() {
+        let attempt = |cj: &str, xkkh: &str, xqm: &str| {
+            enrich_grade(&json!({
+                "cj": cj,
+                "xf": 2.0,
+                "xkkh": xkkh,
+                "kcdm": "211G0001",
+                "kcmc": "程序设计",
+                "xnm": "2024",
+                "xqm": xqm,
+            }))
+        };
+        let grades = vec![
+            attempt("95", "(2024-2025-1)-211G0001-0001-1", "3"),
+            attempt("60", "(2024-2025-2)-211G0001-0001-1", "12"),
+        ];
+        let set = HashSet::new();
+
+        let latest = compute_gpa_by_policy(&grades, &set, &RetakePolicy::Latest.into(), &[]);
+        assert_eq!(latest.hundred_point, 60.0);
+        assert_eq!(
+            latest.retake_choices[0].chosen,
+            vec!["(2024-2025-2)-211G0001-0001-1"]
+        );
+
+        let average = compute_gpa_by_policy(&grades, &set, &RetakePolicy::Average.into(), &[]);
+        assert_eq!(average.hundred_point, 77.5);
+        assert_eq!(average.total_credits, 2.0);
+        assert_eq!(average.retake_choices[0].chosen.len(), 2);
+
+        let mut selection = RetakeSelection::from(RetakePolicy::Highest);
+        selection.overrides.insert(
+            "211G0001".to_string(),
+            RetakeOverride {
+                exclude: vec!["(2024-2025-1)-211G0001-0001-1".to_string()],
+                ..RetakeOverride::default()
+            },
+        );
+        let voided = compute_gpa_by_policy(&grades, &set, &selection, &[]);
+        assert_eq!(voided.hundred_point, 60.0);
+        assert!(voided.retake_choices[0].reason.contains("已排除"));
+
+        assert!("best".parse::<RetakePolicy>().is_ok());
+        assert!("newest-ish".parse::<RetakePolicy>().is_err());
+    }
 }
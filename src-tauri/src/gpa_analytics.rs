@@ -0,0 +1,282 @@
+use crate::gpa::{
+    compute_gpa_by_policy, extract_semester_name, select_counted_grades, semester_rank, GpaSummary,
+    RetakeSelection,
+};
+use crate::gpa_scale::GpaScale;
+use crate::term::descriptor_from_name;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+
+const OUTLIER_COUNT: usize = 5;
+const HISTOGRAM_BOUNDS: [(f64, f64, &str); 9] = [
+    (0.0, 60.0, "<60"),
+    (60.0, 65.0, "60-64"),
+    (65.0, 70.0, "65-69"),
+    (70.0, 75.0, "70-74"),
+    (75.0, 80.0, "75-79"),
+    (80.0, 85.0, "80-84"),
+    (85.0, 90.0, "85-89"),
+    (90.0, 95.0, "90-94"),
+    (95.0, f64::INFINITY, "95-100"),
+];
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TermAnalytics {
+    pub semester_name: String,
+    pub display_name: String,
+    pub term: GpaSummary,
+    pub cumulative: GpaSummary,
+    pub major: GpaSummary,
+    pub non_major: GpaSummary,
+    pub earned_credits: f64,
+    pub cumulative_credits: f64,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HistogramBucket {
+    pub label: String,
+    pub min: f64,
+    pub max: Option<f64>,
+    pub credits: f64,
+    pub courses: usize,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CourseDeviation {
+    pub xkkh: String,
+    pub course_name: String,
+    pub semester_name: String,
+    pub credit: f64,
+    pub hundred_point: f64,
+    pub five_point: f64,
+    /// Five-point difference from the student's credit-weighted mean.
+    pub delta: f64,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GpaAnalytics {
+    pub terms: Vec<TermAnalytics>,
+    pub histogram: Vec<HistogramBucket>,
+    pub mean_five_point: f64,
+    pub best: Vec<CourseDeviation>,
+    pub worst: Vec<CourseDeviation>,
+}
+
+fn semester_of(grade: &Value) -> String {
+    grade
+        .get("semesterName")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .or_else(|| extract_semester_name(grade))
+        .unwrap_or_else(|| "其他/认定".to_string())
+}
+
+fn is_major(grade: &Value, major_course_ids: &HashSet<String>) -> bool {
+    ["xkkh", "kcdm"].iter().any(|key| {
+        grade
+            .get(*key)
+            .and_then(Value::as_str)
+            .is_some_and(|id| major_course_ids.contains(id))
+    })
+}
+
+fn summarize(
+    grades: &[Value],
+    major_course_ids: &HashSet<String>,
+    selection: &RetakeSelection,
+    scales: &[GpaScale],
+) -> GpaSummary {
+    let mut summary = compute_gpa_by_policy(grades, major_course_ids, selection, scales);
+    summary.retake_choices.clear();
+    summary
+}
+
+pub fn analyze_transcript(
+    grades: &[Value],
+    major_course_ids: &HashSet<String>,
+    selection: &RetakeSelection,
+    scales: &[GpaScale],
+) -> GpaAnalytics {
+    let mut by_term = BTreeMap::<(i64, String), Vec<Value>>::new();
+    for grade in grades {
+        let name = semester_of(grade);
+        by_term
+            .entry((semester_rank(&name), name))
+            .or_default()
+            .push(grade.clone());
+    }
+
+    let mut so_far = Vec::<Value>::new();
+    let mut terms = Vec::with_capacity(by_term.len());
+    for ((_, name), term_grades) in by_term {
+        so_far.extend(term_grades.iter().cloned());
+        let (major, non_major): (Vec<Value>, Vec<Value>) = term_grades
+            .iter()
+            .cloned()
+            .partition(|grade| is_major(grade, major_course_ids));
+
+        let term = summarize(&term_grades, major_course_ids, selection, scales);
+        let cumulative = summarize(&so_far, major_course_ids, selection, scales);
+        terms.push(TermAnalytics {
+            display_name: descriptor_from_name(&name)
+                .map(|descriptor| descriptor.display_name)
+                .unwrap_or_else(|| name.clone()),
+            semester_name: name,
+            earned_credits: term.total_credits,
+            cumulative_credits: cumulative.total_credits,
+            major: summarize(&major, major_course_ids, selection, scales),
+            non_major: summarize(&non_major, &HashSet::new(), selection, scales),
+            term,
+            cumulative,
+        });
+    }
+
+    let counted = select_counted_grades(grades, selection)
+        .into_iter()
+        .filter(|grade| grade.counts_for_gpa && grade.credit > 0.0)
+        .collect::<Vec<_>>();
+
+    let mut histogram = HISTOGRAM_BOUNDS
+        .iter()
+        .map(|(min, max, label)| HistogramBucket {
+            label: label.to_string(),
+            min: *min,
+            max: max.is_finite().then_some(*max),
+            credits: 0.0,
+            courses: 0,
+        })
+        .collect::<Vec<_>>();
+    for grade in &counted {
+        if let Some(bucket) = HISTOGRAM_BOUNDS
+            .iter()
+            .position(|(min, max, _)| grade.hundred_point >= *min && grade.hundred_point < *max)
+        {
+            histogram[bucket].credits += grade.credit;
+            histogram[bucket].courses += 1;
+        }
+    }
+
+    let total_credits = counted.iter().map(|grade| grade.credit).sum::<f64>();
+    let mean_five_point = if total_credits > 0.0 {
+        counted
+            .iter()
+            .map(|grade| grade.credit * grade.five_point)
+            .sum::<f64>()
+            / total_credits
+    } else {
+        0.0
+    };
+
+    let mut deviations = counted
+        .iter()
+        .map(|grade| {
+            let raw = &grades[grade.index];
+            CourseDeviation {
+                xkkh: raw
+                    .get("xkkh")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+                course_name: raw
+                    .get("kcmc")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+                semester_name: semester_of(raw),
+                credit: grade.credit,
+                hundred_point: grade.hundred_point,
+                five_point: grade.five_point,
+                delta: grade.five_point - mean_five_point,
+            }
+        })
+        .collect::<Vec<_>>();
+    deviations.sort_by(|left, right| {
+        right
+            .delta
+            .partial_cmp(&left.delta)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| {
+                right
+                    .hundred_point
+                    .partial_cmp(&left.hundred_point)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .then_with(|| {
+                right
+                    .credit
+                    .partial_cmp(&left.credit)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+    });
+
+    let best = deviations
+        .iter()
+        .filter(|course| course.delta > 0.0)
+        .take(OUTLIER_COUNT)
+        .cloned()
+        .collect();
+    let worst = deviations
+        .iter()
+        .rev()
+        .filter(|course| course.delta < 0.0)
+        .take(OUTLIER_COUNT)
+        .cloned()
+        .collect();
+
+    GpaAnalytics {
+        terms,
+        histogram,
+        mean_five_point,
+        best,
+        worst,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::analyze_transcript;
+    use crate::gpa::{enrich_grade, RetakePolicy};
+    use serde_json::json;
+    use std::collections::HashSet;
+
+    fn grade(cj: &str, xf: f64, xkkh: &str, kcdm: &str) -> serde_json::Value {
+        enrich_grade(&json!({ "cj": cj, "xf": xf, "xkkh": xkkh, "kcdm": kcdm, "kcmc": kcdm }))
+    }
+
+    #[test]
+    fn analytics_accumulate_terms_and_rank_outliers() {
+        let grades = vec![
+            grade("95", 2.0, "(2023-2024-1)-M0000001-1", "M0000001"),
+            grade("70", 2.0, "(2023-2024-1)-C0000001-1", "C0000001"),
+            grade("85", 4.0, "(2023-2024-2)-M0000002-1", "M0000002"),
+            grade("合格", 1.0, "(2023-2024-2)-P0000001-1", "P0000001"),
+        ];
+        let mut major = HashSet::new();
+        major.insert("M0000001".to_string());
+        major.insert("M0000002".to_string());
+
+        let analytics = analyze_transcript(&grades, &major, &RetakePolicy::First.into(), &[]);
+
+        assert_eq!(analytics.terms.len(), 2);
+        assert_eq!(analytics.terms[0].semester_name, "2023-2024-1");
+        assert_eq!(analytics.terms[0].earned_credits, 4.0);
+        assert_eq!(analytics.terms[1].cumulative_credits, 9.0);
+        assert_eq!(analytics.terms[0].major.five_point, 5.0);
+        assert_eq!(analytics.terms[0].non_major.five_point, 2.4);
+        assert_eq!(analytics.terms[1].cumulative.hundred_point, 83.75);
+
+        let eighties = analytics
+            .histogram
+            .iter()
+            .find(|bucket| bucket.label == "85-89")
+            .unwrap();
+        assert_eq!(eighties.credits, 4.0);
+        assert_eq!(analytics.best[0].course_name, "M0000001");
+        assert_eq!(analytics.worst[0].course_name, "C0000001");
+    }
+}
@@ -0,0 +1,178 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+/// Training-plan buckets used by scholarship and 推免 committees.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum CourseCategory {
+    General,
+    Foundation,
+    Major,
+    Personal,
+    Other,
+}
+
+impl CourseCategory {
+    pub const ALL: [CourseCategory; 5] = [
+        CourseCategory::General,
+        CourseCategory::Foundation,
+        CourseCategory::Major,
+        CourseCategory::Personal,
+        CourseCategory::Other,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            CourseCategory::General => "通识",
+            CourseCategory::Foundation => "大类",
+            CourseCategory::Major => "专业",
+            CourseCategory::Personal => "个性",
+            CourseCategory::Other => "其他",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum CategorySource {
+    Manual,
+    Zdbk,
+    MajorList,
+    Fallback,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CategoryAssignment {
+    pub xkkh: String,
+    pub kcdm: String,
+    pub course_name: String,
+    pub category: CourseCategory,
+    pub source: CategorySource,
+    /// The zdbk attribute text the category was derived from, if any.
+    pub raw_category: String,
+}
+
+/// zdbk is inconsistent about which attribute carries the plan category, so
+/// read the first non-empty one of the known field names.
+const CATEGORY_FIELDS: [&str; 6] = ["kcxzmc", "kclbmc", "kcsxmc", "kcgsmc", "kcfl", "kcxz"];
+
+fn read_str<'a>(grade: &'a Value, key: &str) -> &'a str {
+    grade
+        .get(key)
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .trim()
+}
+
+pub fn category_from_text(text: &str) -> Option<CourseCategory> {
+    const RULES: [(&[&str], CourseCategory); 4] = [
+        (&["个性", "跨专业", "自主发展"], CourseCategory::Personal),
+        (&["大类", "学科基础"], CourseCategory::Foundation),
+        (
+            &[
+                "通识",
+                "思政",
+                "思想政治",
+                "体育",
+                "军训",
+                "外语",
+                "英语",
+                "形势与政策",
+            ],
+            CourseCategory::General,
+        ),
+        (&["专业"], CourseCategory::Major),
+    ];
+    RULES
+        .iter()
+        .find(|(keywords, _)| keywords.iter().any(|keyword| text.contains(keyword)))
+        .map(|(_, category)| *category)
+}
+
+/// Assign each grade to a category. Manual overrides are looked up by xkkh,
+/// then kcdm, then retakeKey; then the zdbk attribute text; then the major
+/// course list; anything left is `Other`.
+pub fn categorize_grades(
+    grades: &[Value],
+    major_course_ids: &HashSet<String>,
+    overrides: &HashMap<String, CourseCategory>,
+) -> Vec<CategoryAssignment> {
+    grades
+        .iter()
+        .map(|grade| {
+            let xkkh = read_str(grade, "xkkh");
+            let kcdm = read_str(grade, "kcdm");
+            let retake_key = read_str(grade, "retakeKey");
+            let raw_category = CATEGORY_FIELDS
+                .iter()
+                .map(|key| read_str(grade, key))
+                .find(|value| !value.is_empty())
+                .unwrap_or_default();
+
+            let manual = [xkkh, kcdm, retake_key]
+                .iter()
+                .filter(|key| !key.is_empty())
+                .find_map(|key| overrides.get(*key).copied());
+            let (category, source) = if let Some(category) = manual {
+                (category, CategorySource::Manual)
+            } else if let Some(category) = category_from_text(raw_category) {
+                (category, CategorySource::Zdbk)
+            } else if major_course_ids.contains(xkkh) || major_course_ids.contains(kcdm) {
+                (CourseCategory::Major, CategorySource::MajorList)
+            } else {
+                (CourseCategory::Other, CategorySource::Fallback)
+            };
+
+            CategoryAssignment {
+                xkkh: xkkh.to_string(),
+                kcdm: kcdm.to_string(),
+                course_name: read_str(grade, "kcmc").to_string(),
+                category,
+                source,
+                raw_category: raw_category.to_string(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{categorize_grades, CategorySource, CourseCategory};
+    use serde_json::json;
+    use std::collections::{HashMap, HashSet};
+
+    #[test]
+    fn categories_follow_overrides_then_zdbk_then_major_list() {
+        let grades = vec![
+            json!({ "xkkh": "(2024-2025-1)-A-1", "kcdm": "A", "kcxzmc": "通识选修课" }),
+            json!({ "xkkh": "(2024-2025-1)-B-1", "kcdm": "B", "kclbmc": "大类学科基础课" }),
+            json!({ "xkkh": "(2024-2025-1)-C-1", "kcdm": "C" }),
+            json!({ "xkkh": "(2024-2025-1)-D-1", "kcdm": "D", "kcxzmc": "专业选修课" }),
+            json!({ "xkkh": "(2024-2025-1)-E-1", "kcdm": "E" }),
+        ];
+        let mut major = HashSet::new();
+        major.insert("C".to_string());
+        let mut overrides = HashMap::new();
+        overrides.insert("D".to_string(), CourseCategory::Personal);
+
+        let assigned = categorize_grades(&grades, &major, &overrides);
+        let categories = assigned
+            .iter()
+            .map(|item| item.category)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            categories,
+            vec![
+                CourseCategory::General,
+                CourseCategory::Foundation,
+                CourseCategory::Major,
+                CourseCategory::Personal,
+                CourseCategory::Other,
+            ]
+        );
+        assert_eq!(assigned[3].source, CategorySource::Manual);
+        assert_eq!(assigned[3].raw_category, "专业选修课");
+    }
+}
@@ -0,0 +1,401 @@
+use crate::gpa::{
+    apply_simulated_score, compute_gpa_by_policy, to_five_point, GpaSummary, RetakeSelection,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+
+/// Lowest hundred-point score of every step on the five-point ladder. Any
+/// score between two steps earns the lower step, so these are the only scores
+/// worth asking a student for.
+const LADDER_SCORES: [f64; 13] = [
+    60.0, 62.0, 65.0, 68.0, 71.0, 74.0, 77.0, 80.0, 83.0, 86.0, 89.0, 92.0, 95.0,
+];
+const MAX_PROPOSALS: usize = 3;
+const EPSILON: f64 = 1e-9;
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum PlanScope {
+    #[default]
+    Overall,
+    Major,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum PlanMetric {
+    FivePoint,
+    FourPoint,
+    FourPointLegacy,
+    HundredPoint,
+}
+
+/// A course that has no row in the transcript yet, e.g. one of
+/// `currentCourses` from the scholar payload.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedCourseInput {
+    pub id: String,
+    #[serde(default)]
+    pub course_code: String,
+    #[serde(default)]
+    pub course_name: String,
+    pub credit: f64,
+    #[serde(default)]
+    pub major: bool,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedScore {
+    pub id: String,
+    pub course_name: String,
+    pub credit: f64,
+    pub score: f64,
+    pub five_point: f64,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanProposal {
+    pub scores: Vec<PlannedScore>,
+    /// Credit-weighted mean of the required scores; lower is cheaper.
+    pub average_score: f64,
+    pub achieved: f64,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GpaPlan {
+    pub scope: PlanScope,
+    pub metric: PlanMetric,
+    pub target: f64,
+    pub current: f64,
+    pub minimum_achievable: f64,
+    pub maximum_achievable: f64,
+    pub reachable: bool,
+    pub already_met: bool,
+    pub uniform_score: Option<f64>,
+    pub proposals: Vec<PlanProposal>,
+    pub message: String,
+}
+
+/// One course the planner is allowed to choose a score for. `index` points
+/// into the grade list handed to [`plan_target_gpa`].
+#[derive(Clone, Debug)]
+pub struct PlanSlot {
+    pub index: usize,
+    pub id: String,
+    pub course_name: String,
+    pub credit: f64,
+}
+
+pub fn read_metric(
+    summary: &GpaSummary,
+    scope: PlanScope,
+    metric: PlanMetric,
+) -> Result<f64, String> {
+    match (scope, metric) {
+        (PlanScope::Overall, PlanMetric::FivePoint) => Ok(summary.five_point),
+        (PlanScope::Overall, PlanMetric::FourPoint) => Ok(summary.four_point),
+        (PlanScope::Overall, PlanMetric::FourPointLegacy) => Ok(summary.four_point_legacy),
+        (PlanScope::Overall, PlanMetric::HundredPoint) => Ok(summary.hundred_point),
+        (PlanScope::Major, PlanMetric::FourPoint) => Ok(summary.major_gpa),
+        (PlanScope::Major, PlanMetric::FourPointLegacy) => Ok(summary.major_gpa_legacy),
+        (PlanScope::Major, _) => Err("专业绩点目标仅支持四分制 (4.3 或 4.0)".to_string()),
+    }
+}
+
+struct Evaluator<'a> {
+    grades: Vec<Value>,
+    slots: &'a [PlanSlot],
+    major_course_ids: &'a HashSet<String>,
+    selection: &'a RetakeSelection,
+    scope: PlanScope,
+    metric: PlanMetric,
+}
+
+impl Evaluator<'_> {
+    fn evaluate(&mut self, scores: &[f64]) -> Result<f64, String> {
+        for (slot, score) in self.slots.iter().zip(scores) {
+            apply_simulated_score(&mut self.grades[slot.index], *score);
+        }
+        let summary =
+            compute_gpa_by_policy(&self.grades, self.major_course_ids, self.selection, &[]);
+        read_metric(&summary, self.scope, self.metric)
+    }
+}
+
+#[derive(Clone)]
+struct Frontier {
+    cost: f64,
+    value: f64,
+    levels: Vec<usize>,
+}
+
+/// Solve for the scores the planned slots need so that the chosen GPA reaches
+/// `target`.
+///
+/// Each slot's effect is measured against a baseline where every slot sits at
+/// the lowest passing step, which keeps the retake policy in the loop (a
+/// retake that the policy ignores simply contributes nothing). Combinations are
+/// then searched over the ladder with a cost/value Pareto frontier and every
+/// proposal is re-checked with the real calculation before it is returned.
+pub fn plan_target_gpa(
+    grades: Vec<Value>,
+    slots: &[PlanSlot],
+    major_course_ids: &HashSet<String>,
+    selection: &RetakeSelection,
+    scope: PlanScope,
+    metric: PlanMetric,
+    target: f64,
+) -> Result<GpaPlan, String> {
+    if slots.is_empty() {
+        return Err("请至少选择一门待出分的课程".to_string());
+    }
+    if !target.is_finite() || target <= 0.0 {
+        return Err(format!("目标绩点无效: {target}"));
+    }
+
+    let current = read_metric(
+        &compute_gpa_by_policy(&grades, major_course_ids, selection, &[]),
+        scope,
+        metric,
+    )?;
+
+    let mut evaluator = Evaluator {
+        grades,
+        slots,
+        major_course_ids,
+        selection,
+        scope,
+        metric,
+    };
+    let lowest = vec![LADDER_SCORES[0]; slots.len()];
+    let highest = vec![LADDER_SCORES[LADDER_SCORES.len() - 1]; slots.len()];
+    let minimum_achievable = evaluator.evaluate(&lowest)?;
+    let maximum_achievable = evaluator.evaluate(&highest)?;
+
+    let mut plan = GpaPlan {
+        scope,
+        metric,
+        target,
+        current,
+        minimum_achievable,
+        maximum_achievable,
+        reachable: maximum_achievable + EPSILON >= target,
+        already_met: minimum_achievable + EPSILON >= target,
+        uniform_score: None,
+        proposals: Vec::new(),
+        message: String::new(),
+    };
+
+    if !plan.reachable {
+        plan.message = format!(
+            "目标 {target:.3} 无法达到：所有课程均取得 {:.0} 分以上时最高为 {maximum_achievable:.3}",
+            LADDER_SCORES[LADDER_SCORES.len() - 1]
+        );
+        return Ok(plan);
+    }
+    if plan.already_met {
+        plan.uniform_score = Some(LADDER_SCORES[0]);
+        plan.message = format!("所有课程及格即可达到目标 {target:.3}");
+        return Ok(plan);
+    }
+
+    for score in LADDER_SCORES {
+        if evaluator.evaluate(&vec![score; slots.len()])? + EPSILON >= target {
+            plan.uniform_score = Some(score);
+            break;
+        }
+    }
+
+    let mut gains = Vec::with_capacity(slots.len());
+    for position in 0..slots.len() {
+        let mut scores = lowest.clone();
+        let mut row = Vec::with_capacity(LADDER_SCORES.len());
+        for score in LADDER_SCORES {
+            scores[position] = score;
+            row.push(evaluator.evaluate(&scores)? - minimum_achievable);
+        }
+        gains.push(row);
+    }
+
+    let mut frontier = vec![Frontier {
+        cost: 0.0,
+        value: 0.0,
+        levels: Vec::new(),
+    }];
+    for (slot, row) in slots.iter().zip(&gains) {
+        let mut next = Vec::with_capacity(frontier.len() * LADDER_SCORES.len());
+        for state in &frontier {
+            for (level, gain) in row.iter().enumerate() {
+                let mut levels = state.levels.clone();
+                levels.push(level);
+                next.push(Frontier {
+                    cost: state.cost + slot.credit * LADDER_SCORES[level],
+                    value: state.value + gain,
+                    levels,
+                });
+            }
+        }
+        frontier = prune_frontier(next);
+    }
+
+    let total_credit = slots.iter().map(|slot| slot.credit).sum::<f64>();
+    let needed = target - minimum_achievable;
+    let mut candidates = frontier
+        .into_iter()
+        .filter(|state| state.value + EPSILON >= needed)
+        .collect::<Vec<_>>();
+    candidates.sort_by(|left, right| {
+        left.cost
+            .partial_cmp(&right.cost)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    for candidate in candidates {
+        let scores = candidate
+            .levels
+            .iter()
+            .map(|level| LADDER_SCORES[*level])
+            .collect::<Vec<_>>();
+        let achieved = evaluator.evaluate(&scores)?;
+        if achieved + EPSILON < target {
+            continue;
+        }
+        plan.proposals.push(PlanProposal {
+            scores: slots
+                .iter()
+                .zip(&scores)
+                .map(|(slot, score)| PlannedScore {
+                    id: slot.id.clone(),
+                    course_name: slot.course_name.clone(),
+                    credit: slot.credit,
+                    score: *score,
+                    five_point: to_five_point(*score),
+                })
+                .collect(),
+            average_score: if total_credit > 0.0 {
+                candidate.cost / total_credit
+            } else {
+                0.0
+            },
+            achieved,
+        });
+        if plan.proposals.len() >= MAX_PROPOSALS {
+            break;
+        }
+    }
+
+    plan.message = match (plan.uniform_score, plan.proposals.first()) {
+        (Some(score), Some(best)) => format!(
+            "所有课程均取得 {score:.0} 分以上可达到目标；最省力的组合平均需要 {:.1} 分",
+            best.average_score
+        ),
+        (Some(score), None) => format!("所有课程均取得 {score:.0} 分以上可达到目标"),
+        (None, _) => "目标可以达到，但需要各课程成绩不同".to_string(),
+    };
+    Ok(plan)
+}
+
+/// Keep only states that are not dominated: for rising cost, value must rise.
+fn prune_frontier(mut states: Vec<Frontier>) -> Vec<Frontier> {
+    states.sort_by(|left, right| {
+        left.cost
+            .partial_cmp(&right.cost)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| {
+                right
+                    .value
+                    .partial_cmp(&left.value)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+    });
+
+    let mut kept = Vec::<Frontier>::new();
+    for state in states {
+        if kept
+            .last()
+            .is_some_and(|best| state.value <= best.value + EPSILON)
+        {
+            continue;
+        }
+        kept.push(state);
+    }
+    kept
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{plan_target_gpa, PlanMetric, PlanScope, PlanSlot};
+    use crate::gpa::{enrich_grade, RetakePolicy};
+    use serde_json::json;
+    use std::collections::HashSet;
+
+    fn grade(cj: &str, xf: f64, xkkh: &str) -> serde_json::Value {
+        enrich_grade(&json!({ "cj": cj, "xf": xf, "xkkh": xkkh, "kcmc": xkkh }))
+    }
+
+    fn slot(index: usize, id: &str, credit: f64) -> PlanSlot {
+        PlanSlot {
+            index,
+            id: id.to_string(),
+            course_name: id.to_string(),
+            credit,
+        }
+    }
+
+    #[test]
+    fn planner_finds_mixed_scores_cheaper_than_uniform() {
+        let grades = vec![
+            grade("83", 4.0, "(2023-2024-1)-A0000001-1"),
+            grade("待录", 4.0, "(2024-2025-1)-B0000001-1"),
+            grade("待录", 1.0, "(2024-2025-1)-C0000001-1"),
+        ];
+        let slots = vec![
+            slot(1, "(2024-2025-1)-B0000001-1", 4.0),
+            slot(2, "(2024-2025-1)-C0000001-1", 1.0),
+        ];
+        let plan = plan_target_gpa(
+            grades,
+            &slots,
+            &HashSet::new(),
+            &RetakePolicy::First.into(),
+            PlanScope::Overall,
+            PlanMetric::FivePoint,
+            4.0,
+        )
+        .unwrap();
+
+        assert!(plan.reachable);
+        assert_eq!(plan.current, 3.9);
+        assert_eq!(plan.uniform_score, Some(86.0));
+        let best = &plan.proposals[0];
+        assert!(best.achieved >= 4.0);
+        assert!(best.average_score <= 86.0);
+    }
+
+    #[test]
+    fn unreachable_targets_are_reported() {
+        let grades = vec![
+            grade("60", 10.0, "(2023-2024-1)-A0000001-1"),
+            grade("待录", 1.0, "(2024-2025-1)-B0000001-1"),
+        ];
+        let plan = plan_target_gpa(
+            grades,
+            &[slot(1, "(2024-2025-1)-B0000001-1", 1.0)],
+            &HashSet::new(),
+            &RetakePolicy::First.into(),
+            PlanScope::Overall,
+            PlanMetric::FivePoint,
+            4.5,
+        )
+        .unwrap();
+
+        assert!(!plan.reachable);
+        assert!(plan.proposals.is_empty());
+        assert!(plan.maximum_achievable < 4.5);
+    }
+}
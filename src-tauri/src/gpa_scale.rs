@@ -0,0 +1,234 @@
+use crate::preset_store::{self, Preset, PresetOrigin};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tauri::AppHandle;
+
+const PRESET_SOURCES: &[&str] = &[
+    include_str!("../presets/gpa_scales/zju-5.json"),
+    include_str!("../presets/gpa_scales/zju-4.3.json"),
+    include_str!("../presets/gpa_scales/wes-4.0.json"),
+    include_str!("../presets/gpa_scales/pku-4.0.json"),
+    include_str!("../presets/gpa_scales/percentage.json"),
+];
+
+/// Which enriched grade value the thresholds are compared against.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ScaleInput {
+    #[default]
+    Hundred,
+    FivePoint,
+}
+
+pub type ScaleOrigin = PresetOrigin;
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ScaleThreshold {
+    pub min: f64,
+    pub points: f64,
+}
+
+/// A named GPA scale. A grade string listed in `grade_points` is converted
+/// directly; anything else goes through the threshold table (highest `min`
+/// first), or is copied as-is when `linear` is set.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GpaScale {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub max_points: f64,
+    #[serde(default)]
+    pub input: ScaleInput,
+    #[serde(default)]
+    pub linear: bool,
+    #[serde(default)]
+    pub thresholds: Vec<ScaleThreshold>,
+    #[serde(default)]
+    pub grade_points: BTreeMap<String, f64>,
+    #[serde(default, skip_deserializing)]
+    pub origin: ScaleOrigin,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ScaleGpa {
+    pub id: String,
+    pub name: String,
+    pub max_points: f64,
+    pub gpa: f64,
+    pub major_gpa: f64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GpaScaleImportInput {
+    pub path: Option<String>,
+    pub content: Option<String>,
+}
+
+impl GpaScale {
+    pub fn points_for(&self, cj: &str, hundred_point: f64, five_point: f64) -> f64 {
+        if let Some(points) = self.grade_points.get(cj.trim()) {
+            return *points;
+        }
+
+        let value = match self.input {
+            ScaleInput::Hundred => hundred_point,
+            ScaleInput::FivePoint => five_point,
+        };
+        if self.linear {
+            return value.clamp(0.0, self.max_points);
+        }
+
+        // Five-point values arrive as floats like 4.8 from jd, so allow a hair
+        // of rounding slack on the threshold comparison.
+        self.thresholds
+            .iter()
+            .find(|threshold| value + 1e-9 >= threshold.min)
+            .map(|threshold| threshold.points)
+            .unwrap_or(0.0)
+    }
+}
+
+impl Preset for GpaScale {
+    const LABEL: &'static str = "绩点标准";
+    const USER_DIR: &'static str = "gpa_scales";
+    const SOURCES: &'static [&'static str] = PRESET_SOURCES;
+
+    fn parse(content: &str, origin: PresetOrigin) -> Result<Self, String> {
+        parse_scale(content, origin)
+    }
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+pub fn parse_scale(content: &str, origin: ScaleOrigin) -> Result<GpaScale, String> {
+    let mut scale = serde_json::from_str::<GpaScale>(content)
+        .map_err(|error| format!("绩点标准格式错误: {error}"))?;
+    scale.id = scale.id.trim().to_string();
+    scale.name = scale.name.trim().to_string();
+    scale.origin = origin;
+
+    preset_store::check_id::<GpaScale>(&scale.id)?;
+    if scale.name.is_empty() {
+        return Err(format!("绩点标准 {} 缺少名称", scale.id));
+    }
+    if !scale.max_points.is_finite() || scale.max_points <= 0.0 {
+        return Err(format!("绩点标准 {} 的满绩点无效", scale.id));
+    }
+    if !scale.linear && scale.thresholds.is_empty() && scale.grade_points.is_empty() {
+        return Err(format!("绩点标准 {} 没有任何分段或等级映射", scale.id));
+    }
+
+    let out_of_range =
+        |points: f64| !points.is_finite() || points < 0.0 || points > scale.max_points;
+    if let Some(threshold) = scale
+        .thresholds
+        .iter()
+        .find(|threshold| !threshold.min.is_finite() || out_of_range(threshold.points))
+    {
+        return Err(format!(
+            "绩点标准 {} 的分段 {} → {} 超出范围",
+            scale.id, threshold.min, threshold.points
+        ));
+    }
+    if let Some((grade, points)) = scale
+        .grade_points
+        .iter()
+        .find(|(_, points)| out_of_range(**points))
+    {
+        return Err(format!(
+            "绩点标准 {} 的等级 {grade} → {points} 超出范围",
+            scale.id
+        ));
+    }
+
+    scale.thresholds.sort_by(|left, right| {
+        right
+            .min
+            .partial_cmp(&left.min)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    Ok(scale)
+}
+
+/// Presets followed by every readable user scale, with unreadable files
+/// reported in the second list.
+pub fn load_scales(app: &AppHandle) -> (Vec<GpaScale>, Vec<String>) {
+    preset_store::load(app)
+}
+
+pub fn select_scales(scales: Vec<GpaScale>, ids: Option<&[String]>) -> Vec<GpaScale> {
+    match ids {
+        Some(ids) => scales
+            .into_iter()
+            .filter(|scale| ids.iter().any(|id| id == &scale.id))
+            .collect(),
+        None => scales,
+    }
+}
+
+pub fn import_scale(app: &AppHandle, input: GpaScaleImportInput) -> Result<GpaScale, String> {
+    preset_store::import(app, input.content, input.path)
+}
+
+pub fn delete_scale(app: &AppHandle, id: &str) -> Result<(), String> {
+    preset_store::delete::<GpaScale>(app, id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_scale, GpaScale, ScaleOrigin};
+    use crate::preset_store::presets;
+
+    #[test]
+    fn presets_parse_and_convert_grades() {
+        let presets = presets::<GpaScale>();
+        assert_eq!(presets.len(), 5);
+
+        let wes = presets.iter().find(|scale| scale.id == "wes-4.0").unwrap();
+        assert_eq!(wes.points_for("82", 82.0, 3.9), 3.0);
+        assert_eq!(wes.points_for("B+", 83.0, 3.9), 3.0);
+        assert_eq!(wes.points_for("59", 59.0, 0.0), 0.0);
+
+        // zdbk's jd wins over the converted score, as in the official GPA.
+        let zju5 = presets.iter().find(|scale| scale.id == "zju-5").unwrap();
+        assert_eq!(zju5.points_for("93", 93.0, 4.5), 4.5);
+
+        let zju43 = presets.iter().find(|scale| scale.id == "zju-4.3").unwrap();
+        assert_eq!(zju43.points_for("93", 93.0, 4.8), 4.2);
+
+        let percentage = presets
+            .iter()
+            .find(|scale| scale.id == "percentage")
+            .unwrap();
+        assert_eq!(percentage.points_for("87.5", 87.5, 4.2), 87.5);
+    }
+
+    #[test]
+    fn invalid_user_scales_are_rejected() {
+        assert!(parse_scale(
+            r#"{ "id": "../x", "name": "x", "maxPoints": 4, "linear": true }"#,
+            ScaleOrigin::User
+        )
+        .is_err());
+        assert!(parse_scale(
+            r#"{ "id": "x", "name": "x", "maxPoints": 4, "thresholds": [{ "min": 60, "points": 5 }] }"#,
+            ScaleOrigin::User
+        )
+        .is_err());
+
+        let scale = parse_scale(
+            r#"{ "id": "x", "name": "x", "maxPoints": 4, "thresholds": [{ "min": 60, "points": 1 }, { "min": 90, "points": 4 }] }"#,
+            ScaleOrigin::User,
+        )
+        .unwrap();
+        assert_eq!(scale.points_for("95", 95.0, 5.0), 4.0);
+        assert_eq!(scale.origin, ScaleOrigin::User);
+    }
+}
@@ -0,0 +1,185 @@
+use crate::json_value::{first_f64, first_str};
+use serde::Serialize;
+use serde_json::Value;
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HomeworkFile {
+    pub id: i64,
+    pub reference_id: Option<i64>,
+    pub name: String,
+    pub size_bytes: u64,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LatePolicy {
+    pub allowed: bool,
+    /// Last moment a late submission is accepted.
+    pub end_time: Option<String>,
+    /// Penalty as shown by 学在浙大, e.g. a percentage deducted.
+    pub penalty: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HomeworkSubmissionRecord {
+    pub id: Option<i64>,
+    pub is_draft: bool,
+    pub submitted_at: Option<String>,
+    pub comment: String,
+    pub files: Vec<HomeworkFile>,
+    pub score: Option<f64>,
+    pub feedback: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HomeworkDetail {
+    pub activity_id: i64,
+    pub course_id: Option<i64>,
+    pub course_name: String,
+    pub title: String,
+    pub description_html: String,
+    pub attachments: Vec<HomeworkFile>,
+    pub start_time: Option<String>,
+    pub end_time: Option<String>,
+    pub late_policy: LatePolicy,
+    /// A non-draft submission exists.
+    pub submitted: bool,
+    pub submissions: Vec<HomeworkSubmissionRecord>,
+    /// Score and feedback of the latest graded submission.
+    pub score: Option<f64>,
+    pub feedback: Option<String>,
+}
+
+fn first<'a>(value: &'a Value, keys: &[&str]) -> Option<&'a Value> {
+    keys.iter()
+        .find_map(|key| value.get(*key).filter(|found| !found.is_null()))
+}
+
+fn files(value: &Value) -> Vec<HomeworkFile> {
+    value
+        .get("uploads")
+        .and_then(Value::as_array)
+        .map(|uploads| {
+            uploads
+                .iter()
+                .filter_map(|upload| {
+                    Some(HomeworkFile {
+                        id: upload.get("id").and_then(Value::as_i64)?,
+                        reference_id: upload.get("reference_id").and_then(Value::as_i64),
+                        name: first_str(upload, &["name", "file_name"]).unwrap_or_default(),
+                        size_bytes: upload.get("size").and_then(Value::as_u64).unwrap_or(0),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn submission_record(submission: &Value) -> HomeworkSubmissionRecord {
+    HomeworkSubmissionRecord {
+        id: submission.get("id").and_then(Value::as_i64),
+        is_draft: submission
+            .get("is_draft")
+            .and_then(Value::as_bool)
+            .unwrap_or(false),
+        submitted_at: first_str(submission, &["submitted_at", "updated_at", "created_at"]),
+        comment: first_str(submission, &["comment"]).unwrap_or_default(),
+        files: files(submission),
+        score: first_f64(submission, &["final_score", "score"]),
+        feedback: first_str(
+            submission,
+            &["instructor_comment", "correct_comment", "feedback"],
+        ),
+    }
+}
+
+/// Builds the homework view from the activity record and the user's
+/// submissions. Course fields come from the todo, since activities only carry
+/// the course id.
+pub fn parse_homework_detail(
+    activity: &Value,
+    submissions: &[Value],
+    course_name: &str,
+) -> Result<HomeworkDetail, String> {
+    let activity_id = activity
+        .get("id")
+        .and_then(Value::as_i64)
+        .ok_or("学在浙大未返回作业信息")?;
+    let data = activity.get("data").unwrap_or(&Value::Null);
+    let submissions = submissions
+        .iter()
+        .map(submission_record)
+        .collect::<Vec<_>>();
+    let graded = submissions
+        .iter()
+        .filter(|submission| !submission.is_draft)
+        .find(|submission| submission.score.is_some() || submission.feedback.is_some());
+
+    Ok(HomeworkDetail {
+        activity_id,
+        course_id: activity.get("course_id").and_then(Value::as_i64),
+        course_name: course_name.to_string(),
+        title: first_str(activity, &["title", "name"]).unwrap_or_default(),
+        description_html: first_str(data, &["description"])
+            .or_else(|| first_str(activity, &["description"]))
+            .unwrap_or_default(),
+        attachments: files(activity),
+        start_time: first_str(activity, &["start_time"]),
+        end_time: first_str(activity, &["end_time", "deadline"]),
+        late_policy: LatePolicy {
+            allowed: first(
+                activity,
+                &["is_allow_late_submission", "allow_late_submission"],
+            )
+            .or_else(|| first(data, &["is_allow_late_submission", "allow_late_submission"]))
+            .and_then(Value::as_bool)
+            .unwrap_or(false),
+            end_time: first_str(activity, &["late_submission_end_time", "late_end_time"])
+                .or_else(|| first_str(data, &["late_submission_end_time", "late_end_time"])),
+            penalty: first(activity, &["late_submission_penalty", "late_penalty"])
+                .or_else(|| first(data, &["late_submission_penalty", "late_penalty"]))
+                .map(|penalty| match penalty {
+                    Value::String(text) => text.clone(),
+                    other => other.to_string(),
+                }),
+        },
+        submitted: submissions.iter().any(|submission| !submission.is_draft),
+        score: graded.and_then(|submission| submission.score),
+        feedback: graded.and_then(|submission| submission.feedback.clone()),
+        submissions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_homework_detail;
+    use serde_json::json;
+
+    #[test]
+    fn homework_detail_reads_activity_and_graded_submission() {
+        let activity = json!({
+            "id": 11, "course_id": 5, "title": "实验一", "end_time": "2024-05-01T15:59:00Z",
+            "data": { "description": "<p>提交报告</p>", "is_allow_late_submission": true, "late_submission_penalty": 20 },
+            "uploads": [{ "id": 1, "reference_id": 9, "name": "模板.docx", "size": 2048 }],
+        });
+        let submissions = vec![
+            json!({ "id": 3, "is_draft": true, "uploads": [] }),
+            json!({ "id": 2, "submitted_at": "2024-04-30T10:00:00Z", "score": "92",
+                    "instructor_comment": "很好", "uploads": [{ "id": 4, "name": "report.pdf" }] }),
+        ];
+        let detail = parse_homework_detail(&activity, &submissions, "操作系统").unwrap();
+
+        assert_eq!(detail.description_html, "<p>提交报告</p>");
+        assert_eq!(detail.attachments[0].reference_id, Some(9));
+        assert!(detail.late_policy.allowed);
+        assert_eq!(detail.late_policy.penalty.as_deref(), Some("20"));
+        assert!(detail.submitted);
+        assert_eq!(detail.score, Some(92.0));
+        assert_eq!(detail.feedback.as_deref(), Some("很好"));
+        assert_eq!(detail.submissions[1].files[0].name, "report.pdf");
+        assert!(parse_homework_detail(&json!({}), &[], "").is_err());
+    }
+}
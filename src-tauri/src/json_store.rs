@@ -0,0 +1,97 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+fn data_path(app: &AppHandle, filename: &str) -> Option<PathBuf> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| {
+            let _ = fs::create_dir_all(&dir);
+            dir.join(filename)
+        })
+        .ok()
+}
+
+/// Moves an unreadable file aside as `<name>.<timestamp>.bak`, so that the
+/// next save starts fresh without destroying what the user had.
+fn back_up(path: &Path) {
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(format!(".{stamp}.bak"));
+    let _ = fs::rename(path, backup);
+}
+
+fn read_file<T: DeserializeOwned + Default>(path: &Path) -> T {
+    let parsed = match fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content).ok(),
+        Err(error) if error.kind() == ErrorKind::NotFound => return T::default(),
+        Err(_) => None,
+    };
+    parsed.unwrap_or_else(|| {
+        back_up(path);
+        T::default()
+    })
+}
+
+fn write_file<T: Serialize + ?Sized>(path: &Path, value: &T) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(value).map_err(|error| error.to_string())?;
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    fs::write(&temp, content).map_err(|error| error.to_string())?;
+    fs::rename(&temp, path).map_err(|error| error.to_string())
+}
+
+/// Reads user data kept as JSON in the app data directory. A missing file
+/// yields the default; a corrupt or schema-mismatched one is backed up first.
+pub fn load<T: DeserializeOwned + Default>(app: &AppHandle, filename: &str) -> T {
+    data_path(app, filename)
+        .map(|path| read_file(&path))
+        .unwrap_or_default()
+}
+
+/// Replaces the stored file; `label` names the data in error messages.
+pub fn store<T: Serialize + ?Sized>(
+    app: &AppHandle,
+    filename: &str,
+    value: &T,
+    label: &str,
+) -> Result<(), String> {
+    let path = data_path(app, filename).ok_or_else(|| format!("无法定位{label}路径"))?;
+    write_file(&path, value).map_err(|error| format!("保存{label}失败: {error}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_file, write_file};
+    use std::fs;
+
+    #[test]
+    fn corrupt_files_are_backed_up_instead_of_overwritten() {
+        let dir = std::env::temp_dir().join(format!("celechron-json-store-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("scenarios.json");
+
+        assert!(read_file::<Vec<String>>(&path).is_empty());
+        write_file(&path, &["A"]).unwrap();
+        assert_eq!(read_file::<Vec<String>>(&path), ["A"]);
+
+        fs::write(&path, r#"{ "not": "a list" }"#).unwrap();
+        assert!(read_file::<Vec<String>>(&path).is_empty());
+        assert!(!path.exists());
+        let backups = fs::read_dir(&dir)
+            .unwrap()
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_name().to_string_lossy().ends_with(".bak"))
+            .map(|entry| fs::read_to_string(entry.path()).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(backups, [r#"{ "not": "a list" }"#]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,54 @@
+use serde_json::Value;
+
+/// Trimmed text under the first of `keys` that holds a string; empty text
+/// counts as missing.
+pub fn first_str(value: &Value, keys: &[&str]) -> Option<String> {
+    keys.iter()
+        .find_map(|key| value.get(*key).and_then(Value::as_str))
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .map(str::to_string)
+}
+
+/// Integer under the first of `keys`, also accepting numeric strings.
+pub fn first_i64(value: &Value, keys: &[&str]) -> Option<i64> {
+    keys.iter().find_map(|key| {
+        value.get(*key).and_then(|found| {
+            found
+                .as_i64()
+                .or_else(|| found.as_str().and_then(|text| text.trim().parse().ok()))
+        })
+    })
+}
+
+/// Number under the first of `keys`, also accepting numeric strings.
+pub fn first_f64(value: &Value, keys: &[&str]) -> Option<f64> {
+    keys.iter().find_map(|key| {
+        value.get(*key).and_then(|found| {
+            found
+                .as_f64()
+                .or_else(|| found.as_str().and_then(|text| text.trim().parse().ok()))
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{first_f64, first_i64, first_str};
+    use serde_json::json;
+
+    #[test]
+    fn accessors_take_the_first_usable_key() {
+        let value = json!({ "name": "  ", "title": " 第一讲 ", "count": "3", "score": 9.5, "weight": " 30 " });
+        assert_eq!(
+            first_str(&value, &["missing", "title"]).as_deref(),
+            Some("第一讲")
+        );
+        assert!(first_str(&value, &["name", "title"]).is_none());
+        assert_eq!(first_i64(&value, &["missing", "count"]), Some(3));
+        assert_eq!(first_f64(&value, &["score"]), Some(9.5));
+        assert_eq!(first_f64(&value, &["weight"]), Some(30.0));
+        assert_eq!(first_str(&value, &["count"]), Some("3".to_string()));
+        assert!(first_i64(&value, &["title"]).is_none());
+    }
+}
@@ -0,0 +1,461 @@
+use crate::classroom::{ClassroomSession, LectureStream, LectureStreamFormat, LectureTrack};
+use crate::materials::{self, MaterialAsset};
+use crate::zjuam::AppState;
+use reqwest::{StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter};
+
+/// Emitted with a [`LectureDownloadProgress`] while a replay downloads.
+pub const PROGRESS_EVENT: &str = "lecture-download-progress";
+/// MP4 progress is reported at most once per this many bytes.
+const EMIT_EVERY_BYTES: u64 = 1024 * 1024;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LectureDownloadInput {
+    pub course_id: i64,
+    pub sub_id: i64,
+    pub course_name: String,
+    pub title: String,
+    /// Tracks to download; both when omitted.
+    pub tracks: Option<Vec<LectureTrack>>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LectureDownloadProgress {
+    pub download_id: String,
+    pub track: LectureTrack,
+    pub downloaded_bytes: u64,
+    pub total_bytes: Option<u64>,
+    pub segments_done: Option<usize>,
+    pub segments_total: Option<usize>,
+    pub done: bool,
+}
+
+/// Owner of a `.part` file, plus the segments already appended to it for HLS.
+/// Keyed on the lecture and track, since replay URLs are signed and change
+/// between sessions.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ResumeState {
+    download_id: String,
+    segments_total: usize,
+    segments_done: usize,
+    bytes: u64,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Playlist {
+    /// Variant playlists with their bandwidth.
+    Master(Vec<(u64, String)>),
+    Media {
+        segments: Vec<String>,
+        encrypted: bool,
+    },
+}
+
+pub fn parse_playlist(base: &Url, text: &str) -> Result<Playlist, String> {
+    if !text.trim_start().starts_with("#EXTM3U") {
+        return Err("回放播放列表无效".to_string());
+    }
+    let mut variants = Vec::new();
+    let mut segments = Vec::new();
+    let mut encrypted = false;
+    let mut bandwidth = None;
+    for line in text.lines().map(str::trim) {
+        if let Some(attributes) = line.strip_prefix("#EXT-X-STREAM-INF:") {
+            bandwidth = Some(
+                attributes
+                    .split(',')
+                    .find_map(|attribute| attribute.strip_prefix("BANDWIDTH="))
+                    .and_then(|value| value.parse().ok())
+                    .unwrap_or(0),
+            );
+            continue;
+        }
+        if let Some(attributes) = line.strip_prefix("#EXT-X-KEY:") {
+            encrypted |= !attributes.contains("METHOD=NONE");
+            continue;
+        }
+        // fMP4 segments need their init section and cannot be joined as TS.
+        if line.starts_with("#EXT-X-MAP:") {
+            return Err("该回放为 fMP4 分段格式，暂不支持下载".to_string());
+        }
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Ok(url) = base.join(line) else {
+            continue;
+        };
+        match bandwidth.take() {
+            Some(bandwidth) => variants.push((bandwidth, url.to_string())),
+            None => segments.push(url.to_string()),
+        }
+    }
+    Ok(if variants.is_empty() {
+        Playlist::Media {
+            segments,
+            encrypted,
+        }
+    } else {
+        Playlist::Master(variants)
+    })
+}
+
+fn track_key(track: LectureTrack) -> &'static str {
+    match track {
+        LectureTrack::Teacher => "teacher",
+        LectureTrack::Screen => "screen",
+    }
+}
+
+fn track_label(track: LectureTrack) -> &'static str {
+    match track {
+        LectureTrack::Teacher => "教师",
+        LectureTrack::Screen => "屏幕",
+    }
+}
+
+fn sidecar(target: &Path, suffix: &str) -> PathBuf {
+    let name = target
+        .file_name()
+        .and_then(|value| value.to_str())
+        .unwrap_or("replay");
+    target.with_file_name(format!("{name}{suffix}"))
+}
+
+fn read_resume_state(path: &Path) -> Option<ResumeState> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+}
+
+fn write_resume_state(path: &Path, state: &ResumeState) -> Result<(), String> {
+    fs::write(
+        path,
+        serde_json::to_vec(state).map_err(|error| error.to_string())?,
+    )
+    .map_err(|error| format!("保存下载进度失败: {error}"))
+}
+
+fn emit(app: &AppHandle, progress: &LectureDownloadProgress) {
+    let _ = app.emit(PROGRESS_EVENT, progress);
+}
+
+async fn fetch_playlist(session: &ClassroomSession, url: &Url) -> Result<Playlist, String> {
+    let (bytes, _) = session.download_bytes(url.as_str()).await?;
+    parse_playlist(url, &String::from_utf8_lossy(&bytes))
+}
+
+/// Streams an MP4 into `<target>.part`, continuing from its current length
+/// when the part belongs to this download and the server honours range
+/// requests.
+async fn download_mp4(
+    app: &AppHandle,
+    session: &ClassroomSession,
+    url: &str,
+    target: &Path,
+    progress: &mut LectureDownloadProgress,
+) -> Result<(), String> {
+    let part = sidecar(target, ".part");
+    let state_path = sidecar(target, ".part.json");
+    let owned = read_resume_state(&state_path)
+        .is_some_and(|state| state.download_id == progress.download_id);
+    let offset = if owned {
+        fs::metadata(&part).map(|meta| meta.len()).unwrap_or(0)
+    } else {
+        0
+    };
+    write_resume_state(
+        &state_path,
+        &ResumeState {
+            download_id: progress.download_id.clone(),
+            ..ResumeState::default()
+        },
+    )?;
+    let mut response = session.get_from(url, offset).await?;
+    let resumed = offset > 0 && response.status() == StatusCode::PARTIAL_CONTENT;
+    let mut options = OpenOptions::new();
+    options.create(true);
+    if resumed {
+        options.append(true);
+    } else {
+        options.write(true).truncate(true);
+    }
+    let mut file = options
+        .open(&part)
+        .map_err(|error| format!("写入课程回放失败: {error}"))?;
+
+    progress.downloaded_bytes = if resumed { offset } else { 0 };
+    progress.total_bytes = response
+        .content_length()
+        .map(|length| length + progress.downloaded_bytes);
+    let mut reported = progress.downloaded_bytes;
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|error| format!("下载课程回放失败: {error}"))?
+    {
+        file.write_all(&chunk)
+            .map_err(|error| format!("写入课程回放失败: {error}"))?;
+        progress.downloaded_bytes += chunk.len() as u64;
+        if progress.downloaded_bytes - reported >= EMIT_EVERY_BYTES {
+            reported = progress.downloaded_bytes;
+            emit(app, progress);
+        }
+    }
+    drop(file);
+    fs::rename(&part, target).map_err(|error| format!("保存课程回放失败: {error}"))?;
+    let _ = fs::remove_file(&state_path);
+    Ok(())
+}
+
+/// Appends HLS segments to `<target>.part`, recording each finished segment
+/// so an interrupted download resumes at the next one.
+async fn download_hls(
+    app: &AppHandle,
+    session: &ClassroomSession,
+    url: &str,
+    target: &Path,
+    progress: &mut LectureDownloadProgress,
+) -> Result<(), String> {
+    let mut playlist_url = Url::parse(url).map_err(|error| format!("回放地址无效: {error}"))?;
+    let mut playlist = fetch_playlist(session, &playlist_url).await?;
+    if let Playlist::Master(variants) = &playlist {
+        let (_, best) = variants
+            .iter()
+            .max_by_key(|(bandwidth, _)| *bandwidth)
+            .ok_or("回放没有可用的清晰度")?;
+        playlist_url = Url::parse(best).map_err(|error| format!("回放地址无效: {error}"))?;
+        playlist = fetch_playlist(session, &playlist_url).await?;
+    }
+    let Playlist::Media {
+        segments,
+        encrypted,
+    } = playlist
+    else {
+        return Err("回放播放列表无效".to_string());
+    };
+    if encrypted {
+        return Err("该回放已加密，暂不支持下载".to_string());
+    }
+    if segments.is_empty() {
+        return Err("回放没有可下载的分段".to_string());
+    }
+
+    let part = sidecar(target, ".part");
+    let state_path = sidecar(target, ".part.json");
+    let part_len = fs::metadata(&part).map(|meta| meta.len()).unwrap_or(0);
+    let mut state = read_resume_state(&state_path)
+        .filter(|state| {
+            state.download_id == progress.download_id
+                && state.segments_total == segments.len()
+                && state.segments_done <= segments.len()
+                && state.bytes <= part_len
+        })
+        .unwrap_or_else(|| ResumeState {
+            download_id: progress.download_id.clone(),
+            segments_total: segments.len(),
+            ..ResumeState::default()
+        });
+    let mut file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&part)
+        .map_err(|error| format!("写入课程回放失败: {error}"))?;
+    file.set_len(state.bytes)
+        .and_then(|_| file.seek(SeekFrom::End(0)))
+        .map_err(|error| format!("写入课程回放失败: {error}"))?;
+
+    progress.segments_total = Some(segments.len());
+    progress.segments_done = Some(state.segments_done);
+    progress.downloaded_bytes = state.bytes;
+    emit(app, progress);
+    for segment in &segments[state.segments_done..] {
+        let bytes = session
+            .get_from(segment, 0)
+            .await?
+            .bytes()
+            .await
+            .map_err(|error| format!("下载课程回放失败: {error}"))?;
+        file.write_all(&bytes)
+            .map_err(|error| format!("写入课程回放失败: {error}"))?;
+        state.segments_done += 1;
+        state.bytes += bytes.len() as u64;
+        write_resume_state(&state_path, &state)?;
+        progress.segments_done = Some(state.segments_done);
+        progress.downloaded_bytes = state.bytes;
+        emit(app, progress);
+    }
+    drop(file);
+    fs::rename(&part, target).map_err(|error| format!("保存课程回放失败: {error}"))?;
+    let _ = fs::remove_file(&state_path);
+    Ok(())
+}
+
+/// Replay streams of a lecture, one per track.
+pub async fn lecture_streams(
+    state: &AppState,
+    course_id: i64,
+    sub_id: i64,
+) -> Result<Vec<LectureStream>, String> {
+    let session = ClassroomSession::login(state).await?;
+    session.get_lecture_streams(course_id, sub_id).await
+}
+
+/// Downloads the selected tracks of a lecture replay into the materials
+/// library. Tracks already downloaded are only re-registered.
+pub async fn download(
+    app: &AppHandle,
+    state: &AppState,
+    input: LectureDownloadInput,
+) -> Result<Vec<MaterialAsset>, String> {
+    let session = ClassroomSession::login(state).await?;
+    let streams = session
+        .get_lecture_streams(input.course_id, input.sub_id)
+        .await?;
+    if streams.is_empty() {
+        return Err("该课程暂无回放视频".to_string());
+    }
+    let tracks = input
+        .tracks
+        .unwrap_or_else(|| vec![LectureTrack::Teacher, LectureTrack::Screen]);
+    let mut selected = Vec::<&LectureStream>::new();
+    for track in tracks {
+        if selected.iter().any(|stream| stream.track == track) {
+            continue;
+        }
+        if let Some(stream) = streams.iter().find(|stream| stream.track == track) {
+            selected.push(stream);
+        }
+    }
+    if selected.is_empty() {
+        return Err("未找到所选机位的回放".to_string());
+    }
+
+    let mut assets = Vec::new();
+    for stream in selected {
+        let (extension, mime_type) = match stream.format {
+            LectureStreamFormat::Hls => ("ts", "video/mp2t"),
+            LectureStreamFormat::Mp4 => ("mp4", "video/mp4"),
+        };
+        let label = track_label(stream.track);
+        let target = materials::material_target_path(
+            app,
+            &input.course_name,
+            &format!("{}-{}-{label}.{extension}", input.title, input.sub_id),
+        )?;
+        let mut progress = LectureDownloadProgress {
+            download_id: format!(
+                "classroom:{}:{}:{}",
+                input.course_id,
+                input.sub_id,
+                track_key(stream.track)
+            ),
+            track: stream.track,
+            downloaded_bytes: 0,
+            total_bytes: None,
+            segments_done: None,
+            segments_total: None,
+            done: false,
+        };
+        if !target.exists() {
+            match stream.format {
+                LectureStreamFormat::Hls => {
+                    download_hls(app, &session, &stream.url, &target, &mut progress).await?
+                }
+                LectureStreamFormat::Mp4 => {
+                    download_mp4(app, &session, &stream.url, &target, &mut progress).await?
+                }
+            }
+        }
+        progress.done = true;
+        emit(app, &progress);
+        assets.push(materials::register_material_file(
+            app,
+            &target,
+            &input.course_name,
+            &format!("{}（{label}）", input.title),
+            format!(
+                "classroom://course/{}/sub/{}#{}",
+                input.course_id,
+                input.sub_id,
+                track_key(stream.track)
+            ),
+            Some(mime_type.to_string()),
+        )?);
+    }
+    Ok(assets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_playlist, Playlist};
+    use crate::classroom::{parse_lecture_streams, LectureStreamFormat, LectureTrack};
+    use reqwest::Url;
+    use serde_json::json;
+
+    #[test]
+    fn replay_streams_and_playlists_resolve() {
+        let streams = parse_lecture_streams(&json!({ "data": { "video_list": [
+            { "type": 1, "preview_url": "https://cdn.example.com/live/teacher/index.m3u8?t=1" },
+            { "type": "2", "preview_url": "https://cdn.example.com/vod/screen.mp4" },
+            { "type": 2, "preview_url": "https://cdn.example.com/vod/screen.mp4" },
+        ] } }));
+        assert_eq!(streams.len(), 2);
+        assert_eq!(streams[0].track, LectureTrack::Teacher);
+        assert_eq!(streams[0].format, LectureStreamFormat::Hls);
+        assert_eq!(streams[1].track, LectureTrack::Screen);
+        assert_eq!(streams[1].format, LectureStreamFormat::Mp4);
+
+        let base = Url::parse("https://cdn.example.com/live/teacher/index.m3u8").unwrap();
+        let master = "#EXTM3U\n#EXT-X-STREAM-INF:AVERAGE-BANDWIDTH=1,BANDWIDTH=800000\nlow/index.m3u8\n#EXT-X-STREAM-INF:BANDWIDTH=2400000\nhigh/index.m3u8\n";
+        assert_eq!(
+            parse_playlist(&base, master).unwrap(),
+            Playlist::Master(vec![
+                (
+                    800000,
+                    "https://cdn.example.com/live/teacher/low/index.m3u8".to_string()
+                ),
+                (
+                    2400000,
+                    "https://cdn.example.com/live/teacher/high/index.m3u8".to_string()
+                ),
+            ])
+        );
+
+        let media = "#EXTM3U\n#EXT-X-TARGETDURATION:10\n#EXTINF:10,\nseg-0.ts\n#EXTINF:10,\n/abs/seg-1.ts\n#EXT-X-ENDLIST\n";
+        let Playlist::Media {
+            segments,
+            encrypted,
+        } = parse_playlist(&base, media).unwrap()
+        else {
+            panic!("expected a media playlist");
+        };
+        assert!(!encrypted);
+        assert_eq!(
+            segments,
+            [
+                "https://cdn.example.com/live/teacher/seg-0.ts",
+                "https://cdn.example.com/abs/seg-1.ts"
+            ]
+        );
+
+        let locked = "#EXTM3U\n#EXT-X-KEY:METHOD=AES-128,URI=\"key\"\nseg-0.ts\n";
+        assert!(matches!(
+            parse_playlist(&base, locked).unwrap(),
+            Playlist::Media {
+                encrypted: true,
+                ..
+            }
+        ));
+        let fragmented = "#EXTM3U\n#EXT-X-MAP:URI=\"init.mp4\"\n#EXTINF:10,\nseg-0.m4s\n";
+        assert!(parse_playlist(&base, fragmented).is_err());
+        assert!(parse_playlist(&base, "<html>").is_err());
+    }
+}
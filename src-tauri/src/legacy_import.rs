@@ -0,0 +1,542 @@
+use crate::local_tasks::{self, LocalTask, LocalTaskKind};
+use crate::scenario::{self, GpaScenario, GpaScenarioInput};
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fs;
+use tauri::AppHandle;
+
+const MAPPING_KEYS: [&str; 3] = ["courseIdMappingList", "courseIdMappings", "courseIdMap"];
+const SELECTION_KEYS: [&str; 2] = ["customGpa", "customGpaSelected"];
+const SIMULATION_KEYS: [&str; 2] = ["simulatedScores", "customScores"];
+const TASK_KEYS: [&str; 2] = ["taskList", "tasks"];
+/// Flow settings of the Flutter app. The flow view here is derived from the
+/// timetable and todos, so there is nothing to carry them over to.
+const FLOW_KEYS: [&str; 5] = [
+    "flowList",
+    "workTime",
+    "restTime",
+    "allowTimeLayerDetail",
+    "flowSettings",
+];
+const SCENARIO_NAME: &str = "旧版 Celechron 自定义 GPA";
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LegacyImportInput {
+    pub path: String,
+    /// Only report what would change. Defaults to true.
+    pub dry_run: Option<bool>,
+    /// The mappings currently in the preferences, to report overwrites.
+    pub existing_mappings: Option<HashMap<String, String>>,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MappingChange {
+    pub from: String,
+    pub to: String,
+    pub previous: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LegacySkip {
+    pub section: String,
+    pub key: String,
+    pub reason: String,
+}
+
+/// What importing does to the migrated scenario; in a dry run, what it would do.
+#[derive(Clone, Copy, Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ScenarioAction {
+    Created,
+    Updated,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LegacyScenarioPreview {
+    pub name: String,
+    pub action: ScenarioAction,
+    pub selected_courses: usize,
+    pub simulated_scores: usize,
+    /// Set once the scenario has been written.
+    pub id: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LegacyImportReport {
+    pub dry_run: bool,
+    /// Existing mappings merged with the imported ones; the caller persists
+    /// these into the preferences when not a dry run.
+    pub course_id_mappings: HashMap<String, String>,
+    pub mapping_changes: Vec<MappingChange>,
+    pub scenario: Option<LegacyScenarioPreview>,
+    pub tasks: Vec<LocalTask>,
+    pub tasks_added: usize,
+    pub skipped: Vec<LegacySkip>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LegacyData {
+    pub mappings: Vec<(String, String)>,
+    pub selected_ids: Vec<String>,
+    pub simulated_scores: HashMap<String, f64>,
+    pub tasks: Vec<LocalTask>,
+    pub skipped: Vec<LegacySkip>,
+}
+
+/// Hive boxes are exported either as a top-level object per box or under a
+/// `boxes` key; values written by `jsonEncode` arrive as strings.
+fn sections(root: &Value) -> Vec<(String, Map<String, Value>)> {
+    let mut sections = Vec::new();
+    let Some(object) = root.as_object() else {
+        return sections;
+    };
+    sections.push((String::new(), object.clone()));
+    let boxes = object
+        .get("boxes")
+        .and_then(Value::as_object)
+        .unwrap_or(object);
+    for (name, value) in boxes {
+        if let Value::Object(inner) = decode(value) {
+            sections.push((name.clone(), inner));
+        }
+    }
+    sections
+}
+
+fn decode(value: &Value) -> Value {
+    match value {
+        Value::String(text) if text.trim_start().starts_with(['[', '{']) => {
+            serde_json::from_str(text).unwrap_or_else(|_| value.clone())
+        }
+        _ => value.clone(),
+    }
+}
+
+fn lookup(sections: &[(String, Map<String, Value>)], keys: &[&str]) -> Option<(String, Value)> {
+    sections.iter().find_map(|(name, object)| {
+        keys.iter().find_map(|key| {
+            object.get(*key).map(|value| {
+                (
+                    format!("{name}.{key}").trim_start_matches('.').to_string(),
+                    decode(value),
+                )
+            })
+        })
+    })
+}
+
+fn text(value: &Value, keys: &[&str]) -> Option<String> {
+    keys.iter()
+        .find_map(|key| match value.get(*key) {
+            Some(Value::String(text)) => Some(text.trim().to_string()),
+            Some(Value::Number(number)) => Some(number.to_string()),
+            _ => None,
+        })
+        .filter(|text| !text.is_empty())
+}
+
+/// Dart enums serialize as `TaskType.deadline` or just `deadline`.
+fn enum_name(value: &str) -> String {
+    value
+        .rsplit('.')
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+fn parse_time(value: Option<&Value>) -> Option<String> {
+    let local = match value? {
+        Value::Number(number) => {
+            let raw = number.as_i64()?;
+            let millis = if raw.abs() > 100_000_000_000 {
+                raw
+            } else {
+                raw * 1000
+            };
+            Local.timestamp_millis_opt(millis).single()?
+        }
+        Value::String(text) => {
+            let text = text.trim();
+            if let Ok(parsed) = DateTime::parse_from_rfc3339(text) {
+                parsed.with_timezone(&Local)
+            } else {
+                let naive = [
+                    "%Y-%m-%dT%H:%M:%S%.f",
+                    "%Y-%m-%d %H:%M:%S%.f",
+                    "%Y-%m-%d %H:%M",
+                ]
+                .iter()
+                .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())?;
+                Local.from_local_datetime(&naive).single()?
+            }
+        }
+        _ => return None,
+    };
+    Some(local.to_rfc3339())
+}
+
+fn parse_task(value: &Value) -> Result<LocalTask, String> {
+    let title = text(value, &["summary", "title", "name"]).ok_or("缺少标题")?;
+    let start_at = ["startTime", "start_time", "start"]
+        .iter()
+        .find_map(|key| parse_time(value.get(*key)));
+    let end_at = ["endTime", "end_time", "end", "deadline"]
+        .iter()
+        .find_map(|key| parse_time(value.get(*key)));
+    let kind = match text(value, &["type", "taskType"]).map(|kind| enum_name(&kind)) {
+        Some(kind) if kind.contains("deadline") => LocalTaskKind::Deadline,
+        Some(kind) if kind.contains("fixed") => LocalTaskKind::Fixed,
+        Some(kind) if kind.parse::<i64>().is_err() => {
+            return Err(format!("不支持的任务类型 {kind}"));
+        }
+        _ if start_at.is_some() => LocalTaskKind::Fixed,
+        _ => LocalTaskKind::Deadline,
+    };
+    if end_at.is_none() {
+        return Err("缺少截止或结束时间".to_string());
+    }
+    if kind == LocalTaskKind::Fixed && start_at.is_none() {
+        return Err("固定任务缺少开始时间".to_string());
+    }
+
+    let id = text(value, &["uid", "id"])
+        .unwrap_or_else(|| format!("{title}-{}", end_at.as_deref().unwrap_or_default()));
+    let done = match value.get("status") {
+        Some(Value::Bool(done)) => *done,
+        Some(Value::String(status)) => {
+            matches!(
+                enum_name(status).as_str(),
+                "completed" | "done" | "finished"
+            )
+        }
+        _ => false,
+    };
+    let repeat = text(value, &["repeatType", "repeat"])
+        .map(|repeat| enum_name(&repeat))
+        .filter(|repeat| !matches!(repeat.as_str(), "norepeat" | "none" | ""));
+    let repeat_until = repeat.as_ref().and_then(|_| {
+        parse_time(
+            value
+                .get("repeatEndsTime")
+                .or_else(|| value.get("repeatUntil")),
+        )
+    });
+
+    Ok(LocalTask {
+        id: format!("flutter-{id}"),
+        kind,
+        title,
+        description: text(value, &["description"]).unwrap_or_default(),
+        location: text(value, &["location"]).unwrap_or_default(),
+        start_at: if kind == LocalTaskKind::Fixed {
+            start_at
+        } else {
+            None
+        },
+        end_at,
+        done,
+        repeat,
+        repeat_until,
+        source: "flutter".to_string(),
+    })
+}
+
+fn skip(skipped: &mut Vec<LegacySkip>, section: &str, key: &str, reason: impl Into<String>) {
+    skipped.push(LegacySkip {
+        section: section.to_string(),
+        key: key.to_string(),
+        reason: reason.into(),
+    });
+}
+
+pub fn parse_backup(root: &Value) -> Result<LegacyData, String> {
+    let sections = sections(root);
+    let mut data = LegacyData::default();
+    let mut found = false;
+
+    if let Some((section, value)) = lookup(&sections, &MAPPING_KEYS) {
+        found = true;
+        let pairs = match &value {
+            Value::Array(items) => items
+                .iter()
+                .enumerate()
+                .map(|(index, item)| {
+                    let item = decode(item);
+                    match (
+                        text(&item, &["id1", "from", "source", "original"]),
+                        text(&item, &["id2", "to", "target", "mapped"]),
+                    ) {
+                        (Some(from), Some(to)) => Ok((from, to)),
+                        _ => Err(index.to_string()),
+                    }
+                })
+                .collect::<Vec<_>>(),
+            Value::Object(map) => map
+                .iter()
+                .map(|(from, to)| match to.as_str().map(str::trim) {
+                    Some(to) if !to.is_empty() => Ok((from.trim().to_string(), to.to_string())),
+                    _ => Err(from.clone()),
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        for pair in pairs {
+            match pair {
+                Ok((from, to)) if from != to => data.mappings.push((from, to)),
+                Ok((from, _)) => skip(&mut data.skipped, &section, &from, "映射到自身，已忽略"),
+                Err(key) => skip(&mut data.skipped, &section, &key, "无法识别的课程映射"),
+            }
+        }
+    }
+
+    if let Some((section, value)) = lookup(&sections, &SELECTION_KEYS) {
+        found = true;
+        match value {
+            Value::Object(map) => {
+                data.selected_ids = map
+                    .iter()
+                    .filter(|(_, selected)| {
+                        selected.as_bool() == Some(true) || selected.as_str() == Some("true")
+                    })
+                    .map(|(id, _)| id.clone())
+                    .collect();
+            }
+            Value::Array(items) => {
+                data.selected_ids = items
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(str::to_string)
+                    .collect();
+            }
+            _ => skip(&mut data.skipped, &section, "", "无法识别的自定义 GPA 选择"),
+        }
+        data.selected_ids.sort();
+    }
+
+    if let Some((section, Value::Object(map))) = lookup(&sections, &SIMULATION_KEYS) {
+        found = true;
+        for (key, score) in map {
+            let parsed = score
+                .as_f64()
+                .or_else(|| score.as_str().and_then(|text| text.trim().parse().ok()));
+            match parsed {
+                Some(score) if (0.0..=100.0).contains(&score) => {
+                    data.simulated_scores.insert(key, score);
+                }
+                _ => skip(
+                    &mut data.skipped,
+                    &section,
+                    &key,
+                    format!("模拟分 {score} 无效"),
+                ),
+            }
+        }
+    }
+
+    if let Some((section, Value::Array(items))) = lookup(&sections, &TASK_KEYS) {
+        found = true;
+        for (index, item) in items.iter().enumerate() {
+            let item = decode(item);
+            match parse_task(&item) {
+                Ok(task) if data.tasks.iter().all(|existing| existing.id != task.id) => {
+                    data.tasks.push(task)
+                }
+                Ok(task) => skip(&mut data.skipped, &section, &task.id, "重复的任务"),
+                Err(reason) => {
+                    let key =
+                        text(&item, &["summary", "title"]).unwrap_or_else(|| index.to_string());
+                    skip(&mut data.skipped, &section, &key, reason);
+                }
+            }
+        }
+    }
+
+    for (name, object) in &sections {
+        for key in FLOW_KEYS.iter().filter(|key| object.contains_key(**key)) {
+            found = true;
+            skip(
+                &mut data.skipped,
+                name,
+                key,
+                "流程视图现由课表和待办自动生成，旧版流程设置不再需要",
+            );
+        }
+    }
+
+    if !found {
+        return Err("文件中没有找到旧版 Celechron 的数据".to_string());
+    }
+    Ok(data)
+}
+
+fn merge_mappings(
+    existing: HashMap<String, String>,
+    imported: &[(String, String)],
+) -> (HashMap<String, String>, Vec<MappingChange>) {
+    let mut merged = existing;
+    let mut changes = Vec::new();
+    for (from, to) in imported {
+        let previous = merged.insert(from.clone(), to.clone());
+        if previous.as_ref() != Some(to) {
+            changes.push(MappingChange {
+                from: from.clone(),
+                to: to.clone(),
+                previous,
+            });
+        }
+    }
+    (merged, changes)
+}
+
+/// The scenario an earlier import created, so re-running the migration
+/// updates it instead of adding another copy.
+fn migrated_scenario(scenarios: &[GpaScenario]) -> Option<&GpaScenario> {
+    scenarios
+        .iter()
+        .find(|scenario| scenario.name == SCENARIO_NAME)
+}
+
+/// Reads a Flutter backup and reports what it maps to. Unless `dry_run` is
+/// false nothing is written; otherwise the scenario and tasks are stored.
+/// `grades` is the current transcript, used to tell simulated forecasts from
+/// what-ifs the way saved scenarios do.
+pub fn import_backup(
+    app: &AppHandle,
+    input: LegacyImportInput,
+    grades: Vec<Value>,
+) -> Result<LegacyImportReport, String> {
+    let content = fs::read_to_string(input.path.trim())
+        .map_err(|error| format!("读取备份文件失败: {error}"))?;
+    let root = serde_json::from_str::<Value>(&content)
+        .map_err(|error| format!("备份文件不是有效的 JSON: {error}"))?;
+    let data = parse_backup(&root)?;
+    let dry_run = input.dry_run.unwrap_or(true);
+    let (course_id_mappings, mapping_changes) =
+        merge_mappings(input.existing_mappings.unwrap_or_default(), &data.mappings);
+
+    let scenarios = scenario::load_scenarios(app);
+    let existing = migrated_scenario(&scenarios);
+    let mut preview =
+        (!data.selected_ids.is_empty() || !data.simulated_scores.is_empty()).then(|| {
+            LegacyScenarioPreview {
+                name: SCENARIO_NAME.to_string(),
+                action: match existing {
+                    Some(_) => ScenarioAction::Updated,
+                    None => ScenarioAction::Created,
+                },
+                selected_courses: data.selected_ids.len(),
+                simulated_scores: data.simulated_scores.len(),
+                id: existing.map(|scenario| scenario.id.clone()),
+            }
+        });
+    let mut tasks_added = 0;
+    if !dry_run {
+        if let Some(preview) = preview.as_mut() {
+            // Settings made on the migrated scenario since then are kept.
+            let input = GpaScenarioInput {
+                name: SCENARIO_NAME.to_string(),
+                note: Some("从旧版 Flutter Celechron 迁移".to_string()),
+                grades,
+                selected_ids: Some(data.selected_ids.clone()),
+                simulated_scores: Some(data.simulated_scores.clone()),
+                course_id_mappings: Some(course_id_mappings.clone()),
+                retake_policy: existing.and_then(|scenario| scenario.retake_policy.clone()),
+                retake_overrides: existing.map(|scenario| scenario.retake_overrides.clone()),
+                scale_ids: existing.and_then(|scenario| scenario.scale_ids.clone()),
+            };
+            let stored = match existing {
+                Some(scenario) => scenario::update_scenario(app, &scenario.id, input)?,
+                None => scenario::create_scenario(app, input)?,
+            };
+            preview.id = Some(stored.id);
+        }
+        tasks_added = local_tasks::merge_tasks(app, data.tasks.clone())?;
+    }
+
+    Ok(LegacyImportReport {
+        dry_run,
+        course_id_mappings,
+        mapping_changes,
+        scenario: preview,
+        tasks: data.tasks,
+        tasks_added,
+        skipped: data.skipped,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{merge_mappings, migrated_scenario, parse_backup, SCENARIO_NAME};
+    use crate::local_tasks::LocalTaskKind;
+    use crate::scenario::GpaScenario;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    #[test]
+    fn legacy_backup_maps_boxes_to_mappings_scenario_and_tasks() {
+        let backup = json!({
+            "boxes": {
+                "option": {
+                    "courseIdMappingList": "[{\"id1\":\"211G0200\",\"id2\":\"21120010\"},{\"id1\":\"X\",\"id2\":\"X\"}]",
+                    "workTime": 45,
+                },
+                "scholar": {
+                    "customGpa": { "(2022-2023-1)-A-1": true, "(2022-2023-1)-B-1": false },
+                    "simulatedScores": { "C": "92", "D": 130 },
+                },
+                "task": {
+                    "taskList": [
+                        { "uid": "t1", "type": "TaskType.deadline", "summary": "实验报告", "endTime": "2023-05-01T23:59:00.000", "status": "TaskStatus.completed" },
+                        { "uid": "t2", "type": "TaskType.fixed", "summary": "自习", "startTime": 1682900000000_i64, "endTime": 1682907200000_i64, "repeatType": "TaskRepeatType.days" },
+                        { "uid": "t3", "type": "TaskType.fixed", "summary": "缺开始", "endTime": "2023-05-02 10:00" },
+                    ],
+                },
+            },
+        });
+        let data = parse_backup(&backup).unwrap();
+
+        assert_eq!(
+            data.mappings,
+            vec![("211G0200".to_string(), "21120010".to_string())]
+        );
+        assert_eq!(data.selected_ids, vec!["(2022-2023-1)-A-1"]);
+        assert_eq!(data.simulated_scores.get("C"), Some(&92.0));
+        assert_eq!(data.tasks.len(), 2);
+        assert_eq!(data.tasks[0].kind, LocalTaskKind::Deadline);
+        assert!(data.tasks[0].done && data.tasks[0].start_at.is_none());
+        assert_eq!(data.tasks[1].kind, LocalTaskKind::Fixed);
+        assert_eq!(data.tasks[1].repeat.as_deref(), Some("days"));
+        let skipped = data
+            .skipped
+            .iter()
+            .map(|skip| skip.key.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(skipped, vec!["X", "D", "缺开始", "workTime"]);
+
+        let mut existing = HashMap::new();
+        existing.insert("211G0200".to_string(), "old".to_string());
+        let (merged, changes) = merge_mappings(existing, &data.mappings);
+        assert_eq!(merged["211G0200"], "21120010");
+        assert_eq!(changes[0].previous.as_deref(), Some("old"));
+        assert!(parse_backup(&json!({ "unrelated": 1 })).is_err());
+    }
+
+    #[test]
+    fn repeated_import_finds_the_migrated_scenario() {
+        let scenario = |id: &str, name: &str| GpaScenario {
+            id: id.to_string(),
+            name: name.to_string(),
+            ..GpaScenario::default()
+        };
+        let scenarios = [scenario("a", "保研冲刺"), scenario("b", SCENARIO_NAME)];
+        assert_eq!(migrated_scenario(&scenarios).unwrap().id, "b");
+        assert!(migrated_scenario(&scenarios[..1]).is_none());
+    }
+}
@@ -1,33 +1,83 @@
 mod api;
+mod archive;
+mod attendance;
+mod audit;
 mod classroom;
+mod course_scores;
 mod courses;
+mod evaluation;
+mod freetime;
 mod gpa;
+mod gpa_analytics;
+mod gpa_category;
+mod gpa_plan;
+mod gpa_scale;
+mod homework;
 mod integrations;
+mod json_store;
+mod json_value;
+mod lecture_video;
+mod legacy_import;
+mod local_tasks;
 mod materials;
+mod notifications;
+mod preset_store;
+mod quizzes;
+mod reconcile;
+mod scenario;
+mod submission;
 mod term;
+mod timetable_merge;
+mod todo_sync;
+mod transcript_export;
+mod transcript_import;
+mod videos;
 mod zdbk;
 mod zjuam;
 
 use crate::api::{cache_read_envelope, cache_write_envelope, envelope};
+use crate::archive::ArchiveTermKey;
+use crate::evaluation::{EvaluationRuleImportInput, TermWindow};
+use crate::freetime::{CommonFreeTimeInput, TimetableShareExportInput};
 use crate::gpa::{
-    apply_simulated_score, compute_gpa_by_policy, enrich_grade, extract_semester_name, RetakePolicy,
+    apply_simulated_score, compute_gpa_by_policy, enrich_grade, extract_semester_name, GpaSummary,
+    RetakeOverride, RetakePolicy, RetakeSelection,
 };
+use crate::gpa_category::CourseCategory;
+use crate::gpa_plan::{PlanMetric, PlanScope, PlanSlot, PlannedCourseInput};
+use crate::gpa_scale::GpaScaleImportInput;
 use crate::integrations::{AiAnalysisInput, DingtalkTestInput};
+use crate::lecture_video::LectureDownloadInput;
+use crate::legacy_import::LegacyImportInput;
 use crate::materials::{
     DownloadMaterialInput, MaterialContentInput, MaterialPathInput, RemoteMaterialDownloadInput,
 };
+use crate::scenario::{GpaScenario, GpaScenarioInput};
+use crate::submission::HomeworkSubmissionInput;
 use crate::term::{
-    descriptor_from_name, descriptor_from_parts, load_term_time_config,
-    normalize_academic_semester, normalize_timetable_sessions,
+    descriptor_from_name, descriptor_from_parts, diff_timetable_sessions, load_term_time_config,
+    normalize_academic_semester, normalize_timetable_sessions, resolve_term_anchor,
+    teaching_weeks_within, NormalizedTimetableSession, TermDescriptor, TermTimeConfig,
+    TimetableChange,
 };
-use chrono::{Datelike, Local};
+use crate::transcript_export::{
+    TranscriptExportInput, TranscriptFormat, TranscriptTranslationsInput,
+};
+use crate::transcript_import::TranscriptImportInput;
+use chrono::{Datelike, Local, Utc};
 use serde::Deserialize;
 use serde_json::{json, Value};
 use std::collections::{HashMap, HashSet};
 
 const SCHOLAR_CACHE_FILE: &str = "cache_scholar_v2.json";
 const TODOS_CACHE_FILE: &str = "cache_todos_v2.json";
-
+/// Per-todo homework details keyed by todo id, each stored as an envelope.
+const TODO_DETAILS_CACHE_FILE: &str = "cache_todo_details_v1.json";
+const ATTENDANCE_CACHE_FILE: &str = "cache_attendance_v1.json";
+const COURSE_ARCHIVE_CACHE_FILE: &str = "cache_course_archive_v1.json";
+const VIDEOS_CACHE_FILE: &str = "cache_videos_v1.json";
+/// Timetable changes are notified when they touch a class within this many days.
+const UPCOMING_CHANGE_DAYS: i64 = 14;
 
 fn annotate_cache_fallback(mut env: Value, requested_fresh: bool, reason: &str) -> Value {
     if let Some(meta) = env.get_mut("_meta").and_then(Value::as_object_mut) {
@@ -41,7 +91,8 @@ fn annotate_cache_fallback(mut env: Value, requested_fresh: bool, reason: &str)
 use std::sync::Arc;
 #[cfg(desktop)]
 use tauri::Manager;
-use tauri::{AppHandle, State};
+use tauri::{AppHandle, Emitter, State};
+use tauri_plugin_notification::NotificationExt;
 use zjuam::AppState;
 
 #[tauri::command]
@@ -74,46 +125,66 @@ async fn login_zju_command(
     }
 }
 
-#[tauri::command]
-async fn fetch_scholar_data(
-    app: AppHandle,
-    state: State<'_, Arc<AppState>>,
-    force_refresh: Option<bool>,
-) -> Result<Value, String> {
-    let force_refresh = force_refresh.unwrap_or(false);
-
-    let (transcript_r, major_r, exams_r, practice_r, learning_courses_r) = tokio::join!(
-        zdbk::get_transcript(&state),
-        zdbk::get_major_grades(&state),
-        zdbk::get_exams(&state),
-        zdbk::get_practice_scores(&state),
-        courses::get_learning_courses(&state),
-    );
-
-    if let Err(error) = transcript_r {
-        if let Some(cached) = cache_read_envelope(&app, SCHOLAR_CACHE_FILE) {
-            return Ok(annotate_cache_fallback(cached, force_refresh, &error));
-        }
-        return Err(error);
-    }
+const RETAKE_POLICIES: [(&str, RetakePolicy); 4] = [
+    ("first", RetakePolicy::First),
+    ("highest", RetakePolicy::Highest),
+    ("latest", RetakePolicy::Latest),
+    ("average", RetakePolicy::Average),
+];
+
+/// One summary per supported retake policy, `first` always leading.
+fn gpa_by_policy(
+    grades: &[Value],
+    major_course_set: &HashSet<String>,
+    overrides: &HashMap<String, RetakeOverride>,
+    scales: &[gpa_scale::GpaScale],
+) -> Vec<(&'static str, GpaSummary)> {
+    RETAKE_POLICIES
+        .iter()
+        .map(|(name, policy)| {
+            let selection = RetakeSelection {
+                policy: *policy,
+                overrides: overrides.clone(),
+            };
+            (
+                *name,
+                compute_gpa_by_policy(grades, major_course_set, &selection, scales),
+            )
+        })
+        .collect()
+}
 
-    let transcript_raw = transcript_r.unwrap_or_default();
-    let major_grades = major_r.unwrap_or_default();
-    let exams = exams_r.unwrap_or_default();
-    let practice = practice_r.unwrap_or(zdbk::PracticeScores {
-        pt2: 0.0,
-        pt3: 0.0,
-        pt4: 0.0,
-    });
+fn gpa_by_policy_json(summaries: Vec<(&'static str, GpaSummary)>) -> Value {
+    Value::Object(
+        summaries
+            .into_iter()
+            .map(|(name, summary)| (name.to_string(), json!(summary)))
+            .collect(),
+    )
+}
 
+/// The `fetch_scholar_data` payload for a raw transcript, shared with offline
+/// transcript imports.
+fn scholar_payload(
+    app: &AppHandle,
+    transcript_raw: &[Value],
+    major_grades: Vec<Value>,
+    exams: Vec<Value>,
+    practice: &zdbk::PracticeScores,
+    current_courses: Vec<Value>,
+) -> Value {
     let processed_grades = transcript_raw.iter().map(enrich_grade).collect::<Vec<_>>();
+    let (scales, _) = gpa_scale::load_scales(app);
     let major_course_ids = collect_major_course_ids(&major_grades);
     let major_course_set = major_course_ids.iter().cloned().collect::<HashSet<_>>();
 
-    let overall_first =
-        compute_gpa_by_policy(&processed_grades, &major_course_set, RetakePolicy::First);
-    let overall_highest =
-        compute_gpa_by_policy(&processed_grades, &major_course_set, RetakePolicy::Highest);
+    let overall_by_policy = gpa_by_policy(
+        &processed_grades,
+        &major_course_set,
+        &HashMap::new(),
+        &scales,
+    );
+    let overall_first = overall_by_policy[0].1.clone();
 
     let mut semesters_map = HashMap::<String, Vec<Value>>::new();
     for grade in &processed_grades {
@@ -134,8 +205,8 @@ async fn fetch_scholar_data(
                     .cmp(right.get("kcmc").and_then(Value::as_str).unwrap_or_default())
             });
             let descriptor = descriptor_from_name(&name);
-            let first = compute_gpa_by_policy(&grades, &major_course_set, RetakePolicy::First);
-            let highest = compute_gpa_by_policy(&grades, &major_course_set, RetakePolicy::Highest);
+            let by_policy = gpa_by_policy(&grades, &major_course_set, &HashMap::new(), &scales);
+            let first = by_policy[0].1.clone();
             let term = descriptor.clone().map(|value| json!(value)).unwrap_or(Value::Null);
             let display_name = descriptor
                 .as_ref()
@@ -152,10 +223,7 @@ async fn fetch_scholar_data(
                     "displayName": display_name,
                     "term": term,
                     "grades": grades,
-                    "gpaByPolicy": {
-                        "first": first,
-                        "highest": highest,
-                    },
+                    "gpaByPolicy": gpa_by_policy_json(by_policy),
                     "gpa": [first.five_point, first.four_point, first.four_point_legacy, first.hundred_point],
                     "credits": first.total_credits,
                 }),
@@ -163,25 +231,38 @@ async fn fetch_scholar_data(
         })
         .collect::<Vec<_>>();
 
-    semesters.sort_by(|left, right| right.0.cmp(&left.0));
+    semesters.sort_by_key(|entry| std::cmp::Reverse(entry.0));
     let semesters = semesters
         .into_iter()
         .map(|(_, value)| value)
         .collect::<Vec<_>>();
 
-    let current_term = {
-        let today = Local::now().date_naive();
-        let year = today.year();
-        let month = today.month();
-        if (2..=8).contains(&month) {
-            descriptor_from_parts((year - 1).to_string(), "2")
-        } else {
-            let start_year = if month == 1 { year - 1 } else { year };
-            descriptor_from_parts(start_year.to_string(), "1")
-        }
-    };
+    json!({
+        "gpa": overall_first,
+        "gpaByPolicy": gpa_by_policy_json(overall_by_policy),
+        "transcript": processed_grades,
+        "majorGrades": major_grades,
+        "majorCourseIds": major_course_ids,
+        "retakePolicySupported": RETAKE_POLICIES.iter().map(|(name, _)| *name).collect::<Vec<_>>(),
+        "exams": exams,
+        "practice": {
+            "pt2": practice.pt2,
+            "pt3": practice.pt3,
+            "pt4": practice.pt4,
+        },
+        "semesters": semesters,
+        "currentCourses": current_courses,
+    })
+}
 
-    let current_courses = learning_courses_r
+/// 学在浙大 courses of the current term with their running score estimates.
+/// Courses whose scores fail to load are reported in the returned warnings.
+async fn current_courses_with_progress(
+    state: &Arc<AppState>,
+    term: &TermDescriptor,
+) -> (Vec<Value>, Vec<String>) {
+    let mut current_courses = courses::get_learning_courses(state)
+        .await
         .unwrap_or_default()
         .into_iter()
         .filter_map(|course| {
@@ -229,36 +310,130 @@ async fn fetch_scholar_data(
                 "courseName": course_name,
                 "credit": credit,
                 "teacher": teacher,
-                "term": current_term.clone(),
+                "term": term,
             }))
         })
         .collect::<Vec<_>>();
 
-    let payload = json!({
-        "gpa": overall_first,
-        "gpaByPolicy": {
-            "first": overall_first,
-            "highest": overall_highest,
-        },
-        "transcript": processed_grades,
-        "majorGrades": major_grades,
-        "majorCourseIds": major_course_ids,
-        "retakePolicySupported": ["first", "highest"],
-        "exams": exams,
-        "practice": {
-            "pt2": practice.pt2,
-            "pt3": practice.pt3,
-            "pt4": practice.pt4,
-        },
-        "semesters": semesters,
-        "currentCourses": current_courses,
+    let score_courses = current_courses
+        .iter()
+        .filter_map(|course| {
+            let course_id = course.get("courseId")?.as_str()?.parse::<i64>().ok()?;
+            let course_name = course.get("courseName")?.as_str()?.to_string();
+            Some((course_id, course_name))
+        })
+        .collect::<Vec<_>>();
+    let (mut score_progress, warnings) =
+        course_scores::fetch_progress(state.clone(), score_courses).await;
+    for course in &mut current_courses {
+        let progress = course
+            .get("courseId")
+            .and_then(Value::as_str)
+            .and_then(|id| id.parse::<i64>().ok())
+            .and_then(|id| score_progress.remove(&id));
+        course["scoreProgress"] = json!(progress);
+    }
+    (current_courses, warnings)
+}
+
+#[tauri::command]
+async fn fetch_scholar_data(
+    app: AppHandle,
+    state: State<'_, Arc<AppState>>,
+    force_refresh: Option<bool>,
+) -> Result<Value, String> {
+    let force_refresh = force_refresh.unwrap_or(false);
+
+    let current_term = current_term_descriptor();
+    // Score estimates fan out over every course, so they load alongside the
+    // zdbk requests instead of after them.
+    let (transcript_r, major_r, exams_r, practice_r, (current_courses, warnings)) = tokio::join!(
+        zdbk::get_transcript(&state),
+        zdbk::get_major_grades(&state),
+        zdbk::get_exams(&state),
+        zdbk::get_practice_scores(&state),
+        current_courses_with_progress(state.inner(), &current_term),
+    );
+
+    if let Err(error) = transcript_r {
+        if let Some(cached) = cache_read_envelope(&app, SCHOLAR_CACHE_FILE) {
+            return Ok(annotate_cache_fallback(cached, force_refresh, &error));
+        }
+        return Err(error);
+    }
+
+    let transcript_raw = transcript_r.unwrap_or_default();
+    let major_grades = major_r.unwrap_or_default();
+    let exams = exams_r.unwrap_or_default();
+    let practice = practice_r.unwrap_or(zdbk::PracticeScores {
+        pt2: 0.0,
+        pt3: 0.0,
+        pt4: 0.0,
     });
 
+    let mut payload = scholar_payload(
+        &app,
+        &transcript_raw,
+        major_grades,
+        exams,
+        &practice,
+        current_courses,
+    );
+    payload["warnings"] = json!(warnings);
+
     let env = envelope(payload, "network");
     cache_write_envelope(&app, SCHOLAR_CACHE_FILE, &env);
     Ok(env)
 }
 
+/// Builds the scholar payload from an exported 成绩单 instead of zdbk, so the
+/// GPA views work without logging in. The result replaces the scholar cache.
+#[tauri::command]
+async fn import_transcript_file(
+    app: AppHandle,
+    input: TranscriptImportInput,
+) -> Result<Value, String> {
+    // Parsing a long PDF or xlsx takes a while, so keep it off the main thread.
+    let imported =
+        tauri::async_runtime::spawn_blocking(move || transcript_import::import_file(&input))
+            .await
+            .map_err(|error| error.to_string())??;
+    let practice = zdbk::PracticeScores {
+        pt2: 0.0,
+        pt3: 0.0,
+        pt4: 0.0,
+    };
+    let mut payload = scholar_payload(
+        &app,
+        &imported.grades,
+        Vec::new(),
+        Vec::new(),
+        &practice,
+        Vec::new(),
+    );
+    payload["import"] = json!({
+        "format": imported.format,
+        "rows": imported.grades.len(),
+        "skipped": imported.skipped,
+    });
+
+    let env = envelope(payload, "import");
+    cache_write_envelope(&app, SCHOLAR_CACHE_FILE, &env);
+    Ok(env)
+}
+
+fn current_term_descriptor() -> TermDescriptor {
+    let today = Local::now().date_naive();
+    let year = today.year();
+    let month = today.month();
+    if (2..=8).contains(&month) {
+        descriptor_from_parts((year - 1).to_string(), "2")
+    } else {
+        let start_year = if month == 1 { year - 1 } else { year };
+        descriptor_from_parts(start_year.to_string(), "1")
+    }
+}
+
 #[tauri::command]
 async fn fetch_timetable(
     app: AppHandle,
@@ -279,7 +454,40 @@ async fn fetch_timetable(
     match zdbk::get_timetable(&state, &term.year, &term.timetable_semester).await {
         Ok(raw_timetable) => {
             let time_config = load_term_time_config(&app, &term).await;
-            let sessions = normalize_timetable_sessions(&term, &raw_timetable);
+            let zdbk_sessions = normalize_timetable_sessions(&term, &raw_timetable);
+            // Diff against the zdbk-only sessions so that a 学在浙大 outage does not
+            // show up as every filled-in room being relocated.
+            let changes = cache_read_envelope(&app, &cache_name)
+                .and_then(|cached| {
+                    let data = cached.get("data")?;
+                    data.get("zdbkSessions")
+                        .or_else(|| data.get("sessions"))
+                        .cloned()
+                })
+                .and_then(|value| {
+                    serde_json::from_value::<Vec<NormalizedTimetableSession>>(value).ok()
+                })
+                .map(|previous| diff_timetable_sessions(&previous, &zdbk_sessions))
+                .unwrap_or_default();
+            notify_upcoming_timetable_changes(&app, &term, &time_config, &changes);
+            // 学在浙大 only lists a past term's courses among the closed ones.
+            let learning_courses = if term.name < current_term_descriptor().name {
+                courses::get_archived_courses(&state)
+                    .await
+                    .map(|courses| archive::courses_in_term(courses, &term))
+            } else {
+                courses::get_learning_courses(&state)
+                    .await
+                    .map(|courses| timetable_merge::filter_courses_for_term(courses, &term))
+            };
+            let (sessions, merge, merge_error) = match learning_courses {
+                Ok(courses) => {
+                    let (sessions, report) =
+                        timetable_merge::merge_learning_schedules(&zdbk_sessions, &courses);
+                    (sessions, Some(report), None)
+                }
+                Err(error) => (zdbk_sessions.clone(), None, Some(error)),
+            };
             let env = envelope(
                 json!({
                     "term": term,
@@ -289,6 +497,10 @@ async fn fetch_timetable(
                     "xqm": term.timetable_semester,
                     "timeConfig": time_config,
                     "sessions": sessions,
+                    "zdbkSessions": zdbk_sessions,
+                    "changes": changes,
+                    "merge": merge,
+                    "mergeError": merge_error,
                     "timetable": raw_timetable,
                 }),
                 "network",
@@ -305,6 +517,102 @@ async fn fetch_timetable(
     }
 }
 
+#[tauri::command]
+fn export_timetable_share(input: TimetableShareExportInput) -> Result<Value, String> {
+    let share = freetime::build_share(
+        &input.term,
+        &input.sessions,
+        input.label.as_deref().unwrap_or_default(),
+    );
+    let code = freetime::encode_share(&share)?;
+    Ok(envelope(
+        json!({
+            "code": code,
+            "term": input.term,
+            "blockCount": share.blocks.len(),
+        }),
+        "network",
+    ))
+}
+
+#[tauri::command]
+async fn find_common_free_time(
+    app: AppHandle,
+    input: CommonFreeTimeInput,
+) -> Result<Value, String> {
+    let shares = input
+        .codes
+        .iter()
+        .filter(|code| !code.trim().is_empty())
+        .map(|code| freetime::decode_share(code))
+        .collect::<Result<Vec<_>, _>>()?;
+    let first = shares.first().ok_or("请至少提供一份课表分享码")?;
+    if let Some(other) = shares.iter().find(|share| share.term != first.term) {
+        return Err(format!(
+            "课表分享码学期不一致: {} / {}",
+            first.term, other.term
+        ));
+    }
+    let term = descriptor_from_name(&first.term)
+        .ok_or_else(|| format!("课表分享码学期无效: {}", first.term))?;
+
+    let time_config = load_term_time_config(&app, &term).await;
+    let anchor = resolve_term_anchor(&term, &time_config);
+    let weeks = input.weeks.unwrap_or_else(|| (1..=16).collect());
+    let days = if input.include_weekends.unwrap_or(false) {
+        (1..=7).collect::<Vec<_>>()
+    } else {
+        (1..=5).collect::<Vec<_>>()
+    };
+    let free_days = freetime::find_common_free_slots(
+        &shares,
+        &time_config.session_times,
+        &weeks,
+        &days,
+        Some(anchor),
+    );
+
+    Ok(envelope(
+        json!({
+            "term": term,
+            "participants": shares.iter().map(|share| share.label.clone()).collect::<Vec<_>>(),
+            "sessionTimes": time_config.session_times,
+            "days": free_days,
+        }),
+        "network",
+    ))
+}
+
+fn notify_upcoming_timetable_changes(
+    app: &AppHandle,
+    term: &TermDescriptor,
+    time_config: &TermTimeConfig,
+    changes: &[TimetableChange],
+) {
+    let anchor = resolve_term_anchor(term, time_config);
+    let weeks = teaching_weeks_within(anchor, Local::now().date_naive(), UPCOMING_CHANGE_DAYS);
+    let upcoming = changes
+        .iter()
+        .filter(|change| {
+            change
+                .affected_weeks
+                .iter()
+                .any(|week| weeks.contains(week))
+        })
+        .map(|change| change.summary.as_str())
+        .collect::<Vec<_>>();
+    if upcoming.is_empty() {
+        return;
+    }
+
+    let _ = app
+        .notification()
+        .builder()
+        .title(format!("课表变动（{} 项）", upcoming.len()))
+        .body(upcoming.join("\n"))
+        .show();
+}
+
 fn normalize_todo_item(todo: &Value) -> Option<Value> {
     let title = todo
         .get("title")
@@ -339,6 +647,11 @@ fn normalize_todo_item(todo: &Value) -> Option<Value> {
         .map(str::trim)
         .unwrap_or("")
         .to_string();
+    let kind = todo
+        .get("type")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
     let status = todo
         .get("status")
         .and_then(Value::as_str)
@@ -360,6 +673,7 @@ fn normalize_todo_item(todo: &Value) -> Option<Value> {
         "endTime": end_time,
         "end_time": end_time,
         "status": status,
+        "type": kind,
         "linkUrl": link_url,
         "raw": todo,
     }))
@@ -386,21 +700,116 @@ fn normalize_todos_payload(raw: Value) -> Value {
     })
 }
 
+/// `(course id, display name)` of 学在浙大 learning courses.
+fn learning_course_names(learning_courses: &[Value]) -> Vec<(i64, String)> {
+    learning_courses
+        .iter()
+        .filter_map(|course| {
+            let name = course
+                .get("display_name")
+                .or_else(|| course.get("name"))
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .trim()
+                .to_string();
+            Some((course.get("id").and_then(Value::as_i64)?, name))
+        })
+        .collect()
+}
+
+/// Adds open quizzes and online exams, plus unwatched videos due soon, that
+/// `/api/todos` left out. Failures produce warnings and are returned as
+/// failed sources, so the todo history does not mistake them for completions.
+async fn merge_activity_todos(
+    state: &State<'_, Arc<AppState>>,
+    data: &mut Value,
+) -> (Vec<String>, todo_sync::FailedSources) {
+    let learning_courses = match courses::get_learning_courses(state).await {
+        Ok(learning_courses) => learning_courses,
+        Err(error) => {
+            let failed = todo_sync::FailedSources {
+                kinds: vec![quizzes::QUIZ_TODO_TYPE, videos::VIDEO_ACTIVITY_TYPE],
+                courses: Vec::new(),
+            };
+            return (vec![format!("课程活动: {error}")], failed);
+        }
+    };
+    let course_list = learning_course_names(&learning_courses);
+    let ((quiz_groups, mut warnings), (video_groups, video_warnings)) = tokio::join!(
+        quizzes::fetch_quizzes(state.inner().clone(), course_list.clone()),
+        videos::fetch_videos(state.inner().clone(), course_list.clone()),
+    );
+    warnings.extend(video_warnings);
+
+    let mut failed = todo_sync::FailedSources::default();
+    for (course_id, _) in &course_list {
+        if !quiz_groups.iter().any(|(synced, _)| synced == course_id) {
+            failed.courses.push((quizzes::QUIZ_TODO_TYPE, *course_id));
+        }
+        if !video_groups.iter().any(|(synced, _)| synced == course_id) {
+            failed
+                .courses
+                .push((videos::VIDEO_ACTIVITY_TYPE, *course_id));
+        }
+    }
+    let quiz_list = quiz_groups
+        .into_iter()
+        .flat_map(|(_, quizzes)| quizzes)
+        .collect::<Vec<_>>();
+    let video_list = video_groups
+        .into_iter()
+        .flat_map(|(_, videos)| videos)
+        .collect::<Vec<_>>();
+
+    if !data.get("todo_list").is_some_and(Value::is_array) {
+        data["todo_list"] = json!([]);
+    }
+    if let Some(todo_list) = data.get_mut("todo_list").and_then(Value::as_array_mut) {
+        let mut known = todo_list
+            .iter()
+            .filter_map(|todo| todo.get("id").and_then(Value::as_i64))
+            .collect::<HashSet<_>>();
+        let now = Utc::now();
+        for todo in quizzes::quiz_todos(&quiz_list, now)
+            .into_iter()
+            .chain(videos::video_todos(&video_list, now))
+        {
+            if todo["id"].as_i64().is_none_or(|id| known.insert(id)) {
+                todo_list.push(todo);
+            }
+        }
+    }
+    (warnings, failed)
+}
+
+/// Video activities of every learning course with the user's watch progress.
 #[tauri::command]
-async fn fetch_todos(
+async fn fetch_video_activities(
     app: AppHandle,
     state: State<'_, Arc<AppState>>,
     force_refresh: Option<bool>,
 ) -> Result<Value, String> {
     let force_refresh = force_refresh.unwrap_or(false);
-    match courses::get_todos(&state).await {
-        Ok(data) => {
-            let env = envelope(normalize_todos_payload(data), "network");
-            cache_write_envelope(&app, TODOS_CACHE_FILE, &env);
+    match courses::get_learning_courses(&state).await {
+        Ok(learning_courses) => {
+            let (video_groups, warnings) = videos::fetch_videos(
+                state.inner().clone(),
+                learning_course_names(&learning_courses),
+            )
+            .await;
+            let video_list = video_groups
+                .into_iter()
+                .flat_map(|(_, videos)| videos)
+                .collect::<Vec<_>>();
+            let env = envelope(
+                json!({ "videos": video_list, "warnings": warnings }),
+                "network",
+            );
+            cache_write_envelope(&app, VIDEOS_CACHE_FILE, &env);
             Ok(env)
         }
         Err(error) => {
-            if let Some(cached) = cache_read_envelope(&app, TODOS_CACHE_FILE) {
+            if let Some(cached) = cache_read_envelope(&app, VIDEOS_CACHE_FILE) {
                 return Ok(annotate_cache_fallback(cached, force_refresh, &error));
             }
             Err(error)
@@ -409,55 +818,316 @@ async fn fetch_todos(
 }
 
 #[tauri::command]
-fn fetch_materials(app: AppHandle) -> Result<Value, String> {
-    Ok(envelope(materials::fetch_materials(&app)?, "network"))
-}
-
-#[tauri::command]
-async fn sync_materials_index(
+async fn fetch_todos(
     app: AppHandle,
     state: State<'_, Arc<AppState>>,
+    force_refresh: Option<bool>,
 ) -> Result<Value, String> {
-    Ok(envelope(
-        materials::sync_materials_index(&app, &state).await?,
-        "network",
-    ))
+    let force_refresh = force_refresh.unwrap_or(false);
+    match courses::get_todos(&state).await {
+        Ok(mut data) => {
+            let (mut warnings, failed) = merge_activity_todos(&state, &mut data).await;
+            let mut payload = normalize_todos_payload(data);
+            let todo_list = payload["todo_list"].as_array().cloned().unwrap_or_default();
+            let changes = todo_sync::record(&app, &todo_list, &failed).unwrap_or_else(|error| {
+                warnings.push(error);
+                todo_sync::TodoDelta::default()
+            });
+            if !changes.is_empty() {
+                let _ = app.emit(todo_sync::TODOS_CHANGED_EVENT, &changes);
+            }
+            payload["warnings"] = json!(warnings);
+            payload["changes"] = json!(changes);
+            let env = envelope(payload, "network");
+            cache_write_envelope(&app, TODOS_CACHE_FILE, &env);
+            Ok(env)
+        }
+        Err(error) => {
+            if let Some(cached) = cache_read_envelope(&app, TODOS_CACHE_FILE) {
+                return Ok(annotate_cache_fallback(cached, force_refresh, &error));
+            }
+            Err(error)
+        }
+    }
 }
 
+/// Todo changes recorded by past syncs, newest first.
 #[tauri::command]
-async fn download_material_asset(
-    app: AppHandle,
-    input: DownloadMaterialInput,
-) -> Result<Value, String> {
+fn fetch_todo_history(app: AppHandle) -> Result<Value, String> {
+    let history = todo_sync::load_history(&app);
     Ok(envelope(
-        materials::download_material_asset(&app, input).await?,
+        json!({ "deltas": history.deltas, "lastSyncedAt": history.last_synced_at }),
         "network",
     ))
 }
 
+/// Resolves a todo to its homework activity and returns the assignment with
+/// the user's submissions, score and feedback. Falls back to the last fetched
+/// detail when offline.
 #[tauri::command]
-async fn cache_remote_material(
+async fn fetch_todo_detail(
     app: AppHandle,
     state: State<'_, Arc<AppState>>,
-    input: RemoteMaterialDownloadInput,
+    todo_id: String,
+    force_refresh: Option<bool>,
+) -> Result<Value, String> {
+    let force_refresh = force_refresh.unwrap_or(false);
+    let todo = cache_read_envelope(&app, TODOS_CACHE_FILE).and_then(|env| {
+        env.pointer("/data/todo_list")
+            .and_then(Value::as_array)
+            .and_then(|todos| {
+                todos
+                    .iter()
+                    .find(|todo| todo.get("id").and_then(Value::as_str) == Some(todo_id.as_str()))
+                    .cloned()
+            })
+    });
+    if let Some(kind) = todo
+        .as_ref()
+        .and_then(|todo| todo.pointer("/raw/type"))
+        .and_then(Value::as_str)
+    {
+        if kind != "homework" {
+            return Err(format!("该待办不是作业（{kind}）"));
+        }
+    }
+    let activity_id = todo_id
+        .trim()
+        .parse::<i64>()
+        .map_err(|_| format!("无法识别的待办编号: {todo_id}"))?;
+    let course_name = todo
+        .as_ref()
+        .and_then(|todo| todo.get("courseName"))
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+
+    let (activity, submissions) = tokio::join!(
+        courses::get_activity(&state, activity_id),
+        courses::get_activity_submissions(&state, activity_id),
+    );
+    // A detail without its submissions would read as "not submitted", so a
+    // failed submissions request fails the whole fetch and keeps the cache.
+    let fetched = activity.and_then(|activity| {
+        homework::parse_homework_detail(&activity, &submissions?, &course_name)
+    });
+
+    let mut details = cache_read_envelope(&app, TODO_DETAILS_CACHE_FILE)
+        .and_then(|env| env.get("data").cloned())
+        .filter(Value::is_object)
+        .unwrap_or_else(|| json!({}));
+    match fetched {
+        Ok(detail) => {
+            let env = envelope(json!(detail), "network");
+            details[todo_id.as_str()] = env.clone();
+            cache_write_envelope(&app, TODO_DETAILS_CACHE_FILE, &envelope(details, "network"));
+            Ok(env)
+        }
+        Err(error) => details
+            .get(todo_id.as_str())
+            .cloned()
+            .map(|cached| annotate_cache_fallback(cached, force_refresh, &error))
+            .ok_or(error),
+    }
+}
+
+/// Uploads attachments and submits (or drafts) a homework todo in place of
+/// sending the user to the web page.
+#[tauri::command]
+async fn submit_homework(
+    app: AppHandle,
+    state: State<'_, Arc<AppState>>,
+    input: HomeworkSubmissionInput,
+) -> Result<Value, String> {
+    let result = submission::submit(&app, &state, input).await?;
+    Ok(envelope(json!(result), "network"))
+}
+
+/// Roll-call history of every learning course, flagging courses whose
+/// attendance rate is below `threshold` (0–1). Falls back to the last fetched
+/// records when offline.
+#[tauri::command]
+async fn fetch_attendance(
+    app: AppHandle,
+    state: State<'_, Arc<AppState>>,
+    threshold: Option<f64>,
+    force_refresh: Option<bool>,
+) -> Result<Value, String> {
+    let force_refresh = force_refresh.unwrap_or(false);
+    let threshold = threshold
+        .filter(|value| (0.0..=1.0).contains(value))
+        .unwrap_or(attendance::DEFAULT_THRESHOLD);
+
+    match courses::get_learning_courses(&state).await {
+        Ok(learning_courses) => {
+            let course_list = learning_course_names(&learning_courses);
+            let course_ids = course_list.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+            let (mut course_attendance, warnings) =
+                attendance::fetch_attendance(state.inner().clone(), course_list).await;
+            if !warnings.is_empty() {
+                let cached = cache_read_envelope(&app, ATTENDANCE_CACHE_FILE)
+                    .and_then(|cached| serde_json::from_value(cached.get("data")?.clone()).ok())
+                    .unwrap_or_default();
+                course_attendance =
+                    attendance::merge_cached(course_attendance, cached, &course_ids);
+            }
+            cache_write_envelope(
+                &app,
+                ATTENDANCE_CACHE_FILE,
+                &envelope(json!(course_attendance), "network"),
+            );
+            let report = attendance::report(course_attendance, threshold, warnings);
+            Ok(envelope(json!(report), "network"))
+        }
+        Err(error) => {
+            let cached = cache_read_envelope(&app, ATTENDANCE_CACHE_FILE).ok_or(error.clone())?;
+            let course_attendance = cached
+                .get("data")
+                .cloned()
+                .and_then(|data| serde_json::from_value(data).ok())
+                .unwrap_or_default();
+            let mut env = cached;
+            env["data"] = json!(attendance::report(course_attendance, threshold, Vec::new()));
+            Ok(annotate_cache_fallback(env, force_refresh, &error))
+        }
+    }
+}
+
+/// Syncs course announcements and the 学在浙大 inbox into one feed. When
+/// offline the stored feed is returned instead.
+#[tauri::command]
+async fn sync_notification_feed(
+    app: AppHandle,
+    state: State<'_, Arc<AppState>>,
+    force_refresh: Option<bool>,
+) -> Result<Value, String> {
+    match notifications::sync_feed(&app, &state).await {
+        Ok(feed) => Ok(envelope(json!(feed), "network")),
+        Err(error) => {
+            let stored = notifications::load_feed(&app);
+            let Some(synced_at) = stored.last_synced_at else {
+                return Err(error);
+            };
+            let mut env = envelope(
+                json!(notifications::view(&stored, Vec::new(), Vec::new())),
+                "cache",
+            );
+            env["_meta"]["timestamp"] = json!(synced_at);
+            Ok(annotate_cache_fallback(
+                env,
+                force_refresh.unwrap_or(false),
+                &error,
+            ))
+        }
+    }
+}
+
+#[tauri::command]
+fn mark_notifications_read(app: AppHandle, ids: Option<Vec<String>>) -> Result<Value, String> {
+    let feed = notifications::mark_read(&app, ids)?;
+    Ok(envelope(json!(feed), "network"))
+}
+
+#[tauri::command]
+fn fetch_materials(app: AppHandle) -> Result<Value, String> {
+    Ok(envelope(materials::fetch_materials(&app)?, "network"))
+}
+
+/// Closed 学在浙大 courses of past terms, grouped by academic year and
+/// semester.
+#[tauri::command]
+async fn fetch_course_archive(
+    app: AppHandle,
+    state: State<'_, Arc<AppState>>,
+    force_refresh: Option<bool>,
+) -> Result<Value, String> {
+    let force_refresh = force_refresh.unwrap_or(false);
+    match courses::get_archived_courses(&state).await {
+        Ok(archived) => {
+            let env = envelope(json!(archive::group_by_term(&archived)), "network");
+            cache_write_envelope(&app, COURSE_ARCHIVE_CACHE_FILE, &env);
+            Ok(env)
+        }
+        Err(error) => {
+            if let Some(cached) = cache_read_envelope(&app, COURSE_ARCHIVE_CACHE_FILE) {
+                return Ok(annotate_cache_fallback(cached, force_refresh, &error));
+            }
+            Err(error)
+        }
+    }
+}
+
+/// Syncs the current term's materials, or those of `archive_term` from the
+/// course archive.
+#[tauri::command]
+async fn sync_materials_index(
+    app: AppHandle,
+    state: State<'_, Arc<AppState>>,
+    archive_term: Option<ArchiveTermKey>,
+) -> Result<Value, String> {
+    Ok(envelope(
+        materials::sync_materials_index(&app, &state, archive_term).await?,
+        "network",
+    ))
+}
+
+/// Replay video streams (teacher and screen tracks) of a 智云课堂 lecture.
+#[tauri::command]
+async fn fetch_lecture_streams(
+    state: State<'_, Arc<AppState>>,
+    course_id: i64,
+    sub_id: i64,
+) -> Result<Value, String> {
+    let streams = lecture_video::lecture_streams(&state, course_id, sub_id).await?;
+    Ok(envelope(json!({ "streams": streams }), "network"))
+}
+
+/// Downloads a lecture replay into the materials library, resuming earlier
+/// partial downloads. Progress is emitted as `lecture-download-progress`.
+#[tauri::command]
+async fn download_lecture_replay(
+    app: AppHandle,
+    state: State<'_, Arc<AppState>>,
+    input: LectureDownloadInput,
+) -> Result<Value, String> {
+    let items = lecture_video::download(&app, &state, input).await?;
+    Ok(envelope(json!({ "items": items }), "network"))
+}
+
+#[tauri::command]
+async fn download_material_asset(
+    app: AppHandle,
+    input: DownloadMaterialInput,
 ) -> Result<Value, String> {
     Ok(envelope(
-        materials::cache_remote_material(&app, &state, input).await?,
-        "network",
-    ))
-}
-
-#[tauri::command]
-fn read_material_text(app: AppHandle, input: MaterialContentInput) -> Result<Value, String> {
-    Ok(envelope(
-        materials::read_material_text(&app, input)?,
-        "network",
-    ))
-}
-
-#[tauri::command]
-fn open_material_asset(app: AppHandle, input: MaterialPathInput) -> Result<Value, String> {
-    Ok(envelope(
+        materials::download_material_asset(&app, input).await?,
+        "network",
+    ))
+}
+
+#[tauri::command]
+async fn cache_remote_material(
+    app: AppHandle,
+    state: State<'_, Arc<AppState>>,
+    input: RemoteMaterialDownloadInput,
+) -> Result<Value, String> {
+    Ok(envelope(
+        materials::cache_remote_material(&app, &state, input).await?,
+        "network",
+    ))
+}
+
+#[tauri::command]
+fn read_material_text(app: AppHandle, input: MaterialContentInput) -> Result<Value, String> {
+    Ok(envelope(
+        materials::read_material_text(&app, input)?,
+        "network",
+    ))
+}
+
+#[tauri::command]
+fn open_material_asset(app: AppHandle, input: MaterialPathInput) -> Result<Value, String> {
+    Ok(envelope(
         materials::open_material_asset(&app, input)?,
         "network",
     ))
@@ -496,33 +1166,27 @@ struct GpaPreviewInput {
     retake_policy: Option<String>,
     major_course_ids: Option<Vec<String>>,
     course_id_mappings: Option<HashMap<String, String>>,
+    scale_ids: Option<Vec<String>>,
+    retake_overrides: Option<HashMap<String, RetakeOverride>>,
 }
 
-#[tauri::command]
-fn calculate_gpa_preview(input: GpaPreviewInput) -> Result<Value, String> {
-    let selected = input
-        .selected_ids
-        .unwrap_or_default()
-        .into_iter()
-        .collect::<HashSet<_>>();
-    let has_selection = !selected.is_empty();
-    let simulated = input.simulated_scores.unwrap_or_default();
-    let course_mappings = input.course_id_mappings.unwrap_or_default();
-    let major_set = input
-        .major_course_ids
-        .unwrap_or_default()
-        .into_iter()
-        .collect::<HashSet<_>>();
-
+/// Enrich `raw`, keep only `selected` rows (all when empty), then apply
+/// simulated scores and course id mappings.
+fn preview_grades(
+    raw: &[Value],
+    selected: &HashSet<String>,
+    simulated: &HashMap<String, f64>,
+    course_mappings: &HashMap<String, String>,
+) -> Vec<Value> {
     let mut grades = Vec::new();
-    for raw in &input.grades {
+    for raw in raw {
         let mut grade = enrich_grade(raw);
         let xkkh = grade
             .get("xkkh")
             .and_then(Value::as_str)
             .unwrap_or_default()
             .to_string();
-        if has_selection && !selected.contains(&xkkh) {
+        if !selected.is_empty() && !selected.contains(&xkkh) {
             continue;
         }
 
@@ -544,12 +1208,676 @@ fn calculate_gpa_preview(input: GpaPreviewInput) -> Result<Value, String> {
             apply_simulated_score(&mut grade, *score);
         }
 
+        apply_course_mapping(&mut grade, course_mappings);
+        grades.push(grade);
+    }
+    grades
+}
+
+#[tauri::command]
+fn calculate_gpa_preview(app: AppHandle, input: GpaPreviewInput) -> Result<Value, String> {
+    let selected = input
+        .selected_ids
+        .unwrap_or_default()
+        .into_iter()
+        .collect::<HashSet<_>>();
+    let major_set = input
+        .major_course_ids
+        .unwrap_or_default()
+        .into_iter()
+        .collect::<HashSet<_>>();
+    let grades = preview_grades(
+        &input.grades,
+        &selected,
+        &input.simulated_scores.unwrap_or_default(),
+        &input.course_id_mappings.unwrap_or_default(),
+    );
+
+    let selection = retake_selection(input.retake_policy.as_deref(), input.retake_overrides)?;
+    let (scales, _) = gpa_scale::load_scales(&app);
+    let scales = gpa_scale::select_scales(scales, input.scale_ids.as_deref());
+    Ok(json!(compute_gpa_by_policy(
+        &grades, &major_set, &selection, &scales
+    )))
+}
+
+fn retake_selection(
+    policy: Option<&str>,
+    overrides: Option<HashMap<String, RetakeOverride>>,
+) -> Result<RetakeSelection, String> {
+    Ok(RetakeSelection {
+        policy: policy.unwrap_or("first").parse::<RetakePolicy>()?,
+        overrides: overrides.unwrap_or_default(),
+    })
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GpaPlanInput {
+    grades: Vec<Value>,
+    target: f64,
+    scope: Option<PlanScope>,
+    metric: Option<PlanMetric>,
+    planned_ids: Option<Vec<String>>,
+    planned_courses: Option<Vec<PlannedCourseInput>>,
+    retake_policy: Option<String>,
+    retake_overrides: Option<HashMap<String, RetakeOverride>>,
+    major_course_ids: Option<Vec<String>>,
+    course_id_mappings: Option<HashMap<String, String>>,
+}
+
+#[tauri::command]
+fn plan_target_gpa(input: GpaPlanInput) -> Result<Value, String> {
+    let planned_ids = input
+        .planned_ids
+        .unwrap_or_default()
+        .into_iter()
+        .collect::<HashSet<_>>();
+    let course_mappings = input.course_id_mappings.unwrap_or_default();
+    let mut major_set = input
+        .major_course_ids
+        .unwrap_or_default()
+        .into_iter()
+        .collect::<HashSet<_>>();
+
+    let mut grades = Vec::new();
+    let mut slots = Vec::new();
+    for raw in &input.grades {
+        let mut grade = enrich_grade(raw);
+        apply_course_mapping(&mut grade, &course_mappings);
+        let xkkh = grade
+            .get("xkkh")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+        if planned_ids.contains(&xkkh) {
+            slots.push(PlanSlot {
+                index: grades.len(),
+                id: xkkh,
+                course_name: grade
+                    .get("kcmc")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+                credit: grade
+                    .get("credit")
+                    .and_then(Value::as_f64)
+                    .unwrap_or_default(),
+            });
+        }
+        grades.push(grade);
+    }
+
+    for course in input.planned_courses.unwrap_or_default() {
+        if course.credit <= 0.0 {
+            continue;
+        }
+        // 学在浙大 course codes are usually full xkkh values; fall back to the
+        // course id so the synthetic row still gets a stable retake key.
+        let xkkh = if course.course_code.starts_with('(') {
+            course.course_code.clone()
+        } else {
+            course.id.clone()
+        };
+        if slots.iter().any(|slot| slot.id == xkkh) {
+            continue;
+        }
+        if course.major {
+            major_set.insert(xkkh.clone());
+            if !course.course_code.is_empty() {
+                major_set.insert(course.course_code.clone());
+            }
+        }
+        let mut grade = enrich_grade(&json!({
+            "xkkh": xkkh,
+            "kcdm": course.course_code,
+            "kcmc": course.course_name,
+            "xf": course.credit,
+            "cj": "待录",
+        }));
         apply_course_mapping(&mut grade, &course_mappings);
+        slots.push(PlanSlot {
+            index: grades.len(),
+            id: course.id,
+            course_name: course.course_name,
+            credit: course.credit,
+        });
         grades.push(grade);
     }
 
-    let policy = RetakePolicy::from_str(input.retake_policy.as_deref().unwrap_or("first"));
-    Ok(json!(compute_gpa_by_policy(&grades, &major_set, policy)))
+    let scope = input.scope.unwrap_or(PlanScope::Overall);
+    let metric = input.metric.unwrap_or(match scope {
+        PlanScope::Overall => PlanMetric::FivePoint,
+        PlanScope::Major => PlanMetric::FourPoint,
+    });
+    let selection = retake_selection(input.retake_policy.as_deref(), input.retake_overrides)?;
+    let plan = gpa_plan::plan_target_gpa(
+        grades,
+        &slots,
+        &major_set,
+        &selection,
+        scope,
+        metric,
+        input.target,
+    )?;
+    Ok(json!(plan))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GpaAnalyticsInput {
+    grades: Vec<Value>,
+    retake_policy: Option<String>,
+    retake_overrides: Option<HashMap<String, RetakeOverride>>,
+    major_course_ids: Option<Vec<String>>,
+    course_id_mappings: Option<HashMap<String, String>>,
+    scale_ids: Option<Vec<String>>,
+}
+
+#[tauri::command]
+fn analyze_gpa(app: AppHandle, input: GpaAnalyticsInput) -> Result<Value, String> {
+    let course_mappings = input.course_id_mappings.unwrap_or_default();
+    let major_set = input
+        .major_course_ids
+        .unwrap_or_default()
+        .into_iter()
+        .collect::<HashSet<_>>();
+    let grades = input
+        .grades
+        .iter()
+        .map(|raw| {
+            let mut grade = enrich_grade(raw);
+            apply_course_mapping(&mut grade, &course_mappings);
+            grade
+        })
+        .collect::<Vec<_>>();
+
+    let selection = retake_selection(input.retake_policy.as_deref(), input.retake_overrides)?;
+    let (scales, _) = gpa_scale::load_scales(&app);
+    let scales = gpa_scale::select_scales(scales, input.scale_ids.as_deref());
+    Ok(json!(gpa_analytics::analyze_transcript(
+        &grades, &major_set, &selection, &scales
+    )))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GpaCategoryInput {
+    grades: Vec<Value>,
+    retake_overrides: Option<HashMap<String, RetakeOverride>>,
+    major_course_ids: Option<Vec<String>>,
+    course_id_mappings: Option<HashMap<String, String>>,
+    category_overrides: Option<HashMap<String, CourseCategory>>,
+    scale_ids: Option<Vec<String>>,
+}
+
+#[tauri::command]
+fn calculate_gpa_by_category(app: AppHandle, input: GpaCategoryInput) -> Result<Value, String> {
+    let course_mappings = input.course_id_mappings.unwrap_or_default();
+    let retake_overrides = input.retake_overrides.unwrap_or_default();
+    let major_set = input
+        .major_course_ids
+        .unwrap_or_default()
+        .into_iter()
+        .collect::<HashSet<_>>();
+    let grades = input
+        .grades
+        .iter()
+        .map(|raw| {
+            let mut grade = enrich_grade(raw);
+            apply_course_mapping(&mut grade, &course_mappings);
+            grade
+        })
+        .collect::<Vec<_>>();
+    let assignments = gpa_category::categorize_grades(
+        &grades,
+        &major_set,
+        &input.category_overrides.unwrap_or_default(),
+    );
+
+    let (scales, _) = gpa_scale::load_scales(&app);
+    let scales = gpa_scale::select_scales(scales, input.scale_ids.as_deref());
+    let categories = CourseCategory::ALL
+        .iter()
+        .filter_map(|category| {
+            let members = grades
+                .iter()
+                .zip(&assignments)
+                .filter(|(_, assignment)| assignment.category == *category)
+                .map(|(grade, _)| grade.clone())
+                .collect::<Vec<_>>();
+            if members.is_empty() {
+                return None;
+            }
+            let by_policy = gpa_by_policy(&members, &major_set, &retake_overrides, &scales);
+            Some(json!({
+                "category": category,
+                "label": category.label(),
+                "courseCount": members.len(),
+                "gpaByPolicy": gpa_by_policy_json(by_policy),
+            }))
+        })
+        .collect::<Vec<_>>();
+
+    Ok(json!({
+        "categories": categories,
+        "assignments": assignments,
+    }))
+}
+
+#[tauri::command]
+fn list_gpa_scales(app: AppHandle) -> Result<Value, String> {
+    let (scales, errors) = gpa_scale::load_scales(&app);
+    Ok(envelope(
+        json!({
+            "scales": scales,
+            "errors": errors,
+        }),
+        "network",
+    ))
+}
+
+#[tauri::command]
+fn import_gpa_scale(app: AppHandle, input: GpaScaleImportInput) -> Result<Value, String> {
+    let scale = gpa_scale::import_scale(&app, input)?;
+    Ok(envelope(json!(scale), "network"))
+}
+
+#[tauri::command]
+fn delete_gpa_scale(app: AppHandle, id: String) -> Result<Value, String> {
+    gpa_scale::delete_scale(&app, &id)?;
+    Ok(envelope(json!({ "id": id }), "network"))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GpaScenarioRunInput {
+    ids: Vec<String>,
+    /// Latest transcript; the cached scholar data is used when omitted.
+    grades: Option<Vec<Value>>,
+    major_course_ids: Option<Vec<String>>,
+    /// Policy for the unsimulated baseline in comparisons.
+    retake_policy: Option<String>,
+}
+
+fn scenario_transcript(
+    app: &AppHandle,
+    grades: Option<Vec<Value>>,
+    major_course_ids: Option<Vec<String>>,
+) -> Result<(Vec<Value>, HashSet<String>), String> {
+    let cached = cache_read_envelope(app, SCHOLAR_CACHE_FILE);
+    let cached_field = |key: &str| {
+        cached
+            .as_ref()
+            .and_then(|env| env.get("data"))
+            .and_then(|data| data.get(key))
+            .and_then(Value::as_array)
+            .cloned()
+    };
+    let grades = grades
+        .or_else(|| cached_field("transcript"))
+        .ok_or("没有可用的成绩单，请先刷新学业数据")?;
+    let major_set = major_course_ids
+        .or_else(|| {
+            cached_field("majorCourseIds").map(|ids| {
+                ids.iter()
+                    .filter_map(Value::as_str)
+                    .map(str::to_string)
+                    .collect()
+            })
+        })
+        .unwrap_or_default()
+        .into_iter()
+        .collect::<HashSet<_>>();
+    Ok((grades, major_set))
+}
+
+fn run_scenario(
+    app: &AppHandle,
+    scenario: &GpaScenario,
+    transcript: &[Value],
+    major_set: &HashSet<String>,
+) -> Result<(GpaSummary, Value), String> {
+    let grades = preview_grades(
+        &scenario::merge_planned_grades(transcript, scenario),
+        &scenario.selected_ids.iter().cloned().collect(),
+        &scenario.simulated_scores,
+        &scenario.course_id_mappings,
+    );
+    let selection = retake_selection(
+        scenario.retake_policy.as_deref(),
+        Some(scenario.retake_overrides.clone()),
+    )?;
+    let (scales, _) = gpa_scale::load_scales(app);
+    let scales = gpa_scale::select_scales(scales, scenario.scale_ids.as_deref());
+    let summary = compute_gpa_by_policy(&grades, major_set, &selection, &scales);
+    let stale = scenario::stale_simulations(transcript, scenario);
+    let result = json!({
+        "scenario": scenario,
+        "summary": summary,
+        "stale": stale,
+    });
+    Ok((summary, result))
+}
+
+#[tauri::command]
+fn list_gpa_scenarios(app: AppHandle) -> Result<Value, String> {
+    Ok(envelope(
+        json!({ "scenarios": scenario::load_scenarios(&app) }),
+        "network",
+    ))
+}
+
+#[tauri::command]
+fn create_gpa_scenario(app: AppHandle, input: GpaScenarioInput) -> Result<Value, String> {
+    let created = scenario::create_scenario(&app, input)?;
+    Ok(envelope(json!(created), "network"))
+}
+
+#[tauri::command]
+fn update_gpa_scenario(
+    app: AppHandle,
+    id: String,
+    input: GpaScenarioInput,
+) -> Result<Value, String> {
+    let updated = scenario::update_scenario(&app, &id, input)?;
+    Ok(envelope(json!(updated), "network"))
+}
+
+#[tauri::command]
+fn delete_gpa_scenario(app: AppHandle, id: String) -> Result<Value, String> {
+    scenario::delete_scenario(&app, &id)?;
+    Ok(envelope(json!({ "id": id }), "network"))
+}
+
+#[tauri::command]
+fn duplicate_gpa_scenario(
+    app: AppHandle,
+    id: String,
+    name: Option<String>,
+) -> Result<Value, String> {
+    let copy = scenario::duplicate_scenario(&app, &id, name)?;
+    Ok(envelope(json!(copy), "network"))
+}
+
+#[tauri::command]
+fn load_gpa_scenario(
+    app: AppHandle,
+    id: String,
+    grades: Option<Vec<Value>>,
+    major_course_ids: Option<Vec<String>>,
+) -> Result<Value, String> {
+    let saved = scenario::find_scenario(&app, &id)?;
+    let (transcript, major_set) = scenario_transcript(&app, grades, major_course_ids)?;
+    Ok(run_scenario(&app, &saved, &transcript, &major_set)?.1)
+}
+
+#[tauri::command]
+fn compare_gpa_scenarios(app: AppHandle, input: GpaScenarioRunInput) -> Result<Value, String> {
+    if input.ids.is_empty() {
+        return Err("请选择要对比的模拟方案".to_string());
+    }
+    let (transcript, major_set) = scenario_transcript(&app, input.grades, input.major_course_ids)?;
+    let baseline_grades = transcript.iter().map(enrich_grade).collect::<Vec<_>>();
+    let baseline = compute_gpa_by_policy(
+        &baseline_grades,
+        &major_set,
+        &retake_selection(input.retake_policy.as_deref(), None)?,
+        &[],
+    );
+
+    let mut results = Vec::with_capacity(input.ids.len());
+    for id in &input.ids {
+        let saved = scenario::find_scenario(&app, id)?;
+        let (summary, mut result) = run_scenario(&app, &saved, &transcript, &major_set)?;
+        result["delta"] = json!({
+            "fivePoint": summary.five_point - baseline.five_point,
+            "fourPoint": summary.four_point - baseline.four_point,
+            "fourPointLegacy": summary.four_point_legacy - baseline.four_point_legacy,
+            "hundredPoint": summary.hundred_point - baseline.hundred_point,
+            "totalCredits": summary.total_credits - baseline.total_credits,
+        });
+        results.push(result);
+    }
+
+    Ok(json!({
+        "baseline": baseline,
+        "results": results,
+    }))
+}
+
+#[tauri::command]
+fn audit_transcript(app: AppHandle, grades: Option<Vec<Value>>) -> Result<Value, String> {
+    let (grades, _) = scenario_transcript(&app, grades, None)?;
+    Ok(json!(audit::audit_transcript(&grades)))
+}
+
+/// Compares our GPA under every retake policy with what zdbk reports. A
+/// missing or unreachable summary falls back to the per-course `jd` values.
+#[tauri::command]
+async fn reconcile_gpa(
+    app: AppHandle,
+    state: State<'_, Arc<AppState>>,
+    grades: Option<Vec<Value>>,
+    retake_overrides: Option<HashMap<String, RetakeOverride>>,
+) -> Result<Value, String> {
+    let (grades, _) = scenario_transcript(&app, grades, None)?;
+    let (official, official_error) = match zdbk::get_gpa_summary(&state).await {
+        Ok(summary) => (summary, None),
+        Err(error) => (None, Some(error)),
+    };
+    let report = reconcile::reconcile(
+        &grades,
+        official,
+        &RETAKE_POLICIES,
+        &retake_overrides.unwrap_or_default(),
+    );
+    let mut payload = json!(report);
+    payload["officialError"] = json!(official_error);
+    Ok(envelope(payload, "network"))
+}
+
+/// Migrates data exported from the Flutter app. Dry runs only report; the
+/// returned `courseIdMappings` are for the caller to save into preferences.
+#[tauri::command]
+fn import_legacy_data(app: AppHandle, input: LegacyImportInput) -> Result<Value, String> {
+    let grades = scenario_transcript(&app, None, None)
+        .map(|(grades, _)| grades)
+        .unwrap_or_default();
+    let report = legacy_import::import_backup(&app, input, grades)?;
+    Ok(json!(report))
+}
+
+#[tauri::command]
+fn list_local_tasks(app: AppHandle) -> Result<Value, String> {
+    Ok(envelope(
+        json!({ "tasks": local_tasks::load_tasks(&app) }),
+        "network",
+    ))
+}
+
+#[tauri::command]
+fn delete_local_task(app: AppHandle, id: String) -> Result<Value, String> {
+    local_tasks::delete_task(&app, &id)?;
+    Ok(envelope(json!({ "id": id }), "network"))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EvaluationInput {
+    grades: Vec<Value>,
+    rule_id: Option<String>,
+    /// An unsaved rule, e.g. while the user is editing weights.
+    rule: Option<Value>,
+    term_window: Option<TermWindow>,
+    practice: Option<zdbk::PracticeScores>,
+    bonuses: Option<HashMap<String, f64>>,
+    retake_policy: Option<String>,
+    retake_overrides: Option<HashMap<String, RetakeOverride>>,
+    major_course_ids: Option<Vec<String>>,
+    course_id_mappings: Option<HashMap<String, String>>,
+}
+
+#[tauri::command]
+fn evaluate_scholarship(app: AppHandle, input: EvaluationInput) -> Result<Value, String> {
+    let rule = match (input.rule, input.rule_id.as_deref()) {
+        (Some(rule), _) => evaluation::parse_rule(&rule.to_string(), evaluation::RuleOrigin::User)?,
+        (None, Some(id)) => evaluation::load_rules(&app)
+            .0
+            .into_iter()
+            .find(|rule| rule.id == id)
+            .ok_or_else(|| format!("未找到测评规则: {id}"))?,
+        (None, None) => return Err("请选择测评规则".to_string()),
+    };
+    let (scales, _) = gpa_scale::load_scales(&app);
+    let scale = scales
+        .into_iter()
+        .find(|scale| scale.id == rule.scale_id)
+        .ok_or_else(|| format!("未找到绩点标准: {}", rule.scale_id))?;
+
+    let course_mappings = input.course_id_mappings.unwrap_or_default();
+    let major_set = input
+        .major_course_ids
+        .unwrap_or_default()
+        .into_iter()
+        .collect::<HashSet<_>>();
+    let grades = input
+        .grades
+        .iter()
+        .map(|raw| {
+            let mut grade = enrich_grade(raw);
+            apply_course_mapping(&mut grade, &course_mappings);
+            grade
+        })
+        .collect::<Vec<_>>();
+    let selection = retake_selection(input.retake_policy.as_deref(), input.retake_overrides)?;
+    let window = input
+        .term_window
+        .unwrap_or_else(|| rule.term_window.clone());
+
+    Ok(json!(evaluation::evaluate(
+        &rule,
+        &window,
+        &grades,
+        &scale,
+        &major_set,
+        &selection,
+        input.practice.as_ref(),
+        &input.bonuses.unwrap_or_default(),
+    )))
+}
+
+#[tauri::command]
+fn list_evaluation_rules(app: AppHandle) -> Result<Value, String> {
+    let (rules, errors) = evaluation::load_rules(&app);
+    Ok(envelope(
+        json!({
+            "rules": rules,
+            "errors": errors,
+        }),
+        "network",
+    ))
+}
+
+#[tauri::command]
+fn import_evaluation_rule(
+    app: AppHandle,
+    input: EvaluationRuleImportInput,
+) -> Result<Value, String> {
+    let rule = evaluation::import_rule(&app, input)?;
+    Ok(envelope(json!(rule), "network"))
+}
+
+#[tauri::command]
+fn delete_evaluation_rule(app: AppHandle, id: String) -> Result<Value, String> {
+    evaluation::delete_rule(&app, &id)?;
+    Ok(envelope(json!({ "id": id }), "network"))
+}
+
+#[tauri::command]
+fn export_english_transcript(
+    app: AppHandle,
+    input: TranscriptExportInput,
+) -> Result<Value, String> {
+    let course_mappings = input.course_id_mappings.clone().unwrap_or_default();
+    let grades = input
+        .grades
+        .iter()
+        .map(|raw| {
+            let mut grade = enrich_grade(raw);
+            apply_course_mapping(&mut grade, &course_mappings);
+            grade
+        })
+        .collect::<Vec<_>>();
+    let selection = retake_selection(
+        input.retake_policy.as_deref(),
+        input.retake_overrides.clone(),
+    )?;
+    let scale_id = input.scale_id.as_deref().unwrap_or("zju-5");
+    let (scales, _) = gpa_scale::load_scales(&app);
+    let scale = scales
+        .into_iter()
+        .find(|scale| scale.id == scale_id)
+        .ok_or_else(|| format!("未找到绩点标准: {scale_id}"))?;
+    let translations = transcript_export::load_translations(&app);
+    let transcript =
+        transcript_export::build_transcript(&input, &grades, &scale, &selection, &translations);
+
+    let format = input.format.unwrap_or_default();
+    let (content, extension, mime_type) = match format {
+        TranscriptFormat::Csv => (
+            transcript_export::render_csv(&transcript),
+            "csv",
+            "text/csv",
+        ),
+        TranscriptFormat::Json => (
+            serde_json::to_string_pretty(&transcript).map_err(|error| error.to_string())?,
+            "json",
+            "application/json",
+        ),
+        TranscriptFormat::Html => (
+            transcript_export::render_html(&transcript),
+            "html",
+            "text/html",
+        ),
+    };
+    let saved_path = match input.output_path.as_deref().map(str::trim) {
+        Some(path) if !path.is_empty() => {
+            std::fs::write(path, &content).map_err(|error| format!("写入成绩单失败: {error}"))?;
+            Some(path.to_string())
+        }
+        _ => None,
+    };
+
+    Ok(envelope(
+        json!({
+            "format": format,
+            "fileName": format!("transcript-{}.{extension}", transcript.scale_id),
+            "mimeType": mime_type,
+            "content": content,
+            "savedPath": saved_path,
+            "untranslated": transcript.untranslated,
+            "transcript": transcript,
+        }),
+        "network",
+    ))
+}
+
+#[tauri::command]
+fn get_transcript_translations(app: AppHandle) -> Result<Value, String> {
+    Ok(envelope(
+        json!(transcript_export::load_translations(&app)),
+        "network",
+    ))
+}
+
+#[tauri::command]
+fn save_transcript_translations(
+    app: AppHandle,
+    input: TranscriptTranslationsInput,
+) -> Result<Value, String> {
+    let entries = transcript_export::save_translations(&app, input)?;
+    Ok(envelope(json!(entries), "network"))
 }
 
 fn apply_course_mapping(grade: &mut Value, mappings: &HashMap<String, String>) {
@@ -631,16 +1959,54 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             login_zju_command,
             fetch_scholar_data,
+            import_transcript_file,
             fetch_timetable,
+            export_timetable_share,
+            find_common_free_time,
             fetch_todos,
+            fetch_todo_detail,
+            fetch_todo_history,
+            fetch_video_activities,
+            submit_homework,
+            fetch_attendance,
+            sync_notification_feed,
+            mark_notifications_read,
             fetch_materials,
+            fetch_course_archive,
             sync_materials_index,
             download_material_asset,
+            fetch_lecture_streams,
+            download_lecture_replay,
             cache_remote_material,
             read_material_text,
             open_material_asset,
             remove_material_cache,
             calculate_gpa_preview,
+            plan_target_gpa,
+            analyze_gpa,
+            calculate_gpa_by_category,
+            list_gpa_scales,
+            import_gpa_scale,
+            delete_gpa_scale,
+            export_english_transcript,
+            get_transcript_translations,
+            save_transcript_translations,
+            evaluate_scholarship,
+            list_evaluation_rules,
+            import_evaluation_rule,
+            delete_evaluation_rule,
+            list_gpa_scenarios,
+            create_gpa_scenario,
+            update_gpa_scenario,
+            delete_gpa_scenario,
+            duplicate_gpa_scenario,
+            load_gpa_scenario,
+            compare_gpa_scenarios,
+            audit_transcript,
+            reconcile_gpa,
+            import_legacy_data,
+            list_local_tasks,
+            delete_local_task,
             run_ai_analysis,
             send_dingtalk_test,
         ])
@@ -760,10 +2126,18 @@ mod local_smoke {
         let processed_grades = transcript_raw.iter().map(enrich_grade).collect::<Vec<_>>();
         let major_course_ids = collect_major_course_ids(&major_grades);
         let major_set = major_course_ids.iter().cloned().collect::<HashSet<_>>();
-        let overall_first =
-            compute_gpa_by_policy(&processed_grades, &major_set, RetakePolicy::First);
-        let overall_highest =
-            compute_gpa_by_policy(&processed_grades, &major_set, RetakePolicy::Highest);
+        let overall_first = compute_gpa_by_policy(
+            &processed_grades,
+            &major_set,
+            &RetakePolicy::First.into(),
+            &[],
+        );
+        let overall_highest = compute_gpa_by_policy(
+            &processed_grades,
+            &major_set,
+            &RetakePolicy::Highest.into(),
+            &[],
+        );
 
         let mut semesters = processed_grades
             .iter()
@@ -780,12 +2154,14 @@ mod local_smoke {
                     .as_ref()
                     .map(|value| value.display_name.clone())
                     .unwrap_or_else(|| name.clone());
-                let first = compute_gpa_by_policy(&grades, &major_set, RetakePolicy::First);
-                let highest = compute_gpa_by_policy(&grades, &major_set, RetakePolicy::Highest);
+                let first =
+                    compute_gpa_by_policy(&grades, &major_set, &RetakePolicy::First.into(), &[]);
+                let highest =
+                    compute_gpa_by_policy(&grades, &major_set, &RetakePolicy::Highest.into(), &[]);
                 (name, display_name, first, highest)
             })
             .collect::<Vec<_>>();
-        semesters.sort_by(|left, right| term_rank(&right.0).cmp(&term_rank(&left.0)));
+        semesters.sort_by_key(|entry| std::cmp::Reverse(term_rank(&entry.0)));
 
         let today = Local::now().date_naive();
         let current_term = resolve_current_term(today);
@@ -821,9 +2197,8 @@ mod local_smoke {
                 flow_items.push((
                     end_time,
                     format!(
-                        "[DDL] {} | {}",
+                        "[DDL] {} | {title} @ {course_name}",
                         end_time.format("%Y-%m-%d %H:%M"),
-                        format!("{title} @ {course_name}")
                     ),
                 ));
             }
@@ -893,7 +2268,7 @@ mod local_smoke {
                 ));
             }
         }
-        flow_items.sort_by(|left, right| left.0.cmp(&right.0));
+        flow_items.sort_by_key(|entry| entry.0);
 
         println!("=== Celechron Local Smoke ===");
         println!(
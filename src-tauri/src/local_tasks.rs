@@ -0,0 +1,69 @@
+use crate::json_store;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+const LOCAL_TASK_FILE: &str = "local_tasks.json";
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum LocalTaskKind {
+    /// Only an end time matters.
+    #[default]
+    Deadline,
+    /// Occupies a time range, like a self-scheduled study block.
+    Fixed,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LocalTask {
+    pub id: String,
+    pub kind: LocalTaskKind,
+    pub title: String,
+    pub description: String,
+    pub location: String,
+    /// RFC 3339; absent for deadlines.
+    pub start_at: Option<String>,
+    /// RFC 3339.
+    pub end_at: Option<String>,
+    pub done: bool,
+    /// Repeat unit such as `days`; absent when the task does not repeat.
+    pub repeat: Option<String>,
+    pub repeat_until: Option<String>,
+    /// Where the task came from, e.g. `flutter` for migrated ones.
+    pub source: String,
+}
+
+pub fn load_tasks(app: &AppHandle) -> Vec<LocalTask> {
+    json_store::load(app, LOCAL_TASK_FILE)
+}
+
+fn store_tasks(app: &AppHandle, tasks: &[LocalTask]) -> Result<(), String> {
+    json_store::store(app, LOCAL_TASK_FILE, tasks, "本地任务")
+}
+
+/// Adds tasks whose id is not stored yet; returns how many were added.
+pub fn merge_tasks(app: &AppHandle, incoming: Vec<LocalTask>) -> Result<usize, String> {
+    let mut tasks = load_tasks(app);
+    let before = tasks.len();
+    for task in incoming {
+        if tasks.iter().all(|existing| existing.id != task.id) {
+            tasks.push(task);
+        }
+    }
+    let added = tasks.len() - before;
+    if added > 0 {
+        store_tasks(app, &tasks)?;
+    }
+    Ok(added)
+}
+
+pub fn delete_task(app: &AppHandle, id: &str) -> Result<(), String> {
+    let mut tasks = load_tasks(app);
+    let before = tasks.len();
+    tasks.retain(|task| task.id != id);
+    if tasks.len() == before {
+        return Err(format!("未找到本地任务: {id}"));
+    }
+    store_tasks(app, &tasks)
+}
@@ -1,3 +1,4 @@
+use crate::archive::{self, ArchiveTermKey};
 use crate::classroom::{ClassroomQuerySummary, ClassroomSession, ClassroomSubject};
 use crate::courses;
 use crate::term;
@@ -87,6 +88,9 @@ struct RemoteMaterialsIndex {
     last_synced_at: Option<u64>,
     warnings: Vec<String>,
     week_label: Option<String>,
+    /// Courses synced from the archive, kept when the current term is synced.
+    #[serde(default)]
+    archived_course_ids: Vec<i64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -241,6 +245,60 @@ async fn resolve_material_sync_window(app: &AppHandle, courses: &[Value]) -> Mat
     }
 }
 
+/// Window of an archived term, spanning its courses' dates. Nothing falls in
+/// the current week.
+fn archive_sync_window(courses: &[Value]) -> MaterialSyncWindow {
+    let term_start = courses
+        .iter()
+        .filter_map(|course| {
+            course
+                .get("start_date")
+                .and_then(Value::as_str)
+                .and_then(parse_date_only)
+        })
+        .min()
+        .unwrap_or_else(|| Local::now().date_naive() - Duration::days(140));
+    let term_end = derive_term_end_from_courses(courses, term_start);
+
+    MaterialSyncWindow {
+        term_start_ts: midnight_ts(term_start - Duration::days(14)),
+        term_end_ts: midnight_ts(term_end + Duration::days(21)),
+        week_start_ts: 0,
+        week_end_ts: 0,
+    }
+}
+
+/// Items of courses outside this sync that survive it, with the updated
+/// archived course ids. An archive sync keeps everything else; a current-term
+/// sync keeps only archived courses.
+fn retained_remote_items(
+    previous: RemoteMaterialsIndex,
+    course_ids: &[i64],
+    archive: bool,
+) -> (Vec<RemoteMaterialAsset>, Vec<i64>) {
+    let mut archived_course_ids = previous
+        .archived_course_ids
+        .into_iter()
+        .filter(|course_id| archive || !course_ids.contains(course_id))
+        .collect::<Vec<_>>();
+    let items = previous
+        .items
+        .into_iter()
+        .filter(|item| {
+            !course_ids.contains(&item.course_id)
+                && (archive || archived_course_ids.contains(&item.course_id))
+        })
+        .collect();
+    if archive {
+        for course_id in course_ids {
+            if !archived_course_ids.contains(course_id) {
+                archived_course_ids.push(*course_id);
+            }
+        }
+    }
+    (items, archived_course_ids)
+}
+
 fn materials_root(app: &AppHandle) -> Result<PathBuf, String> {
     let dir = app
         .path()
@@ -459,7 +517,7 @@ fn infer_filename(url: &str, response: &reqwest::Response, preferred_name: Optio
         .and_then(|parsed| {
             parsed
                 .path_segments()
-                .and_then(|segments| segments.last().map(|value| value.to_string()))
+                .and_then(|mut segments| segments.next_back().map(|value| value.to_string()))
         })
         .map(|value| sanitize_segment(&value))
         .filter(|value| !value.is_empty())
@@ -713,9 +771,7 @@ fn normalize_classroom_subject(
 ) -> Option<RemoteMaterialAsset> {
     let dated_updated_at = classroom_subject_timestamp(&subject);
     if subject.week_bucket != "current" {
-        let Some(ts) = dated_updated_at else {
-            return None;
-        };
+        let ts = dated_updated_at?;
         if ts < window.term_start_ts || ts > window.term_end_ts {
             return None;
         }
@@ -762,6 +818,7 @@ fn normalize_classroom_subject(
     })
 }
 
+#[allow(clippy::too_many_arguments)]
 fn build_material_meta(
     course_name: &str,
     title: &str,
@@ -979,11 +1036,32 @@ pub fn fetch_materials(app: &AppHandle) -> Result<Value, String> {
     Ok(build_materials_payload(items, index, &MaterialSourceContext::default()))
 }
 
-pub async fn sync_materials_index(app: &AppHandle, state: &AppState) -> Result<Value, String> {
+/// Syncs the remote materials index for the current term, or for one
+/// archived term when `archive_term` is given.
+pub async fn sync_materials_index(
+    app: &AppHandle,
+    state: &AppState,
+    archive_term: Option<ArchiveTermKey>,
+) -> Result<Value, String> {
     let root = materials_root(app)?;
     let local_items = read_materials(&root)?;
-    let courses = courses::get_learning_courses(state).await?;
-    let window = resolve_material_sync_window(app, &courses).await;
+    let (courses, window) = match archive_term {
+        Some(key) => {
+            let archived = courses::get_archived_courses(state).await?;
+            let courses = archive::term_courses(archived, &key);
+            if courses.is_empty() {
+                return Err("该学期没有已结课的课程".to_string());
+            }
+            let window = archive_sync_window(&courses);
+            (courses, window)
+        }
+        None => {
+            let courses = courses::get_learning_courses(state).await?;
+            let window = resolve_material_sync_window(app, &courses).await;
+            (courses, window)
+        }
+    };
+    let archive = archive_term.is_some();
     let mut warnings = Vec::<String>::new();
     let mut remote_items = Vec::<RemoteMaterialAsset>::new();
     let mut seen_learning = HashSet::<(i64, i64)>::new();
@@ -1058,6 +1136,12 @@ pub async fn sync_materials_index(app: &AppHandle, state: &AppState) -> Result<V
         }
     }
 
+    let previous = read_remote_index(&root);
+    let previous_week_label = previous.week_label.clone();
+    let (retained_items, archived_course_ids) =
+        retained_remote_items(previous, &course_ids, archive);
+    remote_items.extend(retained_items);
+
     match ClassroomSession::login(state).await {
         Ok(session) => match session.fetch_material_subjects(&course_ids).await {
             Ok(result) => {
@@ -1075,7 +1159,12 @@ pub async fn sync_materials_index(app: &AppHandle, state: &AppState) -> Result<V
                     items: remote_items,
                     last_synced_at: Some(updated_at),
                     warnings: warnings.clone(),
-                    week_label: Some(result.week_label),
+                    week_label: if archive {
+                        previous_week_label
+                    } else {
+                        Some(result.week_label)
+                    },
+                    archived_course_ids,
                 };
                 index.items = attach_download_status(index.items, &local_items);
                 write_remote_index(&root, &index)?;
@@ -1094,7 +1183,8 @@ pub async fn sync_materials_index(app: &AppHandle, state: &AppState) -> Result<V
         items: attach_download_status(remote_items, &local_items),
         last_synced_at: Some(synced_at),
         warnings: warnings.clone(),
-        week_label: None,
+        week_label: if archive { previous_week_label } else { None },
+        archived_course_ids,
     };
     write_remote_index(&root, &index)?;
     Ok(build_materials_payload(local_items, index, &MaterialSourceContext::default()))
@@ -1290,6 +1380,68 @@ pub fn read_material_text(app: &AppHandle, input: MaterialContentInput) -> Resul
     }))
 }
 
+/// Absolute path of a downloaded material, for handing the file to other
+/// features such as homework attachments.
+pub fn material_file_path(app: &AppHandle, relative_path: &str) -> Result<PathBuf, String> {
+    let root = materials_root(app)?;
+    let asset_path = resolve_asset_path(&root, relative_path)?;
+    if !asset_path.exists() {
+        return Err("资料文件不存在".to_string());
+    }
+    Ok(asset_path)
+}
+
+/// Where a file streamed into the library should be written, under its
+/// course folder. Pair with [`register_material_file`] once complete.
+pub fn material_target_path(
+    app: &AppHandle,
+    course_name: &str,
+    file_name: &str,
+) -> Result<PathBuf, String> {
+    let course_dir = materials_root(app)?.join(sanitize_segment(course_name));
+    fs::create_dir_all(&course_dir).map_err(|error| format!("无法创建课程资料目录: {error}"))?;
+    Ok(course_dir.join(sanitize_segment(file_name)))
+}
+
+/// Registers a file already written into the library as a material.
+pub fn register_material_file(
+    app: &AppHandle,
+    asset_path: &Path,
+    course_name: &str,
+    title: &str,
+    source_url: String,
+    mime_type: Option<String>,
+) -> Result<MaterialAsset, String> {
+    let root = materials_root(app)?;
+    let size_bytes = fs::metadata(asset_path)
+        .map_err(|error| format!("读取资料失败: {error}"))?
+        .len();
+    let relative_path = asset_path
+        .strip_prefix(&root)
+        .map_err(|error| format!("生成资料路径失败: {error}"))?;
+    let file_name = asset_path
+        .file_name()
+        .and_then(|value| value.to_str())
+        .unwrap_or("asset");
+    let meta = build_material_meta(
+        course_name,
+        title,
+        source_url,
+        mime_type,
+        size_bytes,
+        relative_path,
+        file_name,
+        now_ts(),
+    );
+    fs::write(
+        material_meta_path(asset_path),
+        serde_json::to_vec_pretty(&meta).map_err(|error| format!("写入资料元数据失败: {error}"))?,
+    )
+    .map_err(|error| format!("写入资料元数据失败: {error}"))?;
+
+    hydrate_asset(&root, asset_path, &meta).ok_or_else(|| "资料元数据构建失败".to_string())
+}
+
 pub fn open_material_asset(app: &AppHandle, input: MaterialPathInput) -> Result<Value, String> {
     let root = materials_root(app)?;
     let asset_path = resolve_asset_path(&root, &input.relative_path)?;
@@ -1382,6 +1534,81 @@ mod tests {
             Some("高等数学/lecture1.pdf")
         );
     }
+
+    fn remote_item(course_id: i64, sub_id: i64) -> RemoteMaterialAsset {
+        RemoteMaterialAsset {
+            id: format!("classroom:{course_id}:{sub_id}"),
+            course_id,
+            course_name: format!("课程{course_id}"),
+            title: format!("第{sub_id}讲"),
+            file_name: format!("{sub_id}.pdf"),
+            source_type: "classroom".to_string(),
+            source_url: String::new(),
+            fallback_source_url: String::new(),
+            mime_type: None,
+            size_bytes: 0,
+            updated_at: 1,
+            downloaded: false,
+            local_relative_path: None,
+            week_bucket: "other".to_string(),
+            preview_image_urls: Vec::new(),
+            remote_ref: Value::Null,
+        }
+    }
+
+    fn item_ids(items: &[RemoteMaterialAsset]) -> Vec<&str> {
+        items.iter().map(|item| item.id.as_str()).collect()
+    }
+
+    #[test]
+    fn archive_sync_then_current_sync_keeps_both_terms() {
+        let previous = RemoteMaterialsIndex {
+            items: vec![remote_item(1, 1)],
+            ..Default::default()
+        };
+        let (mut items, archived_course_ids) = retained_remote_items(previous, &[7], true);
+        assert_eq!(item_ids(&items), ["classroom:1:1"]);
+        assert_eq!(archived_course_ids, [7]);
+
+        items.push(remote_item(7, 1));
+        let after_archive = RemoteMaterialsIndex {
+            items,
+            archived_course_ids,
+            ..Default::default()
+        };
+        let (items, archived_course_ids) = retained_remote_items(after_archive, &[1], false);
+        assert_eq!(item_ids(&items), ["classroom:7:1"]);
+        assert_eq!(archived_course_ids, [7]);
+    }
+
+    #[test]
+    fn current_sync_then_archive_sync_keeps_both_terms() {
+        let previous = RemoteMaterialsIndex {
+            items: vec![remote_item(1, 1), remote_item(3, 1), remote_item(7, 1)],
+            archived_course_ids: vec![7],
+            ..Default::default()
+        };
+        // Course 3 was dropped: neither current nor archived, so it goes.
+        let (mut items, archived_course_ids) = retained_remote_items(previous, &[1], false);
+        assert_eq!(item_ids(&items), ["classroom:7:1"]);
+        assert_eq!(archived_course_ids, [7]);
+
+        items.push(remote_item(1, 2));
+        let after_current = RemoteMaterialsIndex {
+            items,
+            archived_course_ids,
+            ..Default::default()
+        };
+        let (items, archived_course_ids) =
+            retained_remote_items(after_current.clone(), &[7, 8], true);
+        assert_eq!(item_ids(&items), ["classroom:1:2"]);
+        assert_eq!(archived_course_ids, [7, 8]);
+
+        // A course that is current again stops being treated as archived.
+        let (items, archived_course_ids) = retained_remote_items(after_current, &[1, 7], false);
+        assert!(items.is_empty());
+        assert!(archived_course_ids.is_empty());
+    }
 #[test]
 fn classroom_subject_timestamp_extracts_date_from_title() {
     let subject = ClassroomSubject {
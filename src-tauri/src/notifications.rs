@@ -0,0 +1,365 @@
+use crate::courses;
+use crate::json_store;
+use crate::json_value::{first_i64, first_str};
+use crate::zjuam::AppState;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+use std::future::Future;
+use std::sync::Arc;
+use tauri::AppHandle;
+
+const FEED_FILE: &str = "notification_feed.json";
+/// Pages fetched per source when the cursor is not reached, e.g. on first sync.
+const MAX_PAGES: i64 = 3;
+const MAX_ITEMS: usize = 500;
+const INBOX_CURSOR: &str = "inbox";
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum FeedSource {
+    Announcement,
+    Notification,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedItem {
+    pub id: String,
+    pub source: FeedSource,
+    pub course_id: Option<i64>,
+    pub course_name: String,
+    pub title: String,
+    pub content_html: String,
+    pub created_at: String,
+    pub read: bool,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FeedState {
+    pub items: Vec<FeedItem>,
+    /// Items read in the app; 学在浙大's own read flag is kept on the item.
+    pub read_ids: BTreeSet<String>,
+    /// Newest `created_at` seen per source, to stop paging on the next sync.
+    pub cursors: HashMap<String, String>,
+    pub last_synced_at: Option<i64>,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedView {
+    pub items: Vec<FeedItem>,
+    pub unread: usize,
+    /// Ids added by this sync.
+    pub new_ids: Vec<String>,
+    pub last_synced_at: Option<i64>,
+    pub warnings: Vec<String>,
+}
+
+pub fn load_feed(app: &AppHandle) -> FeedState {
+    json_store::load(app, FEED_FILE)
+}
+
+fn store_feed(app: &AppHandle, feed: &FeedState) -> Result<(), String> {
+    json_store::store(app, FEED_FILE, feed, "通知缓存")
+}
+
+pub fn announcement_item(raw: &Value, course_id: i64, course_name: &str) -> Option<FeedItem> {
+    Some(FeedItem {
+        id: format!("announcement-{}", first_i64(raw, &["id"])?),
+        source: FeedSource::Announcement,
+        course_id: Some(course_id),
+        course_name: course_name.to_string(),
+        title: first_str(raw, &["title"]).unwrap_or_default(),
+        content_html: first_str(raw, &["content", "description"]).unwrap_or_default(),
+        created_at: first_str(raw, &["published_at", "created_at", "updated_at"])
+            .unwrap_or_default(),
+        read: raw.get("is_read").and_then(Value::as_bool).unwrap_or(false),
+    })
+}
+
+/// Inbox entries that announce an announcement take its id, so the two
+/// collapse into one item.
+pub fn notification_item(raw: &Value) -> Option<FeedItem> {
+    let payload = raw.get("payload").unwrap_or(&Value::Null);
+    let id = match first_i64(raw, &["announcement_id"])
+        .or_else(|| first_i64(payload, &["announcement_id"]))
+    {
+        Some(announcement) => format!("announcement-{announcement}"),
+        None => format!("notification-{}", first_i64(raw, &["id"])?),
+    };
+    Some(FeedItem {
+        id,
+        source: FeedSource::Notification,
+        course_id: first_i64(raw, &["course_id"]).or_else(|| first_i64(payload, &["course_id"])),
+        course_name: first_str(raw, &["course_name"])
+            .or_else(|| first_str(payload, &["course_name"]))
+            .unwrap_or_default(),
+        title: first_str(raw, &["title", "subject"])
+            .or_else(|| first_str(payload, &["title", "activity_title"]))
+            .unwrap_or_default(),
+        content_html: first_str(raw, &["content", "body"])
+            .or_else(|| first_str(payload, &["content"]))
+            .unwrap_or_default(),
+        created_at: first_str(raw, &["created_at", "timestamp"]).unwrap_or_default(),
+        read: raw
+            .get("is_read")
+            .or_else(|| raw.get("read"))
+            .and_then(Value::as_bool)
+            .unwrap_or(false),
+    })
+}
+
+fn same_post(left: &FeedItem, right: &FeedItem) -> bool {
+    left.id == right.id
+        || (left.course_id.is_some()
+            && left.course_id == right.course_id
+            && !left.title.is_empty()
+            && left.title == right.title
+            && left.created_at.get(..10) == right.created_at.get(..10))
+}
+
+/// Merges fetched items into the feed and returns the ids that are new.
+/// Duplicates fill in each other's missing fields; announcements win.
+pub fn merge_feed(feed: &mut FeedState, incoming: Vec<FeedItem>) -> Vec<String> {
+    let mut new_ids = Vec::new();
+    for item in incoming {
+        match feed
+            .items
+            .iter_mut()
+            .find(|existing| same_post(existing, &item))
+        {
+            Some(existing) => {
+                existing.read |= item.read;
+                if item.source == FeedSource::Announcement {
+                    existing.source = FeedSource::Announcement;
+                    existing.id = item.id;
+                }
+                for (field, value) in [
+                    (&mut existing.title, item.title),
+                    (&mut existing.content_html, item.content_html),
+                    (&mut existing.course_name, item.course_name),
+                    (&mut existing.created_at, item.created_at),
+                ] {
+                    if field.is_empty() {
+                        *field = value;
+                    }
+                }
+                existing.course_id = existing.course_id.or(item.course_id);
+            }
+            None => {
+                new_ids.push(item.id.clone());
+                feed.items.push(item);
+            }
+        }
+    }
+    for item in &mut feed.items {
+        item.read |= feed.read_ids.contains(&item.id);
+    }
+    feed.items
+        .sort_by(|left, right| right.created_at.cmp(&left.created_at));
+    feed.items.truncate(MAX_ITEMS);
+    new_ids
+}
+
+/// Whether a page reached items already seen, so older pages can be skipped.
+fn reached_cursor(items: &[FeedItem], cursor: Option<&String>) -> bool {
+    cursor.is_some_and(|cursor| items.iter().any(|item| &item.created_at <= cursor))
+}
+
+fn advance_cursor(feed: &mut FeedState, key: &str, items: &[FeedItem]) {
+    if let Some(newest) = items.iter().map(|item| &item.created_at).max() {
+        let cursor = feed.cursors.entry(key.to_string()).or_default();
+        if newest > cursor {
+            *cursor = newest.clone();
+        }
+    }
+}
+
+pub fn view(feed: &FeedState, new_ids: Vec<String>, warnings: Vec<String>) -> FeedView {
+    FeedView {
+        unread: feed.items.iter().filter(|item| !item.read).count(),
+        items: feed.items.clone(),
+        new_ids,
+        last_synced_at: feed.last_synced_at,
+        warnings,
+    }
+}
+
+/// Pages through a source until the cursor or `MAX_PAGES`. A failed page
+/// ends paging; its error comes back with the items fetched before it.
+async fn fetch_pages<F, Fut>(
+    cursor: Option<&String>,
+    fetch_page: F,
+) -> (Vec<FeedItem>, Option<String>)
+where
+    F: Fn(i64) -> Fut,
+    Fut: Future<Output = Result<(Vec<FeedItem>, i64), String>>,
+{
+    let mut fetched = Vec::new();
+    for page in 1..=MAX_PAGES {
+        match fetch_page(page).await {
+            Ok((items, pages)) => {
+                let done = page >= pages || reached_cursor(&items, cursor);
+                fetched.extend(items);
+                if done {
+                    break;
+                }
+            }
+            Err(error) => return (fetched, Some(error)),
+        }
+    }
+    (fetched, None)
+}
+
+/// Pulls new announcements of every current course plus the inbox. A source
+/// that fails is reported in `warnings` instead of failing the sync, and its
+/// cursor stays put so the next sync pages back to the missed items.
+pub async fn sync_feed(app: &AppHandle, state: &Arc<AppState>) -> Result<FeedView, String> {
+    let mut feed = load_feed(app);
+    let course_list = courses::get_learning_courses(state)
+        .await?
+        .iter()
+        .filter_map(|course| {
+            let course_id = course.get("id").and_then(Value::as_i64)?;
+            let course_name = first_str(course, &["display_name", "name"]).unwrap_or_default();
+            Some((course_id, course_name))
+        })
+        .collect::<Vec<_>>();
+    let mut incoming = Vec::new();
+
+    let cursors = feed.cursors.clone();
+    let (announcements, mut warnings) =
+        courses::for_each_course(state, course_list, |state, course_id, course_name| {
+            let key = format!("course-{course_id}");
+            let cursor = cursors.get(&key).cloned();
+            async move {
+                let (items, error) = fetch_pages(cursor.as_ref(), |page| {
+                    let (state, course_name) = (&state, &course_name);
+                    async move {
+                        let (raw, pages) =
+                            courses::get_course_announcements(state, course_id, page).await?;
+                        let items = raw
+                            .iter()
+                            .filter_map(|raw| announcement_item(raw, course_id, course_name))
+                            .collect();
+                        Ok((items, pages))
+                    }
+                })
+                .await;
+                match error {
+                    Some(error) if items.is_empty() => Err(error),
+                    error => Ok((key, course_name, items, error)),
+                }
+            }
+        })
+        .await;
+    for (key, course_name, items, error) in announcements {
+        match error {
+            Some(error) => warnings.push(format!("{course_name}: {error}")),
+            None => advance_cursor(&mut feed, &key, &items),
+        }
+        incoming.extend(items);
+    }
+
+    let (inbox, error) = fetch_pages(feed.cursors.get(INBOX_CURSOR), |page| async move {
+        let (raw, pages) = courses::get_notifications(state, page).await?;
+        Ok((raw.iter().filter_map(notification_item).collect(), pages))
+    })
+    .await;
+    match error {
+        Some(error) => warnings.push(format!("消息中心: {error}")),
+        None => advance_cursor(&mut feed, INBOX_CURSOR, &inbox),
+    }
+    incoming.extend(inbox);
+
+    let new_ids = merge_feed(&mut feed, incoming);
+    feed.last_synced_at = Some(Local::now().timestamp());
+    store_feed(app, &feed)?;
+    Ok(view(&feed, new_ids, warnings))
+}
+
+/// Marks items read; `None` marks the whole feed.
+pub fn mark_read(app: &AppHandle, ids: Option<Vec<String>>) -> Result<FeedView, String> {
+    let mut feed = load_feed(app);
+    let ids = ids.unwrap_or_else(|| feed.items.iter().map(|item| item.id.clone()).collect());
+    feed.read_ids.extend(ids);
+    merge_feed(&mut feed, Vec::new());
+    store_feed(app, &feed)?;
+    Ok(view(&feed, Vec::new(), Vec::new()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        announcement_item, fetch_pages, merge_feed, notification_item, FeedSource, FeedState,
+    };
+    use serde_json::json;
+
+    #[test]
+    fn feed_merges_announcements_and_inbox_without_duplicates() {
+        let mut feed = FeedState::default();
+        let inbox = [
+            json!({ "id": 1, "payload": { "announcement_id": 70, "course_id": 5 }, "title": "教室调整", "created_at": "2024-05-02T08:00:00Z", "is_read": true }),
+            json!({ "id": 2, "course_id": 5, "title": "作业延期", "created_at": "2024-05-03T08:00:00Z" }),
+        ];
+        let new_ids = merge_feed(
+            &mut feed,
+            inbox.iter().filter_map(notification_item).collect(),
+        );
+        assert_eq!(new_ids.len(), 2);
+
+        let announcements = [
+            json!({ "id": 70, "title": "教室调整", "content": "<p>改到东三</p>", "created_at": "2024-05-02T08:00:00Z" }),
+            json!({ "id": 71, "title": "作业延期", "content": "<p>延后一周</p>", "created_at": "2024-05-03T09:30:00Z" }),
+        ];
+        feed.read_ids.insert("announcement-71".to_string());
+        let new_ids = merge_feed(
+            &mut feed,
+            announcements
+                .iter()
+                .filter_map(|raw| announcement_item(raw, 5, "操作系统"))
+                .collect(),
+        );
+
+        assert!(new_ids.is_empty());
+        assert_eq!(feed.items.len(), 2);
+        assert_eq!(feed.items[0].id, "announcement-71");
+        assert_eq!(feed.items[0].source, FeedSource::Announcement);
+        assert!(feed.items[0].read);
+        assert_eq!(feed.items[1].content_html, "<p>改到东三</p>");
+        assert_eq!(feed.items[1].course_name, "操作系统");
+        assert!(feed.items[1].read);
+    }
+
+    #[tokio::test]
+    async fn a_failed_page_keeps_earlier_items_and_reports_the_error() {
+        let page_items = |page: i64| {
+            let raw = json!({ "id": page, "title": format!("公告{page}"), "created_at": format!("2024-05-0{page}T08:00:00Z") });
+            announcement_item(&raw, 5, "操作系统")
+                .into_iter()
+                .collect::<Vec<_>>()
+        };
+        let (items, error) = fetch_pages(None, |page| async move {
+            if page == 2 {
+                Err("网络错误".to_string())
+            } else {
+                Ok((page_items(page), 3))
+            }
+        })
+        .await;
+        assert_eq!(items.len(), 1);
+        assert_eq!(error.as_deref(), Some("网络错误"));
+
+        let cursor = "2024-05-01T08:00:00Z".to_string();
+        let (items, error) = fetch_pages(
+            Some(&cursor),
+            |page| async move { Ok((page_items(page), 3)) },
+        )
+        .await;
+        assert_eq!(items.len(), 1);
+        assert!(error.is_none());
+    }
+}
@@ -0,0 +1,137 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum PresetOrigin {
+    #[default]
+    Preset,
+    User,
+}
+
+/// A JSON definition that ships bundled presets and accepts user imports,
+/// stored one file per id under `USER_DIR` in the app data directory.
+pub trait Preset: Serialize + Sized {
+    /// Shown in messages, e.g. `绩点标准`.
+    const LABEL: &'static str;
+    const USER_DIR: &'static str;
+    const SOURCES: &'static [&'static str];
+
+    fn parse(content: &str, origin: PresetOrigin) -> Result<Self, String>;
+    fn id(&self) -> &str;
+}
+
+fn is_valid_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.'))
+}
+
+/// Ids double as file names, so only a safe character set is accepted.
+pub fn check_id<T: Preset>(id: &str) -> Result<(), String> {
+    if is_valid_id(id) {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} id 只能包含字母、数字、-、_ 和 .: {id}",
+            T::LABEL
+        ))
+    }
+}
+
+pub fn presets<T: Preset>() -> Vec<T> {
+    T::SOURCES
+        .iter()
+        .filter_map(|source| T::parse(source, PresetOrigin::Preset).ok())
+        .collect()
+}
+
+fn user_dir<T: Preset>(app: &AppHandle) -> Option<PathBuf> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(T::USER_DIR))
+        .ok()
+}
+
+/// Presets followed by every readable user file. Files that fail to parse are
+/// reported instead of aborting the whole list.
+pub fn load<T: Preset>(app: &AppHandle) -> (Vec<T>, Vec<String>) {
+    let mut items = presets::<T>();
+    let mut errors = Vec::new();
+
+    let Some(dir) = user_dir::<T>(app) else {
+        return (items, errors);
+    };
+    let Ok(entries) = fs::read_dir(&dir) else {
+        return (items, errors);
+    };
+
+    let mut paths = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("json"))
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    for path in paths {
+        let name = path
+            .file_name()
+            .and_then(|value| value.to_str())
+            .unwrap_or_default()
+            .to_string();
+        match fs::read_to_string(&path)
+            .map_err(|error| error.to_string())
+            .and_then(|content| T::parse(&content, PresetOrigin::User))
+        {
+            Ok(item) if items.iter().any(|existing| existing.id() == item.id()) => {
+                errors.push(format!("{name}: {} id 重复: {}", T::LABEL, item.id()));
+            }
+            Ok(item) => items.push(item),
+            Err(error) => errors.push(format!("{name}: {error}")),
+        }
+    }
+
+    (items, errors)
+}
+
+/// Validates and stores a user definition given inline or as a file path.
+pub fn import<T: Preset>(
+    app: &AppHandle,
+    content: Option<String>,
+    path: Option<String>,
+) -> Result<T, String> {
+    let label = T::LABEL;
+    let content = match (content, path) {
+        (Some(content), _) if !content.trim().is_empty() => content,
+        (_, Some(path)) if !path.trim().is_empty() => fs::read_to_string(path.trim())
+            .map_err(|error| format!("读取{label}文件失败: {error}"))?,
+        _ => return Err(format!("请提供{label}文件或内容")),
+    };
+    let item = T::parse(&content, PresetOrigin::User)?;
+    if presets::<T>().iter().any(|preset| preset.id() == item.id()) {
+        return Err(format!("不能覆盖内置{label}: {}", item.id()));
+    }
+
+    let dir = user_dir::<T>(app).ok_or_else(|| format!("无法定位{label}目录"))?;
+    fs::create_dir_all(&dir).map_err(|error| error.to_string())?;
+    let stored = serde_json::to_string_pretty(&item).map_err(|error| error.to_string())?;
+    fs::write(dir.join(format!("{}.json", item.id())), stored)
+        .map_err(|error| format!("保存{label}失败: {error}"))?;
+    Ok(item)
+}
+
+pub fn delete<T: Preset>(app: &AppHandle, id: &str) -> Result<(), String> {
+    let label = T::LABEL;
+    if presets::<T>().iter().any(|preset| preset.id() == id) {
+        return Err(format!("不能删除内置{label}: {id}"));
+    }
+    let dir = user_dir::<T>(app).ok_or_else(|| format!("无法定位{label}目录"))?;
+    let path = dir.join(format!("{id}.json"));
+    if !is_valid_id(id) || !path.exists() {
+        return Err(format!("未找到{label}: {id}"));
+    }
+    fs::remove_file(path).map_err(|error| format!("删除{label}失败: {error}"))
+}
@@ -0,0 +1,139 @@
+use crate::courses;
+use crate::json_value::{first_i64, first_str};
+use crate::zjuam::AppState;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+/// Todo type of quizzes merged into the todo list, distinct from the
+/// `homework`/`exam` types 学在浙大 uses itself.
+pub const QUIZ_TODO_TYPE: &str = "online_quiz";
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct QuizActivity {
+    pub activity_id: i64,
+    pub course_id: i64,
+    pub course_name: String,
+    pub title: String,
+    pub start_time: Option<String>,
+    pub end_time: Option<String>,
+    /// Time limit once started, in minutes.
+    pub duration_minutes: Option<i64>,
+    /// `None` means unlimited attempts.
+    pub attempt_limit: Option<i64>,
+    pub attempts_used: i64,
+    pub completed: bool,
+}
+
+pub fn parse_quiz(raw: &Value, course_id: i64, course_name: &str) -> Option<QuizActivity> {
+    let attempts_used =
+        first_i64(raw, &["submitted_times", "submission_count", "attempts"]).unwrap_or(0);
+    let attempt_limit = first_i64(raw, &["submit_times", "attempt_limit", "max_attempts"])
+        .filter(|limit| *limit > 0);
+    let completed = ["is_submitted", "submitted", "completed"]
+        .iter()
+        .find_map(|key| raw.get(*key).and_then(Value::as_bool))
+        .unwrap_or(attempts_used > 0 && attempt_limit.is_some_and(|limit| attempts_used >= limit));
+    Some(QuizActivity {
+        activity_id: first_i64(raw, &["id", "activity_id"])?,
+        course_id,
+        course_name: course_name.to_string(),
+        title: first_str(raw, &["title", "name"])?,
+        start_time: first_str(raw, &["start_time", "open_time"]),
+        end_time: first_str(raw, &["end_time", "close_time", "deadline"]),
+        duration_minutes: first_i64(raw, &["limit_time", "duration", "time_limit"])
+            .filter(|minutes| *minutes > 0),
+        attempt_limit,
+        attempts_used,
+        completed,
+    })
+}
+
+fn closed(quiz: &QuizActivity, now: DateTime<Utc>) -> bool {
+    quiz.end_time
+        .as_deref()
+        .and_then(|end| DateTime::parse_from_rfc3339(end).ok())
+        .is_some_and(|end| end < now)
+}
+
+/// Todo entries for quizzes that are still open and not yet completed,
+/// shaped like 学在浙大's own todo items.
+pub fn quiz_todos(quizzes: &[QuizActivity], now: DateTime<Utc>) -> Vec<Value> {
+    quizzes
+        .iter()
+        .filter(|quiz| !quiz.completed && !closed(quiz, now))
+        .map(|quiz| {
+            json!({
+                "id": quiz.activity_id,
+                "title": quiz.title,
+                "type": QUIZ_TODO_TYPE,
+                "course_id": quiz.course_id,
+                "course_name": quiz.course_name,
+                "start_time": quiz.start_time,
+                "end_time": quiz.end_time,
+                "url": format!(
+                    "https://courses.zju.edu.cn/course/{}/learning-activity#/exam/{}",
+                    quiz.course_id, quiz.activity_id
+                ),
+                "quiz": quiz,
+            })
+        })
+        .collect()
+}
+
+/// Lists quizzes of every given `(course id, course name)`, grouped by course
+/// id. Courses that fail are left out and reported in the returned warnings.
+pub async fn fetch_quizzes(
+    state: Arc<AppState>,
+    courses: Vec<(i64, String)>,
+) -> (Vec<(i64, Vec<QuizActivity>)>, Vec<String>) {
+    courses::for_each_course(
+        &state,
+        courses,
+        |state, course_id, course_name| async move {
+            let raw = courses::get_course_exams(&state, course_id).await?;
+            let quizzes = raw
+                .iter()
+                .filter_map(|raw| parse_quiz(raw, course_id, &course_name))
+                .collect();
+            Ok((course_id, quizzes))
+        },
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_quiz, quiz_todos, QUIZ_TODO_TYPE};
+    use chrono::{TimeZone, Utc};
+    use serde_json::json;
+
+    #[test]
+    fn quizzes_become_todos_until_completed_or_closed() {
+        let raw = [
+            json!({ "id": 1, "title": "第一章测验", "start_time": "2024-05-01T00:00:00Z", "end_time": "2024-05-08T00:00:00Z",
+                    "limit_time": 30, "submit_times": 2, "submitted_times": 1 }),
+            json!({ "id": 2, "title": "期中在线考试", "end_time": "2024-05-10T00:00:00Z", "submit_times": 1, "submitted_times": "1" }),
+            json!({ "id": 3, "title": "已截止测验", "end_time": "2024-04-01T00:00:00Z" }),
+            json!({ "title": "缺少编号" }),
+        ];
+        let quizzes = raw
+            .iter()
+            .filter_map(|raw| parse_quiz(raw, 5, "操作系统"))
+            .collect::<Vec<_>>();
+        assert_eq!(quizzes.len(), 3);
+        assert_eq!(quizzes[0].duration_minutes, Some(30));
+        assert_eq!(quizzes[0].attempt_limit, Some(2));
+        assert!(!quizzes[0].completed);
+        assert!(quizzes[1].completed);
+        assert_eq!(quizzes[2].attempt_limit, None);
+
+        let todos = quiz_todos(&quizzes, Utc.with_ymd_and_hms(2024, 5, 2, 0, 0, 0).unwrap());
+        assert_eq!(todos.len(), 1);
+        assert_eq!(todos[0]["type"], QUIZ_TODO_TYPE);
+        assert_eq!(todos[0]["course_name"], "操作系统");
+        assert_eq!(todos[0]["quiz"]["attemptsUsed"], 1);
+    }
+}
@@ -0,0 +1,413 @@
+use crate::audit::JD_TOLERANCE;
+use crate::gpa::{
+    enrich_grade, interpret_grade_text, select_counted_grades, to_five_point, GradeText,
+    RetakeOverride, RetakePolicy, RetakeSelection,
+};
+use crate::json_value::first_f64;
+use crate::zdbk::OfficialGpaSummary;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+/// zdbk prints GPAs to two decimals.
+const MATCH_TOLERANCE: f64 = 0.005;
+/// Impacts smaller than this cannot move a two-decimal GPA.
+const MIN_IMPACT: f64 = 0.0005;
+
+#[derive(Clone, Copy, Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ReferenceSource {
+    /// The summary printed by zdbk.
+    Official,
+    /// Credit-weighted `jd` over every counted zdbk row, used when zdbk shows
+    /// no summary.
+    CourseJd,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyReconciliation {
+    pub policy: String,
+    pub five_point: f64,
+    pub hundred_point: f64,
+    pub gpa_credits: f64,
+    pub earned_credits: f64,
+    /// Ours minus the reference; `None` when the reference lacks the figure.
+    pub five_point_delta: Option<f64>,
+    pub credits_delta: Option<f64>,
+    pub matches: bool,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum DiscrepancyKind {
+    /// zdbk's `jd` disagrees with the converted score; we use the `jd`.
+    JdMismatch,
+    /// A retaken course where the policy drops attempts zdbk may count.
+    RetakeChoice,
+    /// A row with a `jd` that our rules leave out of the GPA.
+    NotCounted,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Discrepancy {
+    pub kind: DiscrepancyKind,
+    pub xkkh: String,
+    pub course_name: String,
+    pub credit: f64,
+    pub detail: String,
+    /// How far the five-point GPA would move under each policy if the row
+    /// were counted the other way.
+    pub impact: BTreeMap<String, f64>,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GpaReconciliation {
+    pub official: Option<OfficialGpaSummary>,
+    pub reference: ReferenceSource,
+    pub reference_five_point: f64,
+    pub reference_credits: Option<f64>,
+    pub policies: Vec<PolicyReconciliation>,
+    /// The policy whose five-point GPA lands nearest the reference.
+    pub closest_policy: Option<String>,
+    pub discrepancies: Vec<Discrepancy>,
+}
+
+#[derive(Clone, Copy, Default)]
+struct Totals {
+    points: f64,
+    credits: f64,
+}
+
+impl Totals {
+    fn gpa(self) -> f64 {
+        if self.credits > 0.0 {
+            self.points / self.credits
+        } else {
+            0.0
+        }
+    }
+
+    fn shifted(self, points: f64, credits: f64) -> Self {
+        Totals {
+            points: self.points + points,
+            credits: self.credits + credits,
+        }
+    }
+}
+
+fn text<'a>(grade: &'a Value, key: &str) -> &'a str {
+    grade
+        .get(key)
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .trim()
+}
+
+fn counts_for_gpa(grade: &Value) -> bool {
+    grade
+        .get("gpaIncluded")
+        .and_then(Value::as_bool)
+        .unwrap_or(false)
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+struct PolicyRun {
+    name: String,
+    totals: Totals,
+    hundred: Totals,
+    earned_credits: f64,
+    /// Row index → (credit, five-point) as counted.
+    counted: HashMap<usize, (f64, f64)>,
+}
+
+fn run_policy(
+    grades: &[Value],
+    name: &str,
+    policy: RetakePolicy,
+    overrides: &HashMap<String, RetakeOverride>,
+) -> PolicyRun {
+    let selection = RetakeSelection {
+        policy,
+        overrides: overrides.clone(),
+    };
+    let mut run = PolicyRun {
+        name: name.to_string(),
+        totals: Totals::default(),
+        hundred: Totals::default(),
+        earned_credits: 0.0,
+        counted: HashMap::new(),
+    };
+    for grade in select_counted_grades(grades, &selection) {
+        if grade.credit <= 0.0 {
+            continue;
+        }
+        if grade.earns_credit {
+            run.earned_credits += grade.credit;
+        }
+        if grade.counts_for_gpa {
+            run.totals = run
+                .totals
+                .shifted(grade.credit * grade.five_point, grade.credit);
+            run.hundred = run
+                .hundred
+                .shifted(grade.credit * grade.hundred_point, grade.credit);
+            run.counted
+                .insert(grade.index, (grade.credit, grade.five_point));
+        }
+    }
+    run
+}
+
+/// Compares our per-policy GPA with zdbk's figures and lists the rows that
+/// explain any gap. `grades` may be raw or already enriched.
+pub fn reconcile(
+    grades: &[Value],
+    official: Option<OfficialGpaSummary>,
+    policies: &[(&str, RetakePolicy)],
+    overrides: &HashMap<String, RetakeOverride>,
+) -> GpaReconciliation {
+    let grades = grades.iter().map(enrich_grade).collect::<Vec<_>>();
+    let runs = policies
+        .iter()
+        .map(|(name, policy)| run_policy(&grades, name, *policy, overrides))
+        .collect::<Vec<_>>();
+
+    let course_jd = grades
+        .iter()
+        .filter(|grade| counts_for_gpa(grade))
+        .filter_map(|grade| Some((first_f64(grade, &["credit"])?, first_f64(grade, &["jd"])?)))
+        .filter(|(credit, _)| *credit > 0.0)
+        .fold(Totals::default(), |totals, (credit, jd)| {
+            totals.shifted(credit * jd, credit)
+        });
+    let (reference, reference_five_point) = match official.as_ref().and_then(|o| o.five_point) {
+        Some(five_point) => (ReferenceSource::Official, five_point),
+        None => (ReferenceSource::CourseJd, course_jd.gpa()),
+    };
+    let reference_credits = official.as_ref().and_then(|o| o.credits);
+    let has_reference = reference == ReferenceSource::Official || course_jd.credits > 0.0;
+
+    let policy_results = runs
+        .iter()
+        .map(|run| {
+            let five_point = run.totals.gpa();
+            PolicyReconciliation {
+                policy: run.name.clone(),
+                five_point,
+                hundred_point: run.hundred.gpa(),
+                gpa_credits: run.totals.credits,
+                earned_credits: run.earned_credits,
+                five_point_delta: has_reference.then_some(five_point - reference_five_point),
+                credits_delta: reference_credits.map(|credits| run.earned_credits - credits),
+                matches: has_reference
+                    && (round2(five_point) - reference_five_point).abs() < MATCH_TOLERANCE,
+            }
+        })
+        .collect::<Vec<_>>();
+    let closest_policy = policy_results
+        .iter()
+        .filter_map(|result| Some((result, result.five_point_delta?.abs())))
+        .min_by(|left, right| left.1.total_cmp(&right.1))
+        .map(|(result, _)| result.policy.clone());
+
+    let mut discrepancies = Vec::new();
+    let impacts = |shift: &dyn Fn(&PolicyRun) -> Option<Totals>| {
+        runs.iter()
+            .filter_map(|run| {
+                let impact = shift(run)?.gpa() - run.totals.gpa();
+                (impact.abs() >= MIN_IMPACT).then(|| (run.name.clone(), impact))
+            })
+            .collect::<BTreeMap<_, _>>()
+    };
+
+    for (index, grade) in grades.iter().enumerate() {
+        let credit = first_f64(grade, &["credit"]).unwrap_or(0.0);
+        let Some(jd) = first_f64(grade, &["jd"]) else {
+            continue;
+        };
+        if credit <= 0.0 {
+            continue;
+        }
+        let cj = text(grade, "cj");
+        let (kind, detail, impact) = if !counts_for_gpa(grade) {
+            if jd <= 0.0 {
+                continue;
+            }
+            (
+                DiscrepancyKind::NotCounted,
+                format!("教务网给出绩点 {jd}，但成绩「{cj}」按规则不计入绩点"),
+                impacts(&|run: &PolicyRun| Some(run.totals.shifted(credit * jd, credit))),
+            )
+        } else if let GradeText::Known(score) | GradeText::Numeric(score) = interpret_grade_text(cj)
+        {
+            let converted = to_five_point(score);
+            if (converted - jd).abs() <= JD_TOLERANCE {
+                continue;
+            }
+            (
+                DiscrepancyKind::JdMismatch,
+                format!("教务网绩点 {jd} 与成绩「{cj}」换算的 {converted:.1} 不一致，计算采用教务网绩点"),
+                impacts(&|run: &PolicyRun| {
+                    let (credit, counted) = run.counted.get(&index)?;
+                    Some(run.totals.shifted(credit * (converted - counted), 0.0))
+                }),
+            )
+        } else {
+            continue;
+        };
+        if !impact.is_empty() {
+            discrepancies.push(Discrepancy {
+                kind,
+                xkkh: text(grade, "xkkh").to_string(),
+                course_name: text(grade, "kcmc").to_string(),
+                credit,
+                detail,
+                impact,
+            });
+        }
+    }
+
+    let mut groups = BTreeMap::<&str, Vec<usize>>::new();
+    for (index, grade) in grades.iter().enumerate() {
+        let key = text(grade, "retakeKey");
+        if !key.is_empty() {
+            groups.entry(key).or_default().push(index);
+        }
+    }
+    for rows in groups.values().filter(|rows| rows.len() > 1) {
+        // zdbk's own average counts every graded attempt.
+        let all_attempts = rows
+            .iter()
+            .map(|index| &grades[*index])
+            .filter(|grade| counts_for_gpa(grade))
+            .filter_map(|grade| {
+                Some((
+                    first_f64(grade, &["credit"])?,
+                    first_f64(grade, &["fivePoint"])?,
+                ))
+            })
+            .filter(|(credit, _)| *credit > 0.0)
+            .fold(Totals::default(), |totals, (credit, five)| {
+                totals.shifted(credit * five, credit)
+            });
+        let impact = impacts(&|run: &PolicyRun| {
+            let counted = rows
+                .iter()
+                .filter_map(|index| run.counted.get(index))
+                .fold(Totals::default(), |totals, (credit, five)| {
+                    totals.shifted(credit * five, *credit)
+                });
+            Some(run.totals.shifted(
+                all_attempts.points - counted.points,
+                all_attempts.credits - counted.credits,
+            ))
+        });
+        if impact.is_empty() {
+            continue;
+        }
+        let first = &grades[rows[0]];
+        let attempts = rows
+            .iter()
+            .map(|index| text(&grades[*index], "cj"))
+            .collect::<Vec<_>>()
+            .join("、");
+        discrepancies.push(Discrepancy {
+            kind: DiscrepancyKind::RetakeChoice,
+            xkkh: text(first, "xkkh").to_string(),
+            course_name: text(first, "kcmc").to_string(),
+            credit: first_f64(first, &["credit"]).unwrap_or(0.0),
+            detail: format!(
+                "修读 {} 次（{attempts}），重修口径决定计入哪几次",
+                rows.len()
+            ),
+            impact,
+        });
+    }
+
+    let largest = |discrepancy: &Discrepancy| {
+        discrepancy
+            .impact
+            .values()
+            .fold(0.0_f64, |max, impact| max.max(impact.abs()))
+    };
+    discrepancies.sort_by(|left, right| largest(right).total_cmp(&largest(left)));
+
+    GpaReconciliation {
+        official,
+        reference,
+        reference_five_point,
+        reference_credits,
+        policies: policy_results,
+        closest_policy,
+        discrepancies,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{reconcile, DiscrepancyKind, ReferenceSource};
+    use crate::gpa::RetakePolicy;
+    use crate::zdbk::{parse_gpa_summary, OfficialGpaSummary};
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    #[test]
+    fn reconciliation_explains_gaps_by_course() {
+        let grades = vec![
+            json!({ "xkkh": "(2022-2023-1)-A-1", "kcdm": "A", "kcmc": "微积分", "cj": "59", "xf": "4", "jd": "0" }),
+            json!({ "xkkh": "(2023-2024-1)-A-2", "kcdm": "A", "kcmc": "微积分", "cj": "85", "xf": "4", "jd": "3.9" }),
+            json!({ "xkkh": "(2022-2023-1)-B-1", "kcdm": "B", "kcmc": "英语", "cj": "90", "xf": "2", "jd": "4.8" }),
+        ];
+        let policies = [
+            ("first", RetakePolicy::First),
+            ("latest", RetakePolicy::Latest),
+        ];
+
+        let without_summary = reconcile(&grades, None, &policies, &HashMap::new());
+        assert_eq!(without_summary.reference, ReferenceSource::CourseJd);
+
+        let official = parse_gpa_summary("<span>平均绩点：</span><b>4.20</b> 获得学分: 6");
+        assert_eq!(official.five_point, Some(4.2));
+        assert_eq!(official.credits, Some(6.0));
+        let result = reconcile(&grades, Some(official), &policies, &HashMap::new());
+
+        assert_eq!(result.reference, ReferenceSource::Official);
+        assert_eq!(result.closest_policy.as_deref(), Some("latest"));
+        assert!(result.policies[1].matches);
+        assert!(!result.policies[0].matches);
+        assert_eq!(result.discrepancies[0].kind, DiscrepancyKind::RetakeChoice);
+        assert!(result.discrepancies[0].impact.contains_key("latest"));
+        assert!(result
+            .discrepancies
+            .iter()
+            .any(|item| item.kind == DiscrepancyKind::JdMismatch && item.course_name == "英语"));
+        assert!(OfficialGpaSummary::default().five_point.is_none());
+    }
+
+    #[test]
+    fn gpa_summary_reads_totals_but_not_the_grade_table() {
+        let page = include_str!("../fixtures/zdbk/xscjcx_cxXscjIndex.html");
+        let official = parse_gpa_summary(page);
+        assert_eq!(official.five_point, Some(4.21));
+        assert_eq!(official.hundred_point, Some(88.73));
+        assert_eq!(official.credits, Some(98.5));
+
+        // Without the summary panel only the table's 绩点 header is left.
+        let start = page.find("<div class=\"panel").unwrap();
+        let end = page.find("<table class=\"table table-striped").unwrap();
+        let table_only = format!("{}{}", &page[..start], &page[end..]);
+        assert_eq!(
+            parse_gpa_summary(&table_only),
+            OfficialGpaSummary::default()
+        );
+
+        let bogus = parse_gpa_summary("平均绩点：<b>6.2</b> 获得学分：<b>-3</b>");
+        assert_eq!(bogus, OfficialGpaSummary::default());
+    }
+}
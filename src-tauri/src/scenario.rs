@@ -0,0 +1,324 @@
+use crate::gpa::{RetakeOverride, RetakePolicy};
+use crate::json_store;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use tauri::AppHandle;
+
+const SCENARIO_FILE: &str = "gpa_scenarios.json";
+/// Grades that mean "no real result yet"; a simulation on such a row is a
+/// forecast rather than a what-if over a known score.
+const PENDING_GRADES: [&str; 4] = ["", "待录", "缓考", "无效"];
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GpaScenario {
+    pub id: String,
+    pub name: String,
+    pub note: String,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub selected_ids: Vec<String>,
+    pub simulated_scores: HashMap<String, f64>,
+    pub course_id_mappings: HashMap<String, String>,
+    pub retake_policy: Option<String>,
+    pub retake_overrides: HashMap<String, RetakeOverride>,
+    pub scale_ids: Option<Vec<String>>,
+    /// Simulated rows that had no real grade when saved, e.g. courses still in
+    /// progress. Re-added on load unless the transcript now has them.
+    pub planned_grades: Vec<Value>,
+    /// Simulation keys that were forecasts when saved.
+    pub pending_keys: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GpaScenarioInput {
+    pub name: String,
+    pub note: Option<String>,
+    /// The rows the scenario was built on, used to tell forecasts from
+    /// what-ifs over existing grades.
+    pub grades: Vec<Value>,
+    pub selected_ids: Option<Vec<String>>,
+    pub simulated_scores: Option<HashMap<String, f64>>,
+    pub course_id_mappings: Option<HashMap<String, String>>,
+    pub retake_policy: Option<String>,
+    pub retake_overrides: Option<HashMap<String, RetakeOverride>>,
+    pub scale_ids: Option<Vec<String>>,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StaleSimulation {
+    pub key: String,
+    pub course_name: String,
+    pub simulated: f64,
+    pub actual: String,
+}
+
+pub fn load_scenarios(app: &AppHandle) -> Vec<GpaScenario> {
+    json_store::load(app, SCENARIO_FILE)
+}
+
+fn store_scenarios(app: &AppHandle, scenarios: &[GpaScenario]) -> Result<(), String> {
+    json_store::store(app, SCENARIO_FILE, scenarios, "模拟方案")
+}
+
+pub fn find_scenario(app: &AppHandle, id: &str) -> Result<GpaScenario, String> {
+    load_scenarios(app)
+        .into_iter()
+        .find(|scenario| scenario.id == id)
+        .ok_or_else(|| format!("未找到模拟方案: {id}"))
+}
+
+fn read_str<'a>(grade: &'a Value, key: &str) -> &'a str {
+    grade
+        .get(key)
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .trim()
+}
+
+fn grade_keys(grade: &Value) -> [&str; 3] {
+    [
+        read_str(grade, "xkkh"),
+        read_str(grade, "kcdm"),
+        read_str(grade, "retakeKey"),
+    ]
+}
+
+fn has_real_grade(grade: &Value) -> bool {
+    !PENDING_GRADES.contains(&read_str(grade, "cj"))
+}
+
+fn matches_key(grade: &Value, key: &str) -> bool {
+    !key.is_empty() && grade_keys(grade).contains(&key)
+}
+
+fn unique_id(existing: &[GpaScenario]) -> String {
+    let mut stamp = Local::now().timestamp_millis();
+    loop {
+        let id = format!("scn-{stamp}");
+        if existing.iter().all(|scenario| scenario.id != id) {
+            return id;
+        }
+        stamp += 1;
+    }
+}
+
+fn apply_input(scenario: &mut GpaScenario, input: GpaScenarioInput) -> Result<(), String> {
+    let name = input.name.trim();
+    if name.is_empty() {
+        return Err("模拟方案名称不能为空".to_string());
+    }
+    let simulated = input.simulated_scores.unwrap_or_default();
+    if let Some((key, score)) = simulated
+        .iter()
+        .find(|(_, score)| !score.is_finite() || **score < 0.0 || **score > 100.0)
+    {
+        return Err(format!("{key} 的模拟分 {score} 不在 0-100 之间"));
+    }
+    if let Some(policy) = &input.retake_policy {
+        policy.parse::<RetakePolicy>()?;
+    }
+
+    let pending_keys = simulated
+        .keys()
+        .filter(|key| {
+            !input
+                .grades
+                .iter()
+                .any(|grade| matches_key(grade, key) && has_real_grade(grade))
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    scenario.planned_grades = input
+        .grades
+        .into_iter()
+        .filter(|grade| {
+            !has_real_grade(grade) && pending_keys.iter().any(|key| matches_key(grade, key))
+        })
+        .collect();
+    scenario.pending_keys = pending_keys;
+    scenario.name = name.to_string();
+    scenario.note = input.note.unwrap_or_default().trim().to_string();
+    scenario.selected_ids = input.selected_ids.unwrap_or_default();
+    scenario.simulated_scores = simulated;
+    scenario.course_id_mappings = input.course_id_mappings.unwrap_or_default();
+    scenario.retake_policy = input.retake_policy;
+    scenario.retake_overrides = input.retake_overrides.unwrap_or_default();
+    scenario.scale_ids = input.scale_ids;
+    scenario.updated_at = Local::now().timestamp();
+    Ok(())
+}
+
+pub fn create_scenario(app: &AppHandle, input: GpaScenarioInput) -> Result<GpaScenario, String> {
+    let mut scenarios = load_scenarios(app);
+    let mut scenario = GpaScenario {
+        id: unique_id(&scenarios),
+        created_at: Local::now().timestamp(),
+        ..GpaScenario::default()
+    };
+    apply_input(&mut scenario, input)?;
+    scenarios.push(scenario.clone());
+    store_scenarios(app, &scenarios)?;
+    Ok(scenario)
+}
+
+pub fn update_scenario(
+    app: &AppHandle,
+    id: &str,
+    input: GpaScenarioInput,
+) -> Result<GpaScenario, String> {
+    let mut scenarios = load_scenarios(app);
+    let scenario = scenarios
+        .iter_mut()
+        .find(|scenario| scenario.id == id)
+        .ok_or_else(|| format!("未找到模拟方案: {id}"))?;
+    apply_input(scenario, input)?;
+    let updated = scenario.clone();
+    store_scenarios(app, &scenarios)?;
+    Ok(updated)
+}
+
+pub fn delete_scenario(app: &AppHandle, id: &str) -> Result<(), String> {
+    let mut scenarios = load_scenarios(app);
+    let before = scenarios.len();
+    scenarios.retain(|scenario| scenario.id != id);
+    if scenarios.len() == before {
+        return Err(format!("未找到模拟方案: {id}"));
+    }
+    store_scenarios(app, &scenarios)
+}
+
+pub fn duplicate_scenario(
+    app: &AppHandle,
+    id: &str,
+    name: Option<String>,
+) -> Result<GpaScenario, String> {
+    let mut scenarios = load_scenarios(app);
+    let source = scenarios
+        .iter()
+        .find(|scenario| scenario.id == id)
+        .ok_or_else(|| format!("未找到模拟方案: {id}"))?;
+    let now = Local::now().timestamp();
+    let copy = GpaScenario {
+        id: unique_id(&scenarios),
+        name: name
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| format!("{} 副本", source.name)),
+        created_at: now,
+        updated_at: now,
+        ..source.clone()
+    };
+    scenarios.push(copy.clone());
+    store_scenarios(app, &scenarios)?;
+    Ok(copy)
+}
+
+/// The latest transcript plus any saved planned rows it does not cover yet.
+pub fn merge_planned_grades(transcript: &[Value], scenario: &GpaScenario) -> Vec<Value> {
+    let mut grades = transcript.to_vec();
+    for planned in &scenario.planned_grades {
+        let covered = transcript.iter().any(|grade| {
+            grade_keys(planned)
+                .iter()
+                .any(|key| matches_key(grade, key))
+        });
+        if !covered {
+            grades.push(planned.clone());
+        }
+    }
+    grades
+}
+
+/// Forecasts that the transcript has since replaced with a real grade.
+pub fn stale_simulations(transcript: &[Value], scenario: &GpaScenario) -> Vec<StaleSimulation> {
+    let mut stale = scenario
+        .pending_keys
+        .iter()
+        .filter_map(|key| {
+            let simulated = *scenario.simulated_scores.get(key)?;
+            let grade = transcript
+                .iter()
+                .find(|grade| matches_key(grade, key) && has_real_grade(grade))?;
+            Some(StaleSimulation {
+                key: key.clone(),
+                course_name: read_str(grade, "kcmc").to_string(),
+                simulated,
+                actual: read_str(grade, "cj").to_string(),
+            })
+        })
+        .collect::<Vec<_>>();
+    stale.sort_by(|left, right| left.key.cmp(&right.key));
+    stale
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GpaScenarioInput;
+    use super::{apply_input, merge_planned_grades, stale_simulations, GpaScenario};
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    #[test]
+    fn scenario_tracks_forecasts_until_real_grades_arrive() {
+        let saved_rows = vec![
+            json!({ "xkkh": "(2024-2025-1)-A-1", "kcdm": "A", "kcmc": "数学分析", "cj": "88" }),
+            json!({ "xkkh": "current-B", "kcdm": "B", "kcmc": "线性代数", "cj": "" }),
+        ];
+        let mut simulated = HashMap::new();
+        simulated.insert("(2024-2025-1)-A-1".to_string(), 95.0);
+        simulated.insert("B".to_string(), 90.0);
+
+        let mut scenario = GpaScenario::default();
+        apply_input(
+            &mut scenario,
+            GpaScenarioInput {
+                name: " 冲刺 ".to_string(),
+                note: None,
+                grades: saved_rows,
+                selected_ids: None,
+                simulated_scores: Some(simulated),
+                course_id_mappings: None,
+                retake_policy: None,
+                retake_overrides: None,
+                scale_ids: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(scenario.name, "冲刺");
+        assert_eq!(scenario.pending_keys, vec!["B"]);
+        assert_eq!(scenario.planned_grades.len(), 1);
+
+        let before = vec![json!({ "xkkh": "(2024-2025-1)-A-1", "kcdm": "A", "cj": "88" })];
+        assert_eq!(merge_planned_grades(&before, &scenario).len(), 2);
+        assert!(stale_simulations(&before, &scenario).is_empty());
+
+        let after = vec![
+            before[0].clone(),
+            json!({ "xkkh": "(2024-2025-2)-B-1", "kcdm": "B", "kcmc": "线性代数", "cj": "82" }),
+        ];
+        assert_eq!(merge_planned_grades(&after, &scenario).len(), 2);
+        let stale = stale_simulations(&after, &scenario);
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].actual, "82");
+        assert_eq!(stale[0].simulated, 90.0);
+
+        let invalid = GpaScenarioInput {
+            name: "冲刺".to_string(),
+            note: None,
+            grades: Vec::new(),
+            selected_ids: None,
+            simulated_scores: None,
+            course_id_mappings: None,
+            retake_policy: Some("lowest".to_string()),
+            retake_overrides: None,
+            scale_ids: None,
+        };
+        assert!(apply_input(&mut scenario, invalid).is_err());
+        assert_eq!(scenario.retake_policy, None);
+    }
+}
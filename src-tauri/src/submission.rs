@@ -0,0 +1,205 @@
+use crate::courses;
+use crate::materials;
+use crate::zjuam::AppState;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "kind")]
+pub enum SubmissionAttachment {
+    /// Any file picked from disk.
+    File { path: String },
+    /// A file already in the materials library.
+    Material { relative_path: String },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HomeworkSubmissionInput {
+    pub activity_id: i64,
+    #[serde(default)]
+    pub attachments: Vec<SubmissionAttachment>,
+    /// Uploads from an earlier draft to keep attached.
+    #[serde(default)]
+    pub upload_ids: Vec<i64>,
+    pub comment: Option<String>,
+    pub draft: Option<bool>,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SubmittedUpload {
+    pub id: i64,
+    pub name: String,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SubmissionStatus {
+    Draft,
+    Submitted,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SubmissionResult {
+    pub activity_id: i64,
+    pub submission_id: Option<i64>,
+    pub status: SubmissionStatus,
+    pub submitted_at: Option<String>,
+    pub uploads: Vec<SubmittedUpload>,
+}
+
+fn attachment_path(app: &AppHandle, attachment: &SubmissionAttachment) -> Result<PathBuf, String> {
+    match attachment {
+        SubmissionAttachment::File { path } => {
+            let path = PathBuf::from(path.trim());
+            if !path.is_file() {
+                return Err(format!("附件不存在: {}", path.display()));
+            }
+            Ok(path)
+        }
+        SubmissionAttachment::Material { relative_path } => {
+            materials::material_file_path(app, relative_path)
+        }
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("attachment")
+        .to_string()
+}
+
+fn upload_record(upload: &Value) -> Option<SubmittedUpload> {
+    Some(SubmittedUpload {
+        id: upload.get("id").and_then(Value::as_i64)?,
+        name: upload
+            .get("name")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string(),
+    })
+}
+
+/// Reads the submission record 学在浙大 returns. Falls back to what was sent
+/// for fields the response leaves out.
+fn submission_result(
+    activity_id: i64,
+    response: &Value,
+    draft: bool,
+    sent: Vec<SubmittedUpload>,
+) -> SubmissionResult {
+    let is_draft = response
+        .get("is_draft")
+        .and_then(Value::as_bool)
+        .or_else(|| {
+            response
+                .get("status")
+                .and_then(Value::as_str)
+                .map(|status| status == "draft")
+        })
+        .unwrap_or(draft);
+    let uploads = response
+        .get("uploads")
+        .and_then(Value::as_array)
+        .map(|uploads| uploads.iter().filter_map(upload_record).collect::<Vec<_>>())
+        .filter(|uploads| !uploads.is_empty())
+        .unwrap_or(sent);
+    SubmissionResult {
+        activity_id,
+        submission_id: response.get("id").and_then(Value::as_i64),
+        status: if is_draft {
+            SubmissionStatus::Draft
+        } else {
+            SubmissionStatus::Submitted
+        },
+        submitted_at: ["submitted_at", "updated_at", "created_at"]
+            .iter()
+            .find_map(|key| response.get(*key).and_then(Value::as_str))
+            .map(str::to_string),
+        uploads,
+    }
+}
+
+/// Uploads every attachment, then submits the homework or saves it as a
+/// draft. Nothing is submitted if any upload fails.
+pub async fn submit(
+    app: &AppHandle,
+    state: &AppState,
+    input: HomeworkSubmissionInput,
+) -> Result<SubmissionResult, String> {
+    let paths = input
+        .attachments
+        .iter()
+        .map(|attachment| attachment_path(app, attachment))
+        .collect::<Result<Vec<_>, _>>()?;
+    if paths.is_empty() && input.upload_ids.is_empty() {
+        return Err("请至少添加一个附件".to_string());
+    }
+
+    let mut uploads = Vec::new();
+    for path in &paths {
+        let name = file_name(path);
+        let data = fs::read(path).map_err(|error| format!("读取附件 {name} 失败: {error}"))?;
+        let created = courses::upload_attachment(state, &name, data)
+            .await
+            .map_err(|error| format!("{name}: {error}"))?;
+        uploads.push(upload_record(&created).ok_or_else(|| format!("{name}: 上传未返回编号"))?);
+    }
+
+    let upload_ids = input
+        .upload_ids
+        .iter()
+        .copied()
+        .chain(uploads.iter().map(|upload| upload.id))
+        .collect::<Vec<_>>();
+    let draft = input.draft.unwrap_or(false);
+    let response = courses::submit_homework(
+        state,
+        input.activity_id,
+        &upload_ids,
+        input.comment.as_deref().unwrap_or_default(),
+        draft,
+    )
+    .await?;
+    Ok(submission_result(
+        input.activity_id,
+        &response,
+        draft,
+        uploads,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{submission_result, SubmissionStatus, SubmittedUpload};
+    use serde_json::json;
+
+    #[test]
+    fn submission_result_prefers_response_and_falls_back_to_sent_uploads() {
+        let sent = vec![SubmittedUpload {
+            id: 7,
+            name: "report.pdf".to_string(),
+        }];
+        let submitted = submission_result(
+            1,
+            &json!({ "id": 42, "is_draft": false, "submitted_at": "2024-05-01T23:00:00Z",
+                     "uploads": [{ "id": 7, "name": "report.pdf" }, { "id": 3, "name": "old.pdf" }] }),
+            true,
+            sent.clone(),
+        );
+        assert_eq!(submitted.status, SubmissionStatus::Submitted);
+        assert_eq!(submitted.submission_id, Some(42));
+        assert_eq!(submitted.uploads.len(), 2);
+
+        let draft = submission_result(1, &json!({}), true, sent);
+        assert_eq!(draft.status, SubmissionStatus::Draft);
+        assert_eq!(draft.uploads[0].id, 7);
+        assert!(draft.submitted_at.is_none());
+    }
+}
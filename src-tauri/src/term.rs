@@ -1,3 +1,4 @@
+use chrono::{Datelike, Duration, NaiveDate};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -53,6 +54,29 @@ pub struct NormalizedTimetableSession {
     pub second_half: bool,
 }
 
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum TimetableChangeKind {
+    Added,
+    Removed,
+    Moved,
+    Relocated,
+    Reweeked,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TimetableChange {
+    pub kind: TimetableChangeKind,
+    pub session_id: String,
+    pub xkkh: String,
+    pub course_name: String,
+    pub before: Option<NormalizedTimetableSession>,
+    pub after: Option<NormalizedTimetableSession>,
+    pub affected_weeks: Vec<u8>,
+    pub summary: String,
+}
+
 pub fn normalize_academic_semester(semester: &str) -> Option<&'static str> {
     match semester.trim() {
         "1" | "3" => Some("1"),
@@ -273,6 +297,234 @@ pub fn normalize_timetable_sessions(
     sessions
 }
 
+/// Monday of teaching week 1, falling back to the usual September / late-February
+/// start when the remote calendar has no `dayOfWeekToDays` entry.
+pub fn resolve_term_anchor(term: &TermDescriptor, config: &TermTimeConfig) -> NaiveDate {
+    config
+        .start_date
+        .as_deref()
+        .and_then(|value| NaiveDate::parse_from_str(value, "%Y-%m-%d").ok())
+        .map(monday_of)
+        .unwrap_or_else(|| {
+            let year = term.year.parse::<i32>().unwrap_or(1970);
+            let base = if term.academic_semester == "2" {
+                NaiveDate::from_ymd_opt(year + 1, 2, 24)
+            } else {
+                NaiveDate::from_ymd_opt(year, 9, 9)
+            }
+            .unwrap_or_default();
+            monday_of(base)
+        })
+}
+
+/// 1-based teaching week containing `date`, or `None` outside the 16-week term.
+pub fn teaching_week_of(anchor: NaiveDate, date: NaiveDate) -> Option<u8> {
+    let days = (date - anchor).num_days();
+    if days < 0 {
+        return None;
+    }
+    let week = days / 7 + 1;
+    (week <= 16).then_some(week as u8)
+}
+
+/// Teaching weeks touched by the `days` days starting at `from`. Dates before
+/// the anchor are allowed, so changes are caught in the run-up to week 1.
+pub fn teaching_weeks_within(anchor: NaiveDate, from: NaiveDate, days: i64) -> Vec<u8> {
+    let mut weeks = (0..=days)
+        .filter_map(|offset| teaching_week_of(anchor, from + Duration::days(offset)))
+        .collect::<Vec<_>>();
+    weeks.dedup();
+    weeks
+}
+
+/// Compare two snapshots of the same term. Sessions are paired by `id` first and
+/// then by `xkkh`, so a session that changed day or period is reported as moved
+/// rather than as a removal plus an addition.
+pub fn diff_timetable_sessions(
+    previous: &[NormalizedTimetableSession],
+    current: &[NormalizedTimetableSession],
+) -> Vec<TimetableChange> {
+    let mut unmatched_previous = previous.iter().collect::<Vec<_>>();
+    let mut pairs = Vec::new();
+    let mut added = Vec::new();
+
+    for session in current {
+        if let Some(index) = unmatched_previous
+            .iter()
+            .position(|candidate| candidate.id == session.id)
+        {
+            pairs.push((unmatched_previous.remove(index), session));
+        } else {
+            added.push(session);
+        }
+    }
+
+    let mut still_added = Vec::new();
+    for session in added {
+        let paired = (!session.xkkh.is_empty())
+            .then(|| {
+                unmatched_previous
+                    .iter()
+                    .position(|candidate| candidate.xkkh == session.xkkh)
+            })
+            .flatten();
+        match paired {
+            Some(index) => pairs.push((unmatched_previous.remove(index), session)),
+            None => still_added.push(session),
+        }
+    }
+
+    let mut changes = Vec::new();
+    for (before, after) in pairs {
+        if before.day_of_week != after.day_of_week
+            || before.start_period != after.start_period
+            || before.end_period != after.end_period
+        {
+            changes.push(build_change(
+                TimetableChangeKind::Moved,
+                Some(before),
+                Some(after),
+                format!(
+                    "{} 由周{}第{}-{}节调整到周{}第{}-{}节",
+                    after.course_name,
+                    before.day_of_week,
+                    before.start_period,
+                    before.end_period,
+                    after.day_of_week,
+                    after.start_period,
+                    after.end_period
+                ),
+            ));
+        }
+        if before.location != after.location {
+            changes.push(build_change(
+                TimetableChangeKind::Relocated,
+                Some(before),
+                Some(after),
+                format!(
+                    "{} 上课地点由 {} 改为 {}",
+                    after.course_name,
+                    display_or_pending(&before.location),
+                    display_or_pending(&after.location)
+                ),
+            ));
+        }
+        if before.week_numbers != after.week_numbers {
+            changes.push(build_change(
+                TimetableChangeKind::Reweeked,
+                Some(before),
+                Some(after),
+                format!(
+                    "{} 上课周次由 {} 改为 {}",
+                    after.course_name,
+                    format_weeks(&before.week_numbers),
+                    format_weeks(&after.week_numbers)
+                ),
+            ));
+        }
+    }
+
+    for session in still_added {
+        changes.push(build_change(
+            TimetableChangeKind::Added,
+            None,
+            Some(session),
+            format!(
+                "新增 {} 周{}第{}-{}节",
+                session.course_name, session.day_of_week, session.start_period, session.end_period
+            ),
+        ));
+    }
+    for session in unmatched_previous {
+        changes.push(build_change(
+            TimetableChangeKind::Removed,
+            Some(session),
+            None,
+            format!(
+                "取消 {} 周{}第{}-{}节",
+                session.course_name, session.day_of_week, session.start_period, session.end_period
+            ),
+        ));
+    }
+
+    changes
+}
+
+fn build_change(
+    kind: TimetableChangeKind,
+    before: Option<&NormalizedTimetableSession>,
+    after: Option<&NormalizedTimetableSession>,
+    summary: String,
+) -> TimetableChange {
+    let reference = after.or(before).expect("change needs at least one side");
+    let before_weeks = before
+        .map(|value| value.week_numbers.clone())
+        .unwrap_or_default();
+    let after_weeks = after
+        .map(|value| value.week_numbers.clone())
+        .unwrap_or_default();
+    let mut affected_weeks = match kind {
+        TimetableChangeKind::Reweeked => before_weeks
+            .iter()
+            .filter(|week| !after_weeks.contains(week))
+            .chain(
+                after_weeks
+                    .iter()
+                    .filter(|week| !before_weeks.contains(week)),
+            )
+            .copied()
+            .collect::<Vec<_>>(),
+        _ => before_weeks.into_iter().chain(after_weeks).collect(),
+    };
+    affected_weeks.sort_unstable();
+    affected_weeks.dedup();
+
+    TimetableChange {
+        kind,
+        session_id: reference.id.clone(),
+        xkkh: reference.xkkh.clone(),
+        course_name: reference.course_name.clone(),
+        before: before.cloned(),
+        after: after.cloned(),
+        affected_weeks,
+        summary,
+    }
+}
+
+fn display_or_pending(location: &str) -> &str {
+    if location.is_empty() {
+        "待定"
+    } else {
+        location
+    }
+}
+
+fn format_weeks(weeks: &[u8]) -> String {
+    let mut ranges = Vec::<(u8, u8)>::new();
+    for week in weeks {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == *week => *end = *week,
+            _ => ranges.push((*week, *week)),
+        }
+    }
+    let text = ranges
+        .iter()
+        .map(|(start, end)| {
+            if start == end {
+                start.to_string()
+            } else {
+                format!("{start}-{end}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    format!("第{text}周")
+}
+
+fn monday_of(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(i64::from(date.weekday().num_days_from_monday()))
+}
+
 fn parse_term_config(raw: &Value, source: &str) -> TermTimeConfig {
     let mut holidays = BTreeMap::new();
     if let Some(map) = raw.get("holidays").and_then(Value::as_object) {
@@ -649,8 +901,9 @@ fn read_term_config_cache(app: &AppHandle, filename: &str) -> Option<Value> {
 #[cfg(test)]
 mod tests {
     use super::{
-        build_xkkh_prefix, descriptor_from_name, descriptor_from_parts,
+        build_xkkh_prefix, descriptor_from_name, descriptor_from_parts, diff_timetable_sessions,
         normalize_academic_semester, normalize_timetable_sessions, parse_relative_weeks,
+        teaching_weeks_within, TimetableChangeKind,
     };
     use serde_json::json;
 
@@ -713,4 +966,51 @@ mod tests {
         assert_eq!(sessions.len(), 1);
         assert_eq!(sessions[0].course_name, "程序设计");
     }
+
+    #[test]
+    fn timetable_diff_pairs_moves_by_xkkh() {
+        let descriptor = descriptor_from_parts("2024", "1");
+        let row = |day: &str, location: &str, weeks: &str| {
+            json!({
+                "xkkh": "(2024-2025-1)-211G0001-01",
+                "kcdm": "211G0001",
+                "kcmc": "程序设计",
+                "xqj": day,
+                "jcs": "1-2",
+                "zcs": weeks,
+                "xxq": "秋",
+                "cdmc": location,
+            })
+        };
+        let previous = normalize_timetable_sessions(&descriptor, &[row("1", "东1A-101", "1-8")]);
+        let current = normalize_timetable_sessions(&descriptor, &[row("3", "东2-202", "1-6")]);
+
+        let kinds = diff_timetable_sessions(&previous, &current)
+            .into_iter()
+            .map(|change| change.kind)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                TimetableChangeKind::Moved,
+                TimetableChangeKind::Relocated,
+                TimetableChangeKind::Reweeked,
+            ]
+        );
+
+        let reweeked = diff_timetable_sessions(&previous, &current).pop().unwrap();
+        assert_eq!(reweeked.affected_weeks, vec![7, 8]);
+        assert!(diff_timetable_sessions(&current, &current).is_empty());
+    }
+
+    #[test]
+    fn upcoming_weeks_include_the_run_up_to_week_one() {
+        let anchor = chrono::NaiveDate::from_ymd_opt(2026, 2, 23).unwrap();
+        let before = chrono::NaiveDate::from_ymd_opt(2026, 2, 16).unwrap();
+        assert_eq!(teaching_weeks_within(anchor, before, 14), [1, 2]);
+        let long_before = chrono::NaiveDate::from_ymd_opt(2026, 1, 1).unwrap();
+        assert!(teaching_weeks_within(anchor, long_before, 14).is_empty());
+        let last = chrono::NaiveDate::from_ymd_opt(2026, 6, 8).unwrap();
+        assert_eq!(teaching_weeks_within(anchor, last, 14), [16]);
+    }
 }
@@ -0,0 +1,579 @@
+use crate::term::{NormalizedTimetableSession, TermDescriptor};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+
+/// One meeting parsed from a 学在浙大 course's `classroom_schedule` field.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct LearningScheduleEntry {
+    pub course_id: i64,
+    pub course_code: String,
+    pub course_name: String,
+    pub teacher: String,
+    pub day_of_week: u8,
+    pub start_period: u8,
+    pub end_period: u8,
+    pub week_numbers: Vec<u8>,
+    pub location: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleDisagreement {
+    pub session_id: String,
+    pub course_name: String,
+    pub field: String,
+    pub zdbk: String,
+    pub courses: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct UnmatchedCourse {
+    pub source: String,
+    pub course_code: String,
+    pub course_name: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TimetableMergeReport {
+    pub filled_locations: usize,
+    pub filled_teachers: usize,
+    pub disagreements: Vec<ScheduleDisagreement>,
+    pub only_in_zdbk: Vec<UnmatchedCourse>,
+    pub only_in_courses: Vec<UnmatchedCourse>,
+    pub learning_entries: Vec<LearningScheduleEntry>,
+}
+
+fn read_string(value: &Value, keys: &[&str]) -> String {
+    keys.iter()
+        .find_map(|key| {
+            value.get(*key).and_then(|item| {
+                item.as_str()
+                    .map(|text| text.trim().to_string())
+                    .or_else(|| item.as_i64().map(|number| number.to_string()))
+            })
+        })
+        .filter(|text| !text.is_empty())
+        .unwrap_or_default()
+}
+
+fn read_u8(value: &Value, keys: &[&str]) -> Option<u8> {
+    keys.iter().find_map(|key| {
+        value.get(*key).and_then(|item| {
+            item.as_u64()
+                .and_then(|number| u8::try_from(number).ok())
+                .or_else(|| {
+                    item.as_str()
+                        .and_then(|text| text.trim().parse::<u8>().ok())
+                })
+        })
+    })
+}
+
+fn chinese_weekday(text: &str) -> Option<u8> {
+    match text {
+        "一" | "1" => Some(1),
+        "二" | "2" => Some(2),
+        "三" | "3" => Some(3),
+        "四" | "4" => Some(4),
+        "五" | "5" => Some(5),
+        "六" | "6" => Some(6),
+        "日" | "天" | "七" | "7" => Some(7),
+        _ => None,
+    }
+}
+
+fn parse_number_list(text: &str) -> Vec<u8> {
+    let mut numbers = Vec::new();
+    for segment in text.split([',', '，', '、']) {
+        let bounds = segment
+            .split('-')
+            .filter_map(|part| part.trim().parse::<u8>().ok())
+            .collect::<Vec<_>>();
+        match bounds.as_slice() {
+            [single] => numbers.push(*single),
+            [start, end] if start <= end => numbers.extend(*start..=*end),
+            _ => {}
+        }
+    }
+    numbers.sort_unstable();
+    numbers.dedup();
+    numbers
+}
+
+fn parse_week_text(text: &str) -> Vec<u8> {
+    let digits = text
+        .chars()
+        .filter(|ch| ch.is_ascii_digit() || matches!(ch, '-' | ',' | '，' | '、'))
+        .collect::<String>();
+    let mut weeks = parse_number_list(&digits);
+    if text.contains('单') {
+        weeks.retain(|week| week % 2 == 1);
+    } else if text.contains('双') {
+        weeks.retain(|week| week % 2 == 0);
+    }
+    weeks
+}
+
+/// A meeting as parsed, before it is attached to its course:
+/// day, first and last period, weeks, location and teacher.
+struct ParsedSlot {
+    day: u8,
+    start: u8,
+    end: u8,
+    weeks: Vec<u8>,
+    location: String,
+    teacher: String,
+}
+
+/// Parse free-form schedule text such as
+/// `周一第1,2节{第1-8周} 紫金港东1A-101；星期三第3-5节 紫金港西2-208`.
+fn parse_schedule_text(text: &str) -> Vec<ParsedSlot> {
+    let Ok(re) = Regex::new(
+        r"(?:周|星期)([一二三四五六日天1-7])\s*第?([0-9,，\-]+)节\s*(?:[{（(]([^}）)]*)[}）)])?\s*([^;；\n]*)",
+    ) else {
+        return Vec::new();
+    };
+
+    re.captures_iter(text)
+        .filter_map(|caps| {
+            let day = chinese_weekday(caps.get(1)?.as_str())?;
+            let periods = parse_number_list(caps.get(2)?.as_str());
+            let start = *periods.first()?;
+            let end = *periods.last()?;
+            let weeks = caps
+                .get(3)
+                .map(|value| parse_week_text(value.as_str()))
+                .unwrap_or_default();
+            let location = caps
+                .get(4)
+                .map(|value| {
+                    value
+                        .as_str()
+                        .trim()
+                        .trim_matches(|ch| ch == ',' || ch == '，')
+                })
+                .unwrap_or_default()
+                .to_string();
+            Some(ParsedSlot {
+                day,
+                start,
+                end,
+                weeks,
+                location,
+                teacher: String::new(),
+            })
+        })
+        .collect()
+}
+
+fn collect_schedule_items(value: &Value, output: &mut Vec<ParsedSlot>) {
+    match value {
+        Value::String(text) => {
+            output.extend(parse_schedule_text(text));
+        }
+        Value::Array(items) => {
+            for item in items {
+                collect_schedule_items(item, output);
+            }
+        }
+        Value::Object(map) => {
+            for key in ["schedules", "items", "list", "data"] {
+                if let Some(nested) = map.get(key) {
+                    collect_schedule_items(nested, output);
+                    return;
+                }
+            }
+
+            let day = read_u8(value, &["weekday", "week_day", "day_of_week", "day"]);
+            let start = read_u8(
+                value,
+                &[
+                    "start_lesson",
+                    "start_period",
+                    "lesson_start",
+                    "start_section",
+                    "start",
+                ],
+            );
+            if let (Some(day), Some(start)) = (day.filter(|day| (1..=7).contains(day)), start) {
+                let end = read_u8(
+                    value,
+                    &[
+                        "end_lesson",
+                        "end_period",
+                        "lesson_end",
+                        "end_section",
+                        "end",
+                    ],
+                )
+                .filter(|end| *end >= start)
+                .unwrap_or(start);
+                let weeks = match value.get("weeks").or_else(|| value.get("week")) {
+                    Some(Value::Array(items)) => items
+                        .iter()
+                        .filter_map(|item| item.as_u64().and_then(|week| u8::try_from(week).ok()))
+                        .collect(),
+                    Some(Value::String(text)) => parse_week_text(text),
+                    _ => Vec::new(),
+                };
+                output.push(ParsedSlot {
+                    day,
+                    start,
+                    end,
+                    weeks,
+                    location: read_string(
+                        value,
+                        &[
+                            "classroom",
+                            "classroom_name",
+                            "location",
+                            "room",
+                            "address",
+                            "place",
+                        ],
+                    ),
+                    teacher: read_string(value, &["teacher", "teacher_name", "instructor"]),
+                });
+                return;
+            }
+
+            for nested in map.values().filter(|nested| nested.is_string()) {
+                collect_schedule_items(nested, output);
+            }
+        }
+        _ => {}
+    }
+}
+
+pub fn parse_learning_schedules(courses: &[Value]) -> Vec<LearningScheduleEntry> {
+    let mut entries = Vec::new();
+    for course in courses {
+        let Some(schedule) = course.get("classroom_schedule") else {
+            continue;
+        };
+        let course_name = read_string(course, &["display_name", "name", "second_name"]);
+        let course_code = read_string(course, &["course_code"]);
+        let instructor = course
+            .get("instructors")
+            .and_then(Value::as_array)
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(|item| item.get("name").and_then(Value::as_str))
+                    .map(str::trim)
+                    .filter(|name| !name.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            })
+            .unwrap_or_default();
+
+        let mut items = Vec::new();
+        collect_schedule_items(schedule, &mut items);
+        for slot in items {
+            entries.push(LearningScheduleEntry {
+                course_id: course.get("id").and_then(Value::as_i64).unwrap_or_default(),
+                course_code: course_code.clone(),
+                course_name: course_name.clone(),
+                teacher: if slot.teacher.is_empty() {
+                    instructor.clone()
+                } else {
+                    slot.teacher
+                },
+                day_of_week: slot.day,
+                start_period: slot.start,
+                end_period: slot.end,
+                week_numbers: slot.weeks,
+                location: slot.location,
+            });
+        }
+    }
+    entries
+}
+
+/// Keep only 学在浙大 courses that can belong to `term`: a course code carrying
+/// another term's xkkh prefix is dropped, codes without a prefix are kept.
+pub fn filter_courses_for_term(courses: Vec<Value>, term: &TermDescriptor) -> Vec<Value> {
+    let prefix = crate::term::build_xkkh_prefix(&term.year, &term.academic_semester);
+    courses
+        .into_iter()
+        .filter(|course| {
+            let code = read_string(course, &["course_code"]);
+            !code.starts_with('(') || code.starts_with(&prefix)
+        })
+        .collect()
+}
+
+fn normalize_name(name: &str) -> String {
+    name.chars()
+        .filter(|ch| !ch.is_whitespace() && !matches!(ch, '(' | ')' | '（' | '）'))
+        .collect::<String>()
+        .to_lowercase()
+}
+
+fn course_matches(
+    session: &NormalizedTimetableSession,
+    course_code: &str,
+    course_name: &str,
+) -> bool {
+    if !course_code.is_empty() {
+        if !session.xkkh.is_empty() && course_code == session.xkkh {
+            return true;
+        }
+        if !session.course_id.is_empty() && course_code.contains(&session.course_id) {
+            return true;
+        }
+    }
+    !course_name.is_empty() && normalize_name(course_name) == normalize_name(&session.course_name)
+}
+
+fn normalize_location(location: &str) -> String {
+    location
+        .chars()
+        .filter(|ch| !ch.is_whitespace())
+        .collect::<String>()
+        .replace("（", "(")
+        .replace("）", ")")
+}
+
+/// Reconcile zdbk sessions with 学在浙大 schedules: fill blanks from the other
+/// system, record disagreements, and list courses only one side knows about.
+pub fn merge_learning_schedules(
+    sessions: &[NormalizedTimetableSession],
+    courses: &[Value],
+) -> (Vec<NormalizedTimetableSession>, TimetableMergeReport) {
+    let entries = parse_learning_schedules(courses);
+    let mut merged = sessions.to_vec();
+    let mut report = TimetableMergeReport::default();
+    let mut matched_courses = HashSet::<usize>::new();
+    let mut matched_session_courses = HashSet::<String>::new();
+
+    for session in &mut merged {
+        let course_index = courses.iter().position(|course| {
+            course_matches(
+                session,
+                &read_string(course, &["course_code"]),
+                &read_string(course, &["display_name", "name", "second_name"]),
+            )
+        });
+        let Some(course_index) = course_index else {
+            continue;
+        };
+        matched_courses.insert(course_index);
+        matched_session_courses.insert(session.xkkh.clone() + &session.course_name);
+
+        let course = &courses[course_index];
+        let course_code = read_string(course, &["course_code"]);
+        let course_name = read_string(course, &["display_name", "name", "second_name"]);
+        let candidates = entries
+            .iter()
+            .filter(|entry| entry.course_code == course_code && entry.course_name == course_name)
+            .collect::<Vec<_>>();
+        let slot_match = candidates.iter().find(|entry| {
+            entry.day_of_week == session.day_of_week
+                && entry.start_period <= session.end_period
+                && session.start_period <= entry.end_period
+        });
+
+        let Some(entry) = slot_match else {
+            if !candidates.is_empty() {
+                report.disagreements.push(ScheduleDisagreement {
+                    session_id: session.id.clone(),
+                    course_name: session.course_name.clone(),
+                    field: "time".to_string(),
+                    zdbk: format!(
+                        "周{}第{}-{}节",
+                        session.day_of_week, session.start_period, session.end_period
+                    ),
+                    courses: candidates
+                        .iter()
+                        .map(|entry| {
+                            format!(
+                                "周{}第{}-{}节",
+                                entry.day_of_week, entry.start_period, entry.end_period
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                        .join("；"),
+                });
+            }
+            if session.teacher.is_empty() {
+                let instructor = candidates
+                    .first()
+                    .map(|entry| entry.teacher.clone())
+                    .unwrap_or_else(|| read_instructors(course));
+                if !instructor.is_empty() {
+                    session.teacher = instructor;
+                    report.filled_teachers += 1;
+                }
+            }
+            continue;
+        };
+
+        if session.location.is_empty() && !entry.location.is_empty() {
+            session.location = entry.location.clone();
+            report.filled_locations += 1;
+        } else if !entry.location.is_empty()
+            && normalize_location(&session.location) != normalize_location(&entry.location)
+        {
+            report.disagreements.push(ScheduleDisagreement {
+                session_id: session.id.clone(),
+                course_name: session.course_name.clone(),
+                field: "location".to_string(),
+                zdbk: session.location.clone(),
+                courses: entry.location.clone(),
+            });
+        }
+
+        if session.teacher.is_empty() && !entry.teacher.is_empty() {
+            session.teacher = entry.teacher.clone();
+            report.filled_teachers += 1;
+        }
+
+        if !entry.week_numbers.is_empty() && entry.week_numbers != session.week_numbers {
+            report.disagreements.push(ScheduleDisagreement {
+                session_id: session.id.clone(),
+                course_name: session.course_name.clone(),
+                field: "weeks".to_string(),
+                zdbk: join_weeks(&session.week_numbers),
+                courses: join_weeks(&entry.week_numbers),
+            });
+        }
+    }
+
+    let mut seen_zdbk = HashSet::new();
+    for session in sessions {
+        if matched_session_courses.contains(&(session.xkkh.clone() + &session.course_name)) {
+            continue;
+        }
+        if seen_zdbk.insert(session.course_name.clone()) {
+            report.only_in_zdbk.push(UnmatchedCourse {
+                source: "zdbk".to_string(),
+                course_code: if session.xkkh.is_empty() {
+                    session.course_id.clone()
+                } else {
+                    session.xkkh.clone()
+                },
+                course_name: session.course_name.clone(),
+            });
+        }
+    }
+
+    for (index, course) in courses.iter().enumerate() {
+        if matched_courses.contains(&index) {
+            continue;
+        }
+        report.only_in_courses.push(UnmatchedCourse {
+            source: "courses".to_string(),
+            course_code: read_string(course, &["course_code"]),
+            course_name: read_string(course, &["display_name", "name", "second_name"]),
+        });
+    }
+
+    report.learning_entries = entries;
+    (merged, report)
+}
+
+fn read_instructors(course: &Value) -> String {
+    course
+        .get("instructors")
+        .and_then(Value::as_array)
+        .and_then(|items| items.first())
+        .and_then(|item| item.get("name"))
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn join_weeks(weeks: &[u8]) -> String {
+    weeks
+        .iter()
+        .map(u8::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{merge_learning_schedules, parse_learning_schedules};
+    use crate::term::{descriptor_from_parts, normalize_timetable_sessions};
+    use serde_json::json;
+
+    #[test]
+    fn schedule_text_and_objects_are_parsed() {
+        let courses = vec![
+            json!({
+                "id": 1,
+                "course_code": "(2024-2025-1)-211G0001-01",
+                "name": "程序设计",
+                "classroom_schedule": "周一第1,2节{第1-8周} 紫金港东1A-101；星期三第3-5节{单周} 紫金港西2-208",
+            }),
+            json!({
+                "id": 2,
+                "course_code": "MATH1001",
+                "name": "微积分",
+                "classroom_schedule": [{ "weekday": 2, "start_lesson": 6, "end_lesson": 8, "classroom": "东2-202" }],
+            }),
+        ];
+        let entries = parse_learning_schedules(&courses);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].location, "紫金港东1A-101");
+        assert_eq!(entries[0].week_numbers, (1..=8).collect::<Vec<_>>());
+        assert_eq!((entries[1].day_of_week, entries[1].end_period), (3, 5));
+        assert_eq!(entries[2].location, "东2-202");
+    }
+
+    #[test]
+    fn merge_fills_blanks_and_reports_unmatched_courses() {
+        let term = descriptor_from_parts("2024", "1");
+        let sessions = normalize_timetable_sessions(
+            &term,
+            &[
+                json!({
+                    "xkkh": "(2024-2025-1)-211G0001-01",
+                    "kcdm": "211G0001",
+                    "kcmc": "程序设计",
+                    "xqj": "1",
+                    "jcs": "1-2",
+                    "zcs": "1-8",
+                    "xxq": "秋",
+                }),
+                json!({
+                    "xkkh": "(2024-2025-1)-061B0001-01",
+                    "kcdm": "061B0001",
+                    "kcmc": "线性代数",
+                    "xqj": "4",
+                    "jcs": "1-2",
+                    "zcs": "1-8",
+                    "xxq": "秋",
+                    "cdmc": "东1A-201",
+                }),
+            ],
+        );
+        let courses = vec![
+            json!({
+                "id": 1,
+                "course_code": "(2024-2025-1)-211G0001-01",
+                "name": "程序设计",
+                "instructors": [{ "name": "张老师" }],
+                "classroom_schedule": "周一第1,2节 紫金港东1A-101",
+            }),
+            json!({ "id": 3, "course_code": "SPORT", "name": "体育", "classroom_schedule": "" }),
+        ];
+
+        let (merged, report) = merge_learning_schedules(&sessions, &courses);
+        let programming = merged
+            .iter()
+            .find(|session| session.course_name == "程序设计")
+            .unwrap();
+        assert_eq!(programming.location, "紫金港东1A-101");
+        assert_eq!(programming.teacher, "张老师");
+        assert_eq!(report.only_in_zdbk[0].course_name, "线性代数");
+        assert_eq!(report.only_in_courses[0].course_name, "体育");
+    }
+}
@@ -0,0 +1,284 @@
+use crate::json_store;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::AppHandle;
+
+const HISTORY_FILE: &str = "todo_history.json";
+const MAX_DELTAS: usize = 100;
+/// Emitted with a [`TodoDelta`] whenever a sync changes the todo list.
+pub const TODOS_CHANGED_EVENT: &str = "todos-changed";
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TodoSnapshot {
+    pub id: String,
+    pub title: String,
+    pub course_name: String,
+    #[serde(default)]
+    pub course_id: Option<i64>,
+    #[serde(rename = "type", default)]
+    pub kind: String,
+    pub end_time: String,
+    #[serde(default)]
+    pub completed: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadlineChange {
+    pub todo: TodoSnapshot,
+    pub previous_end_time: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TodoDelta {
+    pub synced_at: i64,
+    pub added: Vec<TodoSnapshot>,
+    pub deadline_moved: Vec<DeadlineChange>,
+    /// Marked done, or gone from the list before its deadline.
+    pub completed: Vec<TodoSnapshot>,
+    /// Gone from the list after its deadline passed.
+    pub removed: Vec<TodoSnapshot>,
+}
+
+impl TodoDelta {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.deadline_moved.is_empty()
+            && self.completed.is_empty()
+            && self.removed.is_empty()
+    }
+}
+
+/// Todo sources that could not be fetched during a sync. Their known todos
+/// are carried over unchanged instead of being reported as gone.
+#[derive(Clone, Debug, Default)]
+pub struct FailedSources {
+    /// Todo types that failed for every course.
+    pub kinds: Vec<&'static str>,
+    /// `(todo type, course id)` pairs that failed.
+    pub courses: Vec<(&'static str, i64)>,
+}
+
+impl FailedSources {
+    fn covers(&self, todo: &TodoSnapshot) -> bool {
+        self.kinds.contains(&todo.kind.as_str())
+            || todo.course_id.is_some_and(|course_id| {
+                self.courses
+                    .iter()
+                    .any(|(kind, failed)| *kind == todo.kind && *failed == course_id)
+            })
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TodoHistory {
+    /// Todos as of the last sync.
+    pub known: Vec<TodoSnapshot>,
+    /// Non-empty deltas, newest first.
+    pub deltas: Vec<TodoDelta>,
+    pub last_synced_at: Option<i64>,
+}
+
+pub fn load_history(app: &AppHandle) -> TodoHistory {
+    json_store::load(app, HISTORY_FILE)
+}
+
+fn store_history(app: &AppHandle, history: &TodoHistory) -> Result<(), String> {
+    json_store::store(app, HISTORY_FILE, history, "待办记录")
+}
+
+/// Reads a normalized todo item, as produced for the todos payload.
+pub fn snapshot(todo: &Value) -> Option<TodoSnapshot> {
+    let text = |key: &str| {
+        todo.get(key)
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string()
+    };
+    let status = text("status").to_ascii_lowercase();
+    Some(TodoSnapshot {
+        id: todo.get("id").and_then(Value::as_str)?.to_string(),
+        title: text("title"),
+        course_name: text("courseName"),
+        course_id: todo
+            .get("raw")
+            .and_then(|raw| raw.get("course_id"))
+            .and_then(Value::as_i64),
+        kind: text("type"),
+        end_time: text("endTime"),
+        completed: matches!(status.as_str(), "completed" | "submitted" | "done"),
+    })
+}
+
+fn passed(end_time: &str, now: DateTime<Local>) -> bool {
+    DateTime::parse_from_rfc3339(end_time).is_ok_and(|end| end < now)
+}
+
+/// Known todos missing from this sync only because their source failed.
+fn carried_over(
+    previous: &[TodoSnapshot],
+    current: &[TodoSnapshot],
+    failed: &FailedSources,
+) -> Vec<TodoSnapshot> {
+    previous
+        .iter()
+        .filter(|known| failed.covers(known) && !current.iter().any(|todo| todo.id == known.id))
+        .cloned()
+        .collect()
+}
+
+pub fn diff(
+    previous: &[TodoSnapshot],
+    current: &[TodoSnapshot],
+    failed: &FailedSources,
+    now: DateTime<Local>,
+) -> TodoDelta {
+    let mut delta = TodoDelta {
+        synced_at: now.timestamp(),
+        ..TodoDelta::default()
+    };
+    for todo in current {
+        match previous.iter().find(|known| known.id == todo.id) {
+            None => delta.added.push(todo.clone()),
+            Some(known) => {
+                if todo.completed && !known.completed {
+                    delta.completed.push(todo.clone());
+                } else if !todo.end_time.is_empty() && todo.end_time != known.end_time {
+                    delta.deadline_moved.push(DeadlineChange {
+                        todo: todo.clone(),
+                        previous_end_time: known.end_time.clone(),
+                    });
+                }
+            }
+        }
+    }
+    for known in previous {
+        if known.completed || failed.covers(known) || current.iter().any(|todo| todo.id == known.id)
+        {
+            continue;
+        }
+        if passed(&known.end_time, now) {
+            delta.removed.push(known.clone());
+        } else {
+            delta.completed.push(known.clone());
+        }
+    }
+    delta
+}
+
+/// Compares the fetched todos with the last sync and stores the result. The
+/// first sync only records a baseline and returns an empty delta. Todos of
+/// `failed` sources stay as they were known until a sync reaches them.
+pub fn record(
+    app: &AppHandle,
+    todo_list: &[Value],
+    failed: &FailedSources,
+) -> Result<TodoDelta, String> {
+    let mut history = load_history(app);
+    let now = Local::now();
+    let mut current = todo_list.iter().filter_map(snapshot).collect::<Vec<_>>();
+    let delta = if history.last_synced_at.is_some() {
+        diff(&history.known, &current, failed, now)
+    } else {
+        TodoDelta {
+            synced_at: now.timestamp(),
+            ..TodoDelta::default()
+        }
+    };
+
+    if !delta.is_empty() {
+        history.deltas.insert(0, delta.clone());
+        history.deltas.truncate(MAX_DELTAS);
+    }
+    current.extend(carried_over(&history.known, &current, failed));
+    history.known = current;
+    history.last_synced_at = Some(now.timestamp());
+    store_history(app, &history)?;
+    Ok(delta)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{carried_over, diff, snapshot, FailedSources};
+    use chrono::{Local, TimeZone};
+    use serde_json::json;
+
+    #[test]
+    fn todo_diff_reports_added_moved_completed_and_removed() {
+        let todo = |id: &str, end: &str, status: &str| {
+            snapshot(&json!({ "id": id, "title": id, "courseName": "操作系统", "type": "homework", "endTime": end, "status": status }))
+                .unwrap()
+        };
+        let previous = [
+            todo("1", "2024-05-10T15:59:00Z", "pending"),
+            todo("2", "2024-05-10T15:59:00Z", "pending"),
+            todo("3", "2024-05-20T15:59:00Z", "pending"),
+            todo("4", "2024-04-20T15:59:00Z", "pending"),
+            todo("5", "2024-05-20T15:59:00Z", "pending"),
+        ];
+        let current = [
+            todo("1", "2024-05-10T15:59:00Z", "pending"),
+            todo("2", "2024-05-17T15:59:00Z", "pending"),
+            todo("5", "2024-05-20T15:59:00Z", "submitted"),
+            todo("6", "2024-05-30T15:59:00Z", "pending"),
+        ];
+        let now = Local.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+        let delta = diff(&previous, &current, &FailedSources::default(), now);
+
+        assert_eq!(delta.added[0].id, "6");
+        assert_eq!(delta.deadline_moved.len(), 1);
+        assert_eq!(
+            delta.deadline_moved[0].previous_end_time,
+            "2024-05-10T15:59:00Z"
+        );
+        let completed = delta
+            .completed
+            .iter()
+            .map(|todo| todo.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(completed, ["5", "3"]);
+        assert_eq!(delta.removed[0].id, "4");
+        assert!(diff(&current, &current, &FailedSources::default(), now).is_empty());
+    }
+
+    #[test]
+    fn todos_of_failed_sources_are_carried_over_unchanged() {
+        let todo = |id: &str, kind: &str, course_id: i64| {
+            snapshot(&json!({ "id": id, "title": id, "courseName": "操作系统", "type": kind, "endTime": "2024-05-10T15:59:00Z", "status": "pending", "raw": { "course_id": course_id } }))
+                .unwrap()
+        };
+        let previous = [
+            todo("1", "online_quiz", 5),
+            todo("2", "online_video", 5),
+            todo("3", "online_video", 6),
+            todo("4", "homework", 5),
+        ];
+        let now = Local.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+
+        let failed = FailedSources {
+            kinds: Vec::new(),
+            courses: vec![("online_video", 5)],
+        };
+        let delta = diff(&previous, &[], &failed, now);
+        let completed = delta
+            .completed
+            .iter()
+            .map(|todo| todo.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(completed, ["1", "3", "4"]);
+        let carried = carried_over(&previous, &[], &failed);
+        assert_eq!(carried, [previous[1].clone()]);
+
+        let failed = FailedSources {
+            kinds: vec!["online_quiz", "online_video"],
+            courses: Vec::new(),
+        };
+        let delta = diff(&previous, &[], &failed, now);
+        assert_eq!(delta.completed, [previous[3].clone()]);
+        assert_eq!(carried_over(&previous, &previous[..1], &failed).len(), 2);
+    }
+}
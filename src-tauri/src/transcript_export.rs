@@ -0,0 +1,473 @@
+use crate::gpa::{
+    compute_gpa_by_policy, extract_semester_name, select_counted_grades, semester_rank,
+    RetakeOverride, RetakePolicy, RetakeSelection,
+};
+use crate::gpa_scale::GpaScale;
+use crate::json_store;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use tauri::AppHandle;
+
+const TRANSLATION_FILE: &str = "transcript_translations.json";
+/// zdbk has used several names for the English course title over the years.
+const ENGLISH_NAME_FIELDS: [&str; 4] = ["kcywmc", "ywkcmc", "kcmcyw", "englishName"];
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum TranscriptFormat {
+    Csv,
+    #[default]
+    Json,
+    Html,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptExportInput {
+    pub grades: Vec<Value>,
+    pub format: Option<TranscriptFormat>,
+    pub scale_id: Option<String>,
+    pub retake_policy: Option<String>,
+    pub retake_overrides: Option<HashMap<String, RetakeOverride>>,
+    pub course_id_mappings: Option<HashMap<String, String>>,
+    pub student_name: Option<String>,
+    pub student_id: Option<String>,
+    pub program: Option<String>,
+    pub output_path: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptTranslationsInput {
+    pub entries: BTreeMap<String, String>,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptCourse {
+    pub code: String,
+    pub title: String,
+    pub title_zh: String,
+    pub translated: bool,
+    pub credits: f64,
+    pub grade: String,
+    pub points: f64,
+    pub counted: bool,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptTerm {
+    pub term: String,
+    pub label: String,
+    pub courses: Vec<TranscriptCourse>,
+    pub term_gpa: f64,
+    pub term_credits: f64,
+    pub cumulative_gpa: f64,
+    pub cumulative_credits: f64,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EnglishTranscript {
+    pub student_name: String,
+    pub student_id: String,
+    pub program: String,
+    pub scale_id: String,
+    pub scale_name: String,
+    pub max_points: f64,
+    pub retake_policy: RetakePolicy,
+    pub terms: Vec<TranscriptTerm>,
+    pub cumulative_gpa: f64,
+    pub total_credits: f64,
+    /// Chinese course names that still need an entry in the translation table.
+    pub untranslated: Vec<String>,
+}
+
+/// The user-editable table, keyed by kcdm or by the Chinese course name.
+pub fn load_translations(app: &AppHandle) -> BTreeMap<String, String> {
+    json_store::load(app, TRANSLATION_FILE)
+}
+
+pub fn save_translations(
+    app: &AppHandle,
+    input: TranscriptTranslationsInput,
+) -> Result<BTreeMap<String, String>, String> {
+    let entries = input
+        .entries
+        .into_iter()
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .filter(|(key, value)| !key.is_empty() && !value.is_empty())
+        .collect::<BTreeMap<_, _>>();
+    json_store::store(app, TRANSLATION_FILE, &entries, "翻译表")?;
+    Ok(entries)
+}
+
+fn read_str<'a>(grade: &'a Value, key: &str) -> &'a str {
+    grade
+        .get(key)
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .trim()
+}
+
+pub fn english_term_label(name: &str) -> String {
+    let parts = name.split('-').collect::<Vec<_>>();
+    match parts.as_slice() {
+        [start, end, "1"] => format!("{start}-{end} Fall & Winter"),
+        [start, end, "2"] => format!("{start}-{end} Spring & Summer"),
+        _ => "Transferred / Recognized Credits".to_string(),
+    }
+}
+
+pub fn english_grade(cj: &str) -> String {
+    match cj.trim() {
+        "优秀" => "Excellent",
+        "良好" => "Good",
+        "中等" => "Fair",
+        "及格" | "合格" => "Pass",
+        "不及格" | "不合格" => "Fail",
+        "缺考" => "Absent",
+        "免修" | "免考" => "Exempted",
+        "弃修" => "Withdrawn",
+        "缓考" => "Deferred",
+        "待录" => "Pending",
+        "无效" => "Void",
+        other => other,
+    }
+    .to_string()
+}
+
+pub fn build_transcript(
+    input: &TranscriptExportInput,
+    grades: &[Value],
+    scale: &GpaScale,
+    selection: &RetakeSelection,
+    translations: &BTreeMap<String, String>,
+) -> EnglishTranscript {
+    let summary = compute_gpa_by_policy(grades, &HashSet::new(), selection, &[]);
+    let mut counted = select_counted_grades(grades, selection)
+        .into_iter()
+        .map(|grade| grade.index)
+        .collect::<HashSet<_>>();
+    // An averaged retake only points at its latest attempt; every averaged
+    // attempt still counts.
+    let chosen = summary
+        .retake_choices
+        .iter()
+        .flat_map(|choice| choice.chosen.iter().cloned())
+        .collect::<HashSet<_>>();
+    for (index, grade) in grades.iter().enumerate() {
+        if chosen.contains(read_str(grade, "xkkh")) {
+            counted.insert(index);
+        }
+    }
+
+    let mut untranslated = Vec::new();
+    let mut by_term = BTreeMap::<(i64, String), Vec<(usize, &Value)>>::new();
+    for (index, grade) in grades.iter().enumerate() {
+        let name = grade
+            .get("semesterName")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .or_else(|| extract_semester_name(grade))
+            .unwrap_or_else(|| "其他/认定".to_string());
+        by_term
+            .entry((semester_rank(&name), name))
+            .or_default()
+            .push((index, grade));
+    }
+
+    let scale_gpa = |rows: &[Value]| {
+        let summary = compute_gpa_by_policy(
+            rows,
+            &HashSet::new(),
+            selection,
+            std::slice::from_ref(scale),
+        );
+        (
+            summary
+                .scales
+                .first()
+                .map(|item| item.gpa)
+                .unwrap_or_default(),
+            summary.total_credits,
+        )
+    };
+
+    let mut so_far = Vec::<Value>::new();
+    let mut terms = Vec::new();
+    for ((_, name), rows) in by_term {
+        let term_grades = rows
+            .iter()
+            .map(|(_, grade)| (*grade).clone())
+            .collect::<Vec<_>>();
+        so_far.extend(term_grades.iter().cloned());
+        let (term_gpa, term_credits) = scale_gpa(&term_grades);
+        let (cumulative_gpa, cumulative_credits) = scale_gpa(&so_far);
+
+        let courses = rows
+            .iter()
+            .map(|(index, grade)| {
+                let title_zh = read_str(grade, "kcmc").to_string();
+                let code = read_str(grade, "kcdm").to_string();
+                let english = ENGLISH_NAME_FIELDS
+                    .iter()
+                    .map(|key| read_str(grade, key))
+                    .find(|value| !value.is_empty())
+                    .map(str::to_string)
+                    .or_else(|| translations.get(&code).cloned())
+                    .or_else(|| translations.get(&title_zh).cloned());
+                if english.is_none() && !untranslated.contains(&title_zh) {
+                    untranslated.push(title_zh.clone());
+                }
+                let cj = read_str(grade, "cj");
+                TranscriptCourse {
+                    translated: english.is_some(),
+                    title: english.unwrap_or_else(|| title_zh.clone()),
+                    title_zh,
+                    code,
+                    credits: grade
+                        .get("credit")
+                        .and_then(Value::as_f64)
+                        .unwrap_or_default(),
+                    grade: english_grade(cj),
+                    points: scale.points_for(
+                        cj,
+                        grade
+                            .get("hundredPoint")
+                            .and_then(Value::as_f64)
+                            .unwrap_or_default(),
+                        grade
+                            .get("fivePoint")
+                            .and_then(Value::as_f64)
+                            .unwrap_or_default(),
+                    ),
+                    counted: counted.contains(index),
+                }
+            })
+            .collect();
+
+        terms.push(TranscriptTerm {
+            label: english_term_label(&name),
+            term: name,
+            courses,
+            term_gpa,
+            term_credits,
+            cumulative_gpa,
+            cumulative_credits,
+        });
+    }
+
+    let (cumulative_gpa, total_credits) = scale_gpa(grades);
+    EnglishTranscript {
+        student_name: input.student_name.clone().unwrap_or_default(),
+        student_id: input.student_id.clone().unwrap_or_default(),
+        program: input.program.clone().unwrap_or_default(),
+        scale_id: scale.id.clone(),
+        scale_name: scale.name.clone(),
+        max_points: scale.max_points,
+        retake_policy: selection.policy,
+        terms,
+        cumulative_gpa,
+        total_credits,
+        untranslated,
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+pub fn render_csv(transcript: &EnglishTranscript) -> String {
+    let mut lines =
+        vec!["Term,Course Code,Course Title,Credits,Grade,Grade Points,Counted".to_string()];
+    for term in &transcript.terms {
+        for course in &term.courses {
+            lines.push(
+                [
+                    csv_field(&term.label),
+                    csv_field(&course.code),
+                    csv_field(&course.title),
+                    format!("{:.1}", course.credits),
+                    csv_field(&course.grade),
+                    format!("{:.2}", course.points),
+                    if course.counted { "Yes" } else { "No" }.to_string(),
+                ]
+                .join(","),
+            );
+        }
+        lines.push(format!(
+            "{},,Term GPA,{:.1},,{:.2},",
+            csv_field(&term.label),
+            term.term_credits,
+            term.term_gpa
+        ));
+    }
+    lines.push(format!(
+        ",,Cumulative GPA ({}),{:.1},,{:.2},",
+        csv_field(&transcript.scale_name),
+        transcript.total_credits,
+        transcript.cumulative_gpa
+    ));
+    lines.join("\n") + "\n"
+}
+
+fn html_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+pub fn render_html(transcript: &EnglishTranscript) -> String {
+    let mut body = String::new();
+    for term in &transcript.terms {
+        body.push_str(&format!(
+            "<section><h2>{}</h2><table><thead><tr><th>Code</th><th>Course Title</th><th>Credits</th><th>Grade</th><th>Points</th></tr></thead><tbody>",
+            html_escape(&term.label)
+        ));
+        for course in &term.courses {
+            body.push_str(&format!(
+                "<tr{}><td>{}</td><td>{}</td><td>{:.1}</td><td>{}</td><td>{:.2}</td></tr>",
+                if course.counted {
+                    ""
+                } else {
+                    " class=\"excluded\""
+                },
+                html_escape(&course.code),
+                html_escape(&course.title),
+                course.credits,
+                html_escape(&course.grade),
+                course.points
+            ));
+        }
+        body.push_str(&format!(
+            "</tbody></table><p class=\"summary\">Term GPA {:.2} · Credits {:.1} · Cumulative GPA {:.2} · Cumulative Credits {:.1}</p></section>",
+            term.term_gpa, term.term_credits, term.cumulative_gpa, term.cumulative_credits
+        ));
+    }
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>Academic Transcript</title>
+<style>
+body {{ font-family: "Times New Roman", serif; margin: 24px; color: #111; }}
+h1 {{ text-align: center; margin-bottom: 4px; }}
+.meta {{ text-align: center; margin-bottom: 16px; }}
+h2 {{ font-size: 15px; margin: 18px 0 6px; }}
+table {{ width: 100%; border-collapse: collapse; font-size: 12px; }}
+th, td {{ border: 1px solid #999; padding: 3px 6px; text-align: left; }}
+tr.excluded td {{ color: #888; text-decoration: line-through; }}
+.summary {{ font-size: 12px; margin: 4px 0 0; }}
+section {{ page-break-inside: avoid; }}
+@media print {{ body {{ margin: 0; }} }}
+</style>
+</head>
+<body>
+<h1>Academic Transcript</h1>
+<div class="meta">{name}{id}{program}<br>Grade points: {scale} (max {max:.1}) · Cumulative GPA {gpa:.2} · Total Credits {credits:.1}</div>
+{body}
+<p class="summary">Struck-through attempts are not counted under the selected retake policy.</p>
+</body>
+</html>
+"#,
+        name = html_escape(&transcript.student_name),
+        id = if transcript.student_id.is_empty() {
+            String::new()
+        } else {
+            format!(" · Student ID {}", html_escape(&transcript.student_id))
+        },
+        program = if transcript.program.is_empty() {
+            String::new()
+        } else {
+            format!(" · {}", html_escape(&transcript.program))
+        },
+        scale = html_escape(&transcript.scale_name),
+        max = transcript.max_points,
+        gpa = transcript.cumulative_gpa,
+        credits = transcript.total_credits,
+        body = body,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{build_transcript, render_csv, TranscriptExportInput};
+    use crate::gpa::{enrich_grade, RetakePolicy};
+    use crate::gpa_scale::GpaScale;
+    use crate::preset_store::presets;
+    use serde_json::json;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn transcript_translates_names_and_marks_retakes() {
+        let grades = vec![
+            enrich_grade(&json!({
+                "cj": "70", "xf": 2.0, "kcdm": "211G0001", "kcmc": "程序设计",
+                "xkkh": "(2023-2024-1)-211G0001-0001-1",
+            })),
+            enrich_grade(&json!({
+                "cj": "90", "xf": 2.0, "kcdm": "211G0001", "kcmc": "程序设计",
+                "xkkh": "(2023-2024-2)-211G0001-0001-1",
+            })),
+            enrich_grade(&json!({
+                "cj": "优秀", "xf": 1.0, "kcdm": "061B0001", "kcmc": "线性代数",
+                "kcywmc": "Linear Algebra", "xkkh": "(2023-2024-2)-061B0001-0001-1",
+            })),
+        ];
+        let input = TranscriptExportInput {
+            grades: Vec::new(),
+            format: None,
+            scale_id: None,
+            retake_policy: None,
+            retake_overrides: None,
+            course_id_mappings: None,
+            student_name: Some("Zhang San".to_string()),
+            student_id: None,
+            program: None,
+            output_path: None,
+        };
+        let scale = presets::<GpaScale>()
+            .into_iter()
+            .find(|scale| scale.id == "wes-4.0")
+            .unwrap();
+        let mut translations = BTreeMap::new();
+        translations.insert(
+            "211G0001".to_string(),
+            "Programming Fundamentals".to_string(),
+        );
+
+        let transcript = build_transcript(
+            &input,
+            &grades,
+            &scale,
+            &RetakePolicy::Highest.into(),
+            &translations,
+        );
+        assert_eq!(transcript.terms.len(), 2);
+        assert_eq!(transcript.terms[0].label, "2023-2024 Fall & Winter");
+        assert!(!transcript.terms[0].courses[0].counted);
+        assert_eq!(
+            transcript.terms[1].courses[0].title,
+            "Programming Fundamentals"
+        );
+        assert_eq!(transcript.terms[1].courses[1].title, "Linear Algebra");
+        assert_eq!(transcript.terms[1].courses[1].grade, "Excellent");
+        assert!(transcript.untranslated.is_empty());
+        assert_eq!(transcript.cumulative_gpa, 4.0);
+
+        let csv = render_csv(&transcript);
+        assert!(csv.starts_with("Term,Course Code"));
+        assert!(csv.contains("Linear Algebra,1.0,Excellent,4.00,Yes"));
+    }
+}
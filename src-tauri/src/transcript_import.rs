@@ -0,0 +1,450 @@
+use crate::gpa::{interpret_grade_text, GradeText};
+use calamine::{Reader, Xlsx};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+
+/// Header synonyms seen across zdbk 成绩单 exports, per grade field.
+const COLUMN_ALIASES: [(&str, &[&str]); 10] = [
+    ("xkkh", &["选课课号", "选课号", "教学班"]),
+    ("kcdm", &["课程代码", "课程号", "课程编号"]),
+    ("kcmc", &["课程名称", "课程名", "课程"]),
+    ("kcywmc", &["英文名称", "课程英文名称", "英文课程名"]),
+    ("cj", &["成绩", "总评成绩", "最终成绩", "总评"]),
+    ("xf", &["学分"]),
+    ("jd", &["绩点"]),
+    ("xnm", &["学年"]),
+    ("xqm", &["学期"]),
+    ("kcxzmc", &["课程性质", "课程类别", "课程属性"]),
+];
+/// Grades zdbk prints instead of a score that the GPA table does not list.
+const EXTRA_GRADE_LABELS: [&str; 2] = ["免修", "免考"];
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ImportFormat {
+    Xlsx,
+    Csv,
+    Pdf,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptImportInput {
+    pub path: String,
+    pub format: Option<ImportFormat>,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscriptImport {
+    pub format: ImportFormat,
+    pub grades: Vec<Value>,
+    /// Source lines or rows that looked like courses but could not be mapped.
+    pub skipped: Vec<String>,
+}
+
+/// Cell text of the first worksheet.
+pub fn xlsx_rows(data: &[u8]) -> Result<Vec<Vec<String>>, String> {
+    let mut workbook: Xlsx<_> = calamine::open_workbook_from_rs(Cursor::new(data))
+        .map_err(|error| format!("不是有效的 xlsx 文件: {error}"))?;
+    let sheet = workbook
+        .worksheet_range_at(0)
+        .ok_or("xlsx 中没有工作表")?
+        .map_err(|error| format!("读取 xlsx 工作表失败: {error}"))?;
+    Ok(sheet
+        .rows()
+        .map(|row| {
+            row.iter()
+                .map(|cell| cell.to_string().trim().to_string())
+                .collect()
+        })
+        .collect())
+}
+
+/// Text lines of every page, in reading order.
+pub fn pdf_lines(data: &[u8]) -> Result<Vec<String>, String> {
+    // pdf-extract panics on some malformed files instead of returning an error.
+    let text = std::panic::catch_unwind(|| pdf_extract::extract_text_from_mem(data))
+        .map_err(|_| "PDF 文件损坏，无法读取".to_string())?
+        .map_err(|error| format!("读取 PDF 失败: {error}"))?;
+    Ok(text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+pub fn csv_rows(text: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(ch) = chars.next() {
+        match (ch, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            ('"', _) => quoted = !quoted,
+            (',', false) => row.push(std::mem::take(&mut field).trim().to_string()),
+            ('\n', false) => {
+                row.push(std::mem::take(&mut field).trim().to_string());
+                rows.push(std::mem::take(&mut row));
+            }
+            ('\r', false) => {}
+            _ => field.push(ch),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field.trim().to_string());
+        rows.push(row);
+    }
+    rows
+}
+
+fn is_grade_label(token: &str) -> bool {
+    matches!(interpret_grade_text(token), GradeText::Known(_))
+        || EXTRA_GRADE_LABELS.contains(&token)
+}
+
+/// "2023-2024" / "2023-2024学年" / "2023" → "2023".
+fn normalize_year(text: &str) -> Option<String> {
+    Regex::new(r"(\d{4})")
+        .unwrap()
+        .captures(text)
+        .map(|caps| caps[1].to_string())
+}
+
+/// zdbk writes the term as 秋冬/春夏, single seasons, or the xqm code itself.
+fn normalize_term(text: &str) -> Option<&'static str> {
+    let text = text.trim();
+    if ["秋", "冬", "第一", "上"]
+        .iter()
+        .any(|key| text.contains(key))
+    {
+        return Some("1");
+    }
+    if ["春", "夏", "第二", "下"]
+        .iter()
+        .any(|key| text.contains(key))
+    {
+        return Some("2");
+    }
+    match text {
+        "1" | "3" => Some("1"),
+        "2" | "12" | "4" | "16" => Some("2"),
+        _ => None,
+    }
+}
+
+fn finish_grade(mut grade: Map<String, Value>) -> Value {
+    if let Some(year) = grade
+        .get("xnm")
+        .and_then(Value::as_str)
+        .and_then(normalize_year)
+    {
+        grade.insert("xnm".to_string(), json!(year));
+    }
+    if let Some(term) = grade
+        .get("xqm")
+        .and_then(Value::as_str)
+        .and_then(normalize_term)
+    {
+        grade.insert("xqm".to_string(), json!(term));
+    }
+    // Without a 选课课号, synthesise one so term detection and retake grouping
+    // behave as they do for fetched rows.
+    let has_xkkh = grade
+        .get("xkkh")
+        .and_then(Value::as_str)
+        .is_some_and(|value| !value.is_empty());
+    if !has_xkkh {
+        let year = grade.get("xnm").and_then(Value::as_str).unwrap_or_default();
+        let term = grade.get("xqm").and_then(Value::as_str).unwrap_or_default();
+        let code = grade
+            .get("kcdm")
+            .and_then(Value::as_str)
+            .filter(|value| !value.is_empty())
+            .or_else(|| grade.get("kcmc").and_then(Value::as_str))
+            .unwrap_or_default()
+            .to_string();
+        let xkkh = match year.parse::<u32>() {
+            Ok(start) if !term.is_empty() => {
+                format!("({start}-{}-{term})-{code}-import", start + 1)
+            }
+            _ => format!("{code}-import"),
+        };
+        grade.insert("xkkh".to_string(), json!(xkkh));
+    }
+    grade.insert("source".to_string(), json!("import"));
+    Value::Object(grade)
+}
+
+/// Map a header row plus data rows onto zdbk grade fields.
+pub fn rows_to_grades(rows: &[Vec<String>]) -> Result<(Vec<Value>, Vec<String>), String> {
+    let (header_index, columns) = rows
+        .iter()
+        .enumerate()
+        .take(20)
+        .find_map(|(index, row)| {
+            let mut columns = HashMap::<&str, usize>::new();
+            for (field, aliases) in COLUMN_ALIASES {
+                // Exact header matches win over partial ones (课程 vs 课程名称).
+                let position = row
+                    .iter()
+                    .position(|cell| aliases.contains(&cell.trim()))
+                    .or_else(|| {
+                        row.iter().position(|cell| {
+                            aliases
+                                .iter()
+                                .any(|alias| alias.chars().count() > 2 && cell.contains(alias))
+                        })
+                    });
+                if let Some(position) = position {
+                    columns.entry(field).or_insert(position);
+                }
+            }
+            (columns.contains_key("kcmc") && columns.contains_key("cj")).then_some((index, columns))
+        })
+        .ok_or("没有找到包含「课程名称」和「成绩」的表头")?;
+
+    let mut grades = Vec::new();
+    let mut skipped = Vec::new();
+    for row in &rows[header_index + 1..] {
+        if row.iter().all(|cell| cell.trim().is_empty()) {
+            continue;
+        }
+        let mut grade = Map::new();
+        for (field, position) in &columns {
+            if let Some(cell) = row.get(*position).map(|cell| cell.trim()) {
+                if !cell.is_empty() {
+                    grade.insert(field.to_string(), json!(cell));
+                }
+            }
+        }
+        let named = grade.get("kcmc").is_some();
+        let graded = grade.get("cj").is_some();
+        if named && graded {
+            grades.push(finish_grade(grade));
+        } else {
+            skipped.push(row.join(" ").trim().to_string());
+        }
+    }
+    Ok((grades, skipped))
+}
+
+/// Best-effort parse of PDF text lines. Term headers like
+/// 「2023-2024学年 秋冬学期」 set the term for the rows that follow.
+pub fn lines_to_grades(lines: &[String]) -> (Vec<Value>, Vec<String>) {
+    let term_re =
+        Regex::new(r"(\d{4})\s*[-－—~至]\s*\d{4}\s*学年\s*(秋冬|春夏|秋|冬|春|夏|第[一二]|[12])")
+            .unwrap();
+    let xkkh_re = Regex::new(r"\(\d{4}-\d{4}-\d+\)-[0-9A-Za-z.\-]+").unwrap();
+    let code_re = Regex::new(r"^[0-9A-Z]{6,12}$").unwrap();
+    let number_re = Regex::new(r"^\d{1,3}(\.\d+)?$").unwrap();
+    let cjk = |text: &str| {
+        text.chars()
+            .any(|ch| ('\u{4e00}'..='\u{9fff}').contains(&ch))
+    };
+
+    let mut term: Option<(String, String)> = None;
+    let mut grades = Vec::new();
+    let mut skipped = Vec::new();
+    for line in lines {
+        if let Some(caps) = term_re.captures(line) {
+            term = normalize_term(&caps[2]).map(|code| (caps[1].to_string(), code.to_string()));
+        }
+
+        let tokens = line.split_whitespace().collect::<Vec<_>>();
+        let xkkh = tokens.iter().find(|token| xkkh_re.is_match(token));
+        let kcdm = tokens
+            .iter()
+            .find(|token| code_re.is_match(token) && token.chars().any(|ch| ch.is_ascii_digit()));
+        if xkkh.is_none() && kcdm.is_none() {
+            continue;
+        }
+
+        let name_start = tokens
+            .iter()
+            .position(|token| cjk(token) && !is_grade_label(token));
+        let Some(name_start) = name_start else {
+            skipped.push(line.clone());
+            continue;
+        };
+        let name_end = tokens[name_start..]
+            .iter()
+            .position(|token| number_re.is_match(token) || is_grade_label(token))
+            .map_or(tokens.len(), |offset| name_start + offset);
+        let name = tokens[name_start..name_end].join(" ");
+        let rest = &tokens[name_end..];
+
+        let is_score = |token: &&str, max: f64| {
+            number_re.is_match(token) && token.parse::<f64>().is_ok_and(|value| value <= max)
+        };
+        let Some(credit) = rest
+            .iter()
+            .position(|token| is_score(token, 20.0) && token.parse::<f64>().unwrap_or(0.0) > 0.0)
+        else {
+            skipped.push(line.clone());
+            continue;
+        };
+        let cj = rest
+            .iter()
+            .position(|token| is_grade_label(token))
+            .or_else(|| {
+                rest.iter()
+                    .skip(credit + 1)
+                    .position(|token| is_score(token, 100.0))
+                    .map(|offset| credit + 1 + offset)
+            });
+        let Some(cj) = cj else {
+            skipped.push(line.clone());
+            continue;
+        };
+
+        let mut grade = Map::new();
+        grade.insert("kcmc".to_string(), json!(name));
+        grade.insert("xf".to_string(), json!(rest[credit]));
+        grade.insert("cj".to_string(), json!(rest[cj]));
+        if let Some(jd) = rest.get(cj + 1).filter(|token| is_score(token, 5.0)) {
+            grade.insert("jd".to_string(), json!(jd));
+        }
+        if let Some(xkkh) = xkkh {
+            grade.insert("xkkh".to_string(), json!(xkkh));
+        }
+        if let Some(kcdm) = kcdm {
+            grade.insert("kcdm".to_string(), json!(kcdm));
+        }
+        if let Some((year, code)) = &term {
+            grade.insert("xnm".to_string(), json!(year));
+            grade.insert("xqm".to_string(), json!(code));
+        }
+        grades.push(finish_grade(grade));
+    }
+    (grades, skipped)
+}
+
+pub fn import_file(input: &TranscriptImportInput) -> Result<TranscriptImport, String> {
+    let path = Path::new(input.path.trim());
+    let format = input
+        .format
+        .or_else(|| {
+            match path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .map(str::to_ascii_lowercase)
+            {
+                Some(ext) if ext == "xlsx" => Some(ImportFormat::Xlsx),
+                Some(ext) if ext == "csv" => Some(ImportFormat::Csv),
+                Some(ext) if ext == "pdf" => Some(ImportFormat::Pdf),
+                _ => None,
+            }
+        })
+        .ok_or("只支持 .xlsx、.csv 和 .pdf 成绩单")?;
+    let data = fs::read(path).map_err(|error| format!("读取成绩单失败: {error}"))?;
+
+    let (grades, skipped) = match format {
+        ImportFormat::Xlsx => rows_to_grades(&xlsx_rows(&data)?)?,
+        ImportFormat::Csv => rows_to_grades(&csv_rows(&String::from_utf8_lossy(&data)))?,
+        ImportFormat::Pdf => lines_to_grades(&pdf_lines(&data)?),
+    };
+    if grades.is_empty() {
+        return Err("成绩单中没有识别到任何课程".to_string());
+    }
+    Ok(TranscriptImport {
+        format,
+        grades,
+        skipped,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{csv_rows, lines_to_grades, pdf_lines, rows_to_grades, xlsx_rows};
+    use crate::gpa::extract_semester_name;
+
+    #[test]
+    fn import_maps_columns_and_pdf_lines() {
+        let rows = csv_rows(
+            "浙江大学本科生成绩单\n学年,学期,课程代码,课程名称,学分,成绩,绩点\n2023-2024,秋冬,211G0280,\"程序设计基础\",3.0,92,4.8\n2023-2024,春夏,061B0010,线性代数,2.5,良好,\n,,,,,,\n",
+        );
+        let (grades, skipped) = rows_to_grades(&rows).unwrap();
+        assert!(skipped.is_empty());
+        assert_eq!(grades.len(), 2);
+        assert_eq!(grades[0]["kcmc"], "程序设计基础");
+        assert_eq!(grades[0]["xnm"], "2023");
+        assert_eq!(grades[1]["xqm"], "2");
+        assert_eq!(
+            extract_semester_name(&grades[1]).as_deref(),
+            Some("2023-2024-2")
+        );
+
+        let lines = [
+            "2022-2023学年 春夏学期".to_string(),
+            "(2022-2023-2)-101C0120-0099107-1 101C0120 大学物理（甲）Ⅰ 4.0 87 4.2".to_string(),
+            "821T0090 形势与政策Ⅰ 1.0 合格".to_string(),
+            "211G0280 无成绩的行".to_string(),
+        ]
+        .to_vec();
+        let (grades, skipped) = lines_to_grades(&lines);
+        assert_eq!(grades.len(), 2);
+        assert_eq!(grades[0]["kcmc"], "大学物理（甲）Ⅰ");
+        assert_eq!(grades[0]["jd"], "4.2");
+        assert_eq!(grades[1]["cj"], "合格");
+        assert_eq!(
+            extract_semester_name(&grades[1]).as_deref(),
+            Some("2022-2023-2")
+        );
+        assert_eq!(skipped.len(), 1);
+    }
+
+    #[test]
+    fn xlsx_export_fixture_imports_every_course() {
+        let data = include_bytes!("../fixtures/transcripts/zdbk_export.xlsx");
+        let (grades, skipped) = rows_to_grades(&xlsx_rows(data).unwrap()).unwrap();
+
+        assert_eq!(grades.len(), 4);
+        assert_eq!(grades[0]["xf"], "3");
+        assert_eq!(grades[0]["jd"], "4.8");
+        assert_eq!(grades[1]["kcmc"], "线性代数 & 解析几何");
+        assert_eq!(grades[1]["cj"], "良好");
+        assert_eq!(grades[2]["kcmc"], "形势与政策Ⅰ");
+        assert_eq!(grades[3]["jd"], "4.2");
+        assert_eq!(
+            extract_semester_name(&grades[3]).as_deref(),
+            Some("2023-2024-1")
+        );
+        assert_eq!(skipped, ["备注：本成绩单仅供参考"]);
+
+        assert!(xlsx_rows(&data[..data.len() / 2]).is_err());
+        assert!(xlsx_rows(b"PK\x03\x04 not a workbook").is_err());
+    }
+
+    #[test]
+    fn pdf_transcript_fixture_reads_each_page_with_its_own_fonts() {
+        // Both pages name their font /F1 but map glyph codes differently.
+        let data = include_bytes!("../fixtures/transcripts/zdbk_transcript.pdf");
+        let (grades, skipped) = lines_to_grades(&pdf_lines(data).unwrap());
+
+        assert_eq!(grades.len(), 3);
+        assert_eq!(grades[0]["kcmc"], "大学物理（甲）Ⅰ");
+        assert_eq!(grades[1]["cj"], "合格");
+        assert_eq!(grades[2]["kcmc"], "程序设计基础");
+        assert_eq!(grades[2]["jd"], "4.8");
+        assert_eq!(
+            extract_semester_name(&grades[2]).as_deref(),
+            Some("2023-2024-1")
+        );
+        assert_eq!(skipped, ["211G0290 无成绩的行"]);
+
+        assert!(pdf_lines(&data[..data.len() / 2]).is_err());
+        assert!(pdf_lines(b"%PDF-1.4 truncated").is_err());
+    }
+}
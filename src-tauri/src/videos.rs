@@ -0,0 +1,195 @@
+use crate::courses;
+use crate::json_value::first_str;
+use crate::zjuam::AppState;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+/// Activity type of online videos on 学在浙大, also used as their todo type.
+pub const VIDEO_ACTIVITY_TYPE: &str = "online_video";
+/// Incomplete videos become todos once their deadline is this close.
+const TODO_HORIZON_DAYS: i64 = 7;
+
+#[derive(Clone, Copy, Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum WatchState {
+    Unwatched,
+    Part,
+    Full,
+}
+
+#[derive(Clone, Debug, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct VideoActivity {
+    pub activity_id: i64,
+    pub course_id: i64,
+    pub course_name: String,
+    pub title: String,
+    pub duration_seconds: Option<u64>,
+    pub watch_state: WatchState,
+    /// Watched share in percent; `None` when 学在浙大 only reports `part`.
+    pub completion: Option<f64>,
+    pub completed: bool,
+    pub end_time: Option<String>,
+}
+
+/// Progress of a read record, either a percentage or `full`/`part`.
+fn completeness(read: &Value) -> Option<(WatchState, Option<f64>)> {
+    let value = read
+        .get("completeness")
+        .or_else(|| read.get("completion"))?;
+    let percent = match value.as_str() {
+        Some("full") => return Some((WatchState::Full, Some(100.0))),
+        Some("part") => return Some((WatchState::Part, None)),
+        Some(text) => text.trim().trim_end_matches('%').parse::<f64>().ok()?,
+        None => value.as_f64()?,
+    }
+    .clamp(0.0, 100.0);
+    let state = if percent >= 100.0 {
+        WatchState::Full
+    } else if percent > 0.0 {
+        WatchState::Part
+    } else {
+        WatchState::Unwatched
+    };
+    Some((state, Some(percent)))
+}
+
+/// Video activities of a course joined with the user's read progress.
+pub fn parse_videos(
+    activities: &[Value],
+    reads: &[Value],
+    course_id: i64,
+    course_name: &str,
+) -> Vec<VideoActivity> {
+    activities
+        .iter()
+        .filter(|activity| {
+            activity.get("type").and_then(Value::as_str) == Some(VIDEO_ACTIVITY_TYPE)
+        })
+        .filter_map(|activity| {
+            let activity_id = activity.get("id").and_then(Value::as_i64)?;
+            let read = reads
+                .iter()
+                .find(|read| read.get("activity_id").and_then(Value::as_i64) == Some(activity_id));
+            let (watch_state, completion) = read
+                .and_then(completeness)
+                .unwrap_or((WatchState::Unwatched, Some(0.0)));
+            let data = activity.get("data").unwrap_or(&Value::Null);
+            Some(VideoActivity {
+                activity_id,
+                course_id,
+                course_name: course_name.to_string(),
+                title: first_str(activity, &["title", "name"])?,
+                duration_seconds: ["duration", "video_duration"].iter().find_map(|key| {
+                    activity
+                        .get(*key)
+                        .or_else(|| data.get(*key))
+                        .and_then(Value::as_f64)
+                        .map(|seconds| seconds.round() as u64)
+                }),
+                watch_state,
+                completion,
+                completed: watch_state == WatchState::Full,
+                end_time: first_str(activity, &["end_time", "deadline"]),
+            })
+        })
+        .collect()
+}
+
+/// Todo entries for incomplete videos due within the horizon and not past
+/// their deadline. Videos without a deadline are never urgent.
+pub fn video_todos(videos: &[VideoActivity], now: DateTime<Utc>) -> Vec<Value> {
+    let horizon = now + Duration::days(TODO_HORIZON_DAYS);
+    videos
+        .iter()
+        .filter(|video| !video.completed)
+        .filter(|video| {
+            video
+                .end_time
+                .as_deref()
+                .and_then(|end| DateTime::parse_from_rfc3339(end).ok())
+                .is_some_and(|end| end >= now && end <= horizon)
+        })
+        .map(|video| {
+            json!({
+                "id": video.activity_id,
+                "title": video.title,
+                "type": VIDEO_ACTIVITY_TYPE,
+                "course_id": video.course_id,
+                "course_name": video.course_name,
+                "end_time": video.end_time,
+                "url": format!(
+                    "https://courses.zju.edu.cn/course/{}/learning-activity#/{}",
+                    video.course_id, video.activity_id
+                ),
+                "video": video,
+            })
+        })
+        .collect()
+}
+
+/// Lists videos of every given `(course id, course name)`, grouped by course
+/// id. Courses that fail, including their watch progress, are left out and
+/// reported in the returned warnings, so unknown progress never turns into
+/// todos.
+pub async fn fetch_videos(
+    state: Arc<AppState>,
+    courses: Vec<(i64, String)>,
+) -> (Vec<(i64, Vec<VideoActivity>)>, Vec<String>) {
+    courses::for_each_course(
+        &state,
+        courses,
+        |state, course_id, course_name| async move {
+            let (activities, reads) = tokio::join!(
+                courses::get_course_activities(&state, course_id),
+                courses::get_activity_reads(&state, course_id),
+            );
+            let reads = reads.map_err(|error| format!("读取观看进度失败: {error}"))?;
+            let videos = parse_videos(&activities?, &reads, course_id, &course_name);
+            Ok((course_id, videos))
+        },
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_videos, video_todos, WatchState, VIDEO_ACTIVITY_TYPE};
+    use chrono::{TimeZone, Utc};
+    use serde_json::json;
+
+    #[test]
+    fn incomplete_videos_near_deadline_become_todos() {
+        let activities = [
+            json!({ "id": 1, "type": "online_video", "title": "第一讲", "end_time": "2024-05-05T15:59:00Z", "data": { "duration": 1800.4 } }),
+            json!({ "id": 2, "type": "online_video", "title": "第二讲", "end_time": "2024-05-05T15:59:00Z" }),
+            json!({ "id": 3, "type": "online_video", "title": "第三讲", "end_time": "2024-06-30T15:59:00Z" }),
+            json!({ "id": 4, "type": "material", "title": "讲义" }),
+            json!({ "id": 5, "type": "online_video", "title": "第四讲", "end_time": "2024-05-04T15:59:00Z" }),
+        ];
+        let reads = [
+            json!({ "activity_id": 1, "completeness": 40 }),
+            json!({ "activity_id": 2, "completeness": "full" }),
+            json!({ "activity_id": 5, "completeness": "part" }),
+        ];
+        let videos = parse_videos(&activities, &reads, 5, "操作系统");
+
+        assert_eq!(videos.len(), 4);
+        assert_eq!(videos[0].duration_seconds, Some(1800));
+        assert_eq!(videos[0].completion, Some(40.0));
+        assert_eq!(videos[0].watch_state, WatchState::Part);
+        assert!(videos[1].completed);
+        assert_eq!(videos[2].completion, Some(0.0));
+        assert_eq!(videos[2].watch_state, WatchState::Unwatched);
+        assert_eq!(videos[3].completion, None);
+        assert_eq!(videos[3].watch_state, WatchState::Part);
+
+        let todos = video_todos(&videos, Utc.with_ymd_and_hms(2024, 5, 1, 0, 0, 0).unwrap());
+        assert_eq!(todos.len(), 2);
+        assert_eq!(todos[0]["id"], 1);
+        assert_eq!(todos[1]["id"], 5);
+        assert_eq!(todos[0]["type"], VIDEO_ACTIVITY_TYPE);
+    }
+}
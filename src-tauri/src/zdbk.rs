@@ -326,3 +326,73 @@ fn extract_practice_score(html: &str, class_name: &str) -> Option<f64> {
     re.captures(html)
         .and_then(|caps| caps[1].parse::<f64>().ok())
 }
+
+/// GPA figures zdbk prints on the grade query page. Each is `None` when the
+/// page does not show it.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OfficialGpaSummary {
+    pub five_point: Option<f64>,
+    pub hundred_point: Option<f64>,
+    pub credits: Option<f64>,
+}
+
+/// Fetch the summary GPA shown above the grade list. `Ok(None)` means the
+/// page loaded but carries no summary.
+pub async fn get_gpa_summary(state: &AppState) -> Result<Option<OfficialGpaSummary>, String> {
+    let client = &state.client;
+    let username = state.username.lock().await;
+    let student_id = username.as_ref().ok_or("未登录")?;
+    let jsid = state.zdbk_jsessionid.lock().await;
+    let rt = state.zdbk_route.lock().await;
+    let cookies = zdbk_cookies(
+        jsid.as_ref().ok_or("教务网未登录")?,
+        rt.as_ref().ok_or("教务网未登录")?,
+    );
+
+    let url = format!(
+        "https://zdbk.zju.edu.cn/jwglxt/cxdy/xscjcx_cxXscjIndex.html?gnmkdm=N5083&layout=default&su={}",
+        student_id
+    );
+    let res = client
+        .get(&url)
+        .header("Cookie", &cookies)
+        .send()
+        .await
+        .map_err(|e| format!("绩点汇总查询失败: {}", e))?;
+
+    let html = res
+        .text()
+        .await
+        .map_err(|e| format!("读取绩点汇总响应失败: {}", e))?;
+
+    let summary = parse_gpa_summary(&html);
+    Ok((summary != OfficialGpaSummary::default()).then_some(summary))
+}
+
+/// Reads the labelled totals above the grade list. Only full labels are
+/// accepted, since the grade table's own 绩点 column header would otherwise
+/// match, and out-of-range values are dropped rather than trusted.
+pub fn parse_gpa_summary(html: &str) -> OfficialGpaSummary {
+    OfficialGpaSummary {
+        five_point: extract_labelled_number(html, &["平均绩点"])
+            .filter(|value| (0.0..=5.0).contains(value)),
+        hundred_point: extract_labelled_number(html, &["平均分"])
+            .filter(|value| (0.0..=100.0).contains(value)),
+        credits: extract_labelled_number(html, &["获得学分", "总学分"])
+            .filter(|value| *value >= 0.0),
+    }
+}
+
+fn extract_labelled_number(html: &str, labels: &[&str]) -> Option<f64> {
+    labels.iter().find_map(|label| {
+        // The value sits in the same or the next cell, a few tags away at most.
+        let pattern = format!(
+            r#"{}\s*[：:]?\s*(?:<[^>]*>\s*){{0,4}}(-?[0-9]+(?:\.[0-9]+)?)"#,
+            regex::escape(label)
+        );
+        let re = regex::Regex::new(&pattern).ok()?;
+        re.captures(html)
+            .and_then(|caps| caps[1].parse::<f64>().ok())
+    })
+}
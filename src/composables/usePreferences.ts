@@ -1,6 +1,7 @@
 import { computed, ref } from 'vue';
+import type { CourseCategory } from '../types/api';
 
-export type RetakePolicy = 'first' | 'highest';
+export type RetakePolicy = 'first' | 'highest' | 'latest' | 'average';
 export type ThemeMode = 'light' | 'dark';
 export type ThemePalette = 'monterey' | 'midnight' | 'aurora' | 'crimson';
 export type GlassEffect = 'liquid' | 'frosted';
@@ -15,6 +16,7 @@ export interface Preferences {
   timeConfigMode: TimeConfigMode;
   manualSemesterAnchors: Record<string, string>;
   courseIdMappings: Record<string, string>;
+  courseCategoryOverrides: Record<string, CourseCategory>;
   dingtalkWebhookEnabled: boolean;
   dingtalkWebhookUrl: string;
   dingtalkWebhookSecret: string;
@@ -33,6 +35,7 @@ const DEFAULTS: Preferences = {
   timeConfigMode: 'remote',
   manualSemesterAnchors: {},
   courseIdMappings: {},
+  courseCategoryOverrides: {},
   dingtalkWebhookEnabled: false,
   dingtalkWebhookUrl: '',
   dingtalkWebhookSecret: '',
@@ -41,8 +44,9 @@ const DEFAULTS: Preferences = {
 };
 
 function sanitize(input: Partial<Preferences> | null | undefined): Preferences {
-  const retakePolicy = input?.retakePolicy === 'highest' || (input as any)?.retakePolicy === 'best'
-    ? 'highest'
+  const rawRetakePolicy = (input as any)?.retakePolicy === 'best' ? 'highest' : input?.retakePolicy;
+  const retakePolicy = ['first', 'highest', 'latest', 'average'].includes(String(rawRetakePolicy))
+    ? (rawRetakePolicy as RetakePolicy)
     : 'first';
   const themeMode = input?.themeMode === 'dark' ? 'dark' : 'light';
   const themePalette = ['monterey', 'midnight', 'aurora', 'crimson'].includes(String(input?.themePalette))
@@ -62,6 +66,7 @@ function sanitize(input: Partial<Preferences> | null | undefined): Preferences {
     hideGpa: Boolean(input?.hideGpa),
     manualSemesterAnchors: input?.manualSemesterAnchors || {},
     courseIdMappings: input?.courseIdMappings || {},
+    courseCategoryOverrides: input?.courseCategoryOverrides || {},
     dingtalkWebhookEnabled: Boolean(input?.dingtalkWebhookEnabled),
     dingtalkWebhookUrl: String((input as any)?.dingtalkWebhookUrl || ''),
     dingtalkWebhookSecret: String((input as any)?.dingtalkWebhookSecret || ''),
@@ -116,6 +121,18 @@ function setManualSemesterAnchor(termName: string, date: string | null) {
   patchPreferences({ manualSemesterAnchors: next });
 }
 
+function setCourseCategoryOverride(courseKey: string, category: CourseCategory | null) {
+  const next = {
+    ...preferencesState.value.courseCategoryOverrides,
+  };
+  if (category) {
+    next[courseKey] = category;
+  } else {
+    delete next[courseKey];
+  }
+  patchPreferences({ courseCategoryOverrides: next });
+}
+
 function bumpAccountScope() {
   accountScope.value += 1;
 }
@@ -130,6 +147,7 @@ export function usePreferences() {
   const timeConfigMode = computed(() => preferencesState.value.timeConfigMode);
   const manualSemesterAnchors = computed(() => preferencesState.value.manualSemesterAnchors);
   const courseIdMappings = computed(() => preferencesState.value.courseIdMappings);
+  const courseCategoryOverrides = computed(() => preferencesState.value.courseCategoryOverrides);
   const zeroClawEndpoint = computed(() => preferencesState.value.zeroClawEndpoint);
   const zeroClawApiKey = computed(() => preferencesState.value.zeroClawApiKey);
   const dingtalkWebhookEnabled = computed(() => preferencesState.value.dingtalkWebhookEnabled);
@@ -147,6 +165,7 @@ export function usePreferences() {
     timeConfigMode,
     manualSemesterAnchors,
     courseIdMappings,
+    courseCategoryOverrides,
     dingtalkWebhookEnabled,
     dingtalkWebhookUrl,
     dingtalkWebhookSecret,
@@ -154,6 +173,7 @@ export function usePreferences() {
     zeroClawApiKey,
     patchPreferences,
     setManualSemesterAnchor,
+    setCourseCategoryOverride,
     bumpAccountScope,
     setRetakePolicy: (value: RetakePolicy) => patchPreferences({ retakePolicy: value }),
     setHideGpa: (value: boolean) => patchPreferences({ hideGpa: value }),
@@ -1,19 +1,66 @@
 import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
 import type {
   AiAnalysisInput,
   AiAnalysisPayload,
   ApiEnvelope,
   ApiMeta,
+  ArchiveTerm,
+  ArchiveTermKey,
+  AttendanceReport,
+  CommonFreeTimeInput,
+  CommonFreeTimePayload,
   DingtalkTestInput,
   DownloadMaterialInput,
+  EvaluationInput,
+  EvaluationResult,
+  EvaluationRule,
+  EvaluationRuleImportInput,
+  EvaluationRulesPayload,
+  GpaAnalytics,
+  GpaCategoryInput,
+  GpaCategoryPayload,
+  GpaAnalyticsInput,
+  GpaPlan,
+  GpaPlanInput,
   GpaPreviewInput,
+  GpaReconciliation,
+  GpaScale,
+  GpaScenario,
+  GpaScenarioCompareInput,
+  GpaScenarioComparison,
+  GpaScenarioInput,
+  GpaScenarioResult,
+  GpaScaleImportInput,
+  GpaScalesPayload,
   GpaSummary,
+  HomeworkDetail,
+  HomeworkSubmissionInput,
+  HomeworkSubmissionResult,
+  LectureDownloadInput,
+  LectureDownloadProgress,
+  LectureStream,
+  LegacyImportInput,
+  LegacyImportReport,
+  LocalTask,
+  MaterialAsset,
   MaterialsPayload,
   MaterialTextPayload,
+  NotificationFeed,
   RemoteMaterialDownloadInput,
+  RetakeOverride,
   ScholarPayload,
   TimetablePayload,
+  TimetableShareExportInput,
+  TimetableSharePayload,
+  TodoDelta,
+  TodoHistory,
   TodosPayload,
+  TranscriptAudit,
+  TranscriptExportInput,
+  TranscriptExportPayload,
+  TranscriptImportInput,
+  VideoActivitiesPayload,
 } from '../types/api';
 
 function fallbackMeta(): ApiMeta {
@@ -48,6 +95,14 @@ export async function fetchScholarData(options?: { forceRefresh?: boolean }): Pr
     ? env.data.retakePolicySupported
     : ['first', 'highest'];
   env.data.currentCourses = Array.isArray(env.data.currentCourses) ? env.data.currentCourses : [];
+  env.data.warnings = Array.isArray(env.data.warnings) ? env.data.warnings : [];
+  return env;
+}
+
+export async function importTranscriptFile(input: TranscriptImportInput): Promise<ApiEnvelope<ScholarPayload>> {
+  const env = await callEnvelope<ScholarPayload>('import_transcript_file', { input });
+  env.data.semesters = Array.isArray(env.data.semesters) ? env.data.semesters : [];
+  env.data.currentCourses = [];
   return env;
 }
 
@@ -60,6 +115,20 @@ export async function fetchTimetable(args: {
   if (!env.data?.term || !Array.isArray(env.data?.sessions)) {
     throw new Error('Invalid timetable payload: term/sessions missing');
   }
+  env.data.changes = Array.isArray(env.data.changes) ? env.data.changes : [];
+  env.data.zdbkSessions = Array.isArray(env.data.zdbkSessions) ? env.data.zdbkSessions : env.data.sessions;
+  env.data.merge = env.data.merge ?? null;
+  env.data.mergeError = env.data.mergeError ?? null;
+  return env;
+}
+
+export async function exportTimetableShare(input: TimetableShareExportInput): Promise<ApiEnvelope<TimetableSharePayload>> {
+  return callEnvelope<TimetableSharePayload>('export_timetable_share', { input });
+}
+
+export async function findCommonFreeTime(input: CommonFreeTimeInput): Promise<ApiEnvelope<CommonFreeTimePayload>> {
+  const env = await callEnvelope<CommonFreeTimePayload>('find_common_free_time', { input });
+  env.data.days = Array.isArray(env.data?.days) ? env.data.days : [];
   return env;
 }
 
@@ -69,11 +138,190 @@ export async function fetchTodos(options?: { forceRefresh?: boolean }): Promise<
   return env;
 }
 
+export async function fetchTodoHistory(): Promise<ApiEnvelope<TodoHistory>> {
+  const env = await callEnvelope<TodoHistory>('fetch_todo_history');
+  env.data.deltas = Array.isArray(env.data?.deltas) ? env.data.deltas : [];
+  return env;
+}
+
+/** Subscribes to the `todos-changed` event a todo sync emits when something changed. */
+export function onTodosChanged(handler: (delta: TodoDelta) => void): Promise<UnlistenFn> {
+  return listen<TodoDelta>('todos-changed', (event) => handler(event.payload));
+}
+
+export async function fetchVideoActivities(options?: {
+  forceRefresh?: boolean;
+}): Promise<ApiEnvelope<VideoActivitiesPayload>> {
+  const env = await callEnvelope<VideoActivitiesPayload>('fetch_video_activities', {
+    forceRefresh: options?.forceRefresh,
+  });
+  env.data.videos = Array.isArray(env.data?.videos) ? env.data.videos : [];
+  env.data.warnings = Array.isArray(env.data?.warnings) ? env.data.warnings : [];
+  return env;
+}
+
+export async function fetchTodoDetail(
+  todoId: string,
+  options?: { forceRefresh?: boolean },
+): Promise<ApiEnvelope<HomeworkDetail>> {
+  const env = await callEnvelope<HomeworkDetail>('fetch_todo_detail', {
+    todoId,
+    forceRefresh: options?.forceRefresh,
+  });
+  env.data.attachments = Array.isArray(env.data?.attachments) ? env.data.attachments : [];
+  env.data.submissions = Array.isArray(env.data?.submissions) ? env.data.submissions : [];
+  return env;
+}
+
 export async function calculateGpaPreview(input: GpaPreviewInput): Promise<GpaSummary> {
   const result = await invoke('calculate_gpa_preview', { input });
   return result as GpaSummary;
 }
 
+export async function planTargetGpa(input: GpaPlanInput): Promise<GpaPlan> {
+  const result = (await invoke('plan_target_gpa', { input })) as GpaPlan;
+  result.proposals = Array.isArray(result?.proposals) ? result.proposals : [];
+  return result;
+}
+
+export async function analyzeGpa(input: GpaAnalyticsInput): Promise<GpaAnalytics> {
+  const result = (await invoke('analyze_gpa', { input })) as GpaAnalytics;
+  result.terms = Array.isArray(result?.terms) ? result.terms : [];
+  result.histogram = Array.isArray(result?.histogram) ? result.histogram : [];
+  result.best = Array.isArray(result?.best) ? result.best : [];
+  result.worst = Array.isArray(result?.worst) ? result.worst : [];
+  return result;
+}
+
+export async function calculateGpaByCategory(input: GpaCategoryInput): Promise<GpaCategoryPayload> {
+  const result = (await invoke('calculate_gpa_by_category', { input })) as GpaCategoryPayload;
+  result.categories = Array.isArray(result?.categories) ? result.categories : [];
+  result.assignments = Array.isArray(result?.assignments) ? result.assignments : [];
+  return result;
+}
+
+export async function listGpaScales(): Promise<ApiEnvelope<GpaScalesPayload>> {
+  const env = await callEnvelope<GpaScalesPayload>('list_gpa_scales');
+  env.data.scales = Array.isArray(env.data?.scales) ? env.data.scales : [];
+  env.data.errors = Array.isArray(env.data?.errors) ? env.data.errors : [];
+  return env;
+}
+
+export async function importGpaScale(input: GpaScaleImportInput): Promise<ApiEnvelope<GpaScale>> {
+  return callEnvelope<GpaScale>('import_gpa_scale', { input });
+}
+
+export async function deleteGpaScale(id: string): Promise<ApiEnvelope<{ id: string }>> {
+  return callEnvelope<{ id: string }>('delete_gpa_scale', { id });
+}
+
+export async function evaluateScholarship(input: EvaluationInput): Promise<EvaluationResult> {
+  const result = (await invoke('evaluate_scholarship', { input })) as EvaluationResult;
+  result.items = Array.isArray(result?.items) ? result.items : [];
+  result.tiers = Array.isArray(result?.tiers) ? result.tiers : [];
+  result.warnings = Array.isArray(result?.warnings) ? result.warnings : [];
+  return result;
+}
+
+export async function listEvaluationRules(): Promise<ApiEnvelope<EvaluationRulesPayload>> {
+  const env = await callEnvelope<EvaluationRulesPayload>('list_evaluation_rules');
+  env.data.rules = Array.isArray(env.data?.rules) ? env.data.rules : [];
+  env.data.errors = Array.isArray(env.data?.errors) ? env.data.errors : [];
+  return env;
+}
+
+export async function importEvaluationRule(input: EvaluationRuleImportInput): Promise<ApiEnvelope<EvaluationRule>> {
+  return callEnvelope<EvaluationRule>('import_evaluation_rule', { input });
+}
+
+export async function deleteEvaluationRule(id: string): Promise<ApiEnvelope<{ id: string }>> {
+  return callEnvelope<{ id: string }>('delete_evaluation_rule', { id });
+}
+
+export async function exportEnglishTranscript(input: TranscriptExportInput): Promise<ApiEnvelope<TranscriptExportPayload>> {
+  const env = await callEnvelope<TranscriptExportPayload>('export_english_transcript', { input });
+  env.data.untranslated = Array.isArray(env.data?.untranslated) ? env.data.untranslated : [];
+  return env;
+}
+
+export async function getTranscriptTranslations(): Promise<ApiEnvelope<Record<string, string>>> {
+  return callEnvelope<Record<string, string>>('get_transcript_translations');
+}
+
+export async function saveTranscriptTranslations(entries: Record<string, string>): Promise<ApiEnvelope<Record<string, string>>> {
+  return callEnvelope<Record<string, string>>('save_transcript_translations', { input: { entries } });
+}
+
+export async function auditTranscript(grades?: any[]): Promise<TranscriptAudit> {
+  const result = (await invoke('audit_transcript', { grades })) as TranscriptAudit;
+  result.findings = Array.isArray(result?.findings) ? result.findings : [];
+  return result;
+}
+
+export async function listGpaScenarios(): Promise<ApiEnvelope<{ scenarios: GpaScenario[] }>> {
+  const env = await callEnvelope<{ scenarios: GpaScenario[] }>('list_gpa_scenarios');
+  env.data.scenarios = Array.isArray(env.data?.scenarios) ? env.data.scenarios : [];
+  return env;
+}
+
+export async function createGpaScenario(input: GpaScenarioInput): Promise<ApiEnvelope<GpaScenario>> {
+  return callEnvelope<GpaScenario>('create_gpa_scenario', { input });
+}
+
+export async function updateGpaScenario(id: string, input: GpaScenarioInput): Promise<ApiEnvelope<GpaScenario>> {
+  return callEnvelope<GpaScenario>('update_gpa_scenario', { id, input });
+}
+
+export async function reconcileGpa(
+  grades?: any[],
+  retakeOverrides?: Record<string, RetakeOverride>,
+): Promise<ApiEnvelope<GpaReconciliation>> {
+  const env = await callEnvelope<GpaReconciliation>('reconcile_gpa', { grades, retakeOverrides });
+  env.data.policies = Array.isArray(env.data?.policies) ? env.data.policies : [];
+  env.data.discrepancies = Array.isArray(env.data?.discrepancies) ? env.data.discrepancies : [];
+  return env;
+}
+
+export async function importLegacyData(input: LegacyImportInput): Promise<LegacyImportReport> {
+  const result = (await invoke('import_legacy_data', { input })) as LegacyImportReport;
+  result.mappingChanges = Array.isArray(result?.mappingChanges) ? result.mappingChanges : [];
+  result.tasks = Array.isArray(result?.tasks) ? result.tasks : [];
+  result.skipped = Array.isArray(result?.skipped) ? result.skipped : [];
+  return result;
+}
+
+export async function listLocalTasks(): Promise<ApiEnvelope<{ tasks: LocalTask[] }>> {
+  const env = await callEnvelope<{ tasks: LocalTask[] }>('list_local_tasks');
+  env.data.tasks = Array.isArray(env.data?.tasks) ? env.data.tasks : [];
+  return env;
+}
+
+export async function deleteLocalTask(id: string): Promise<ApiEnvelope<{ id: string }>> {
+  return callEnvelope<{ id: string }>('delete_local_task', { id });
+}
+
+export async function deleteGpaScenario(id: string): Promise<ApiEnvelope<{ id: string }>> {
+  return callEnvelope<{ id: string }>('delete_gpa_scenario', { id });
+}
+
+export async function duplicateGpaScenario(id: string, name?: string): Promise<ApiEnvelope<GpaScenario>> {
+  return callEnvelope<GpaScenario>('duplicate_gpa_scenario', { id, name });
+}
+
+export async function loadGpaScenario(id: string, grades?: any[], majorCourseIds?: string[]): Promise<GpaScenarioResult> {
+  const result = (await invoke('load_gpa_scenario', { id, grades, majorCourseIds })) as GpaScenarioResult;
+  result.stale = Array.isArray(result?.stale) ? result.stale : [];
+  return result;
+}
+
+export async function compareGpaScenarios(input: GpaScenarioCompareInput): Promise<GpaScenarioComparison> {
+  const result = (await invoke('compare_gpa_scenarios', { input })) as GpaScenarioComparison;
+  result.results = Array.isArray(result?.results)
+    ? result.results.map((item) => ({ ...item, stale: Array.isArray(item?.stale) ? item.stale : [] }))
+    : [];
+  return result;
+}
+
 function normalizeMaterialsPayload(env: ApiEnvelope<MaterialsPayload>) {
   env.data.defaultScope = env.data?.defaultScope === 'all' ? 'all' : env.data?.defaultScope === 'current-term' ? 'current-term' : 'current-week';
   env.data.courseFilters = Array.isArray(env.data?.courseFilters) ? env.data.courseFilters : [];
@@ -85,12 +333,57 @@ function normalizeMaterialsPayload(env: ApiEnvelope<MaterialsPayload>) {
   return env;
 }
 
+export async function submitHomework(
+  input: HomeworkSubmissionInput,
+): Promise<ApiEnvelope<HomeworkSubmissionResult>> {
+  const env = await callEnvelope<HomeworkSubmissionResult>('submit_homework', { input });
+  env.data.uploads = Array.isArray(env.data?.uploads) ? env.data.uploads : [];
+  return env;
+}
+
+export async function fetchAttendance(options?: {
+  threshold?: number;
+  forceRefresh?: boolean;
+}): Promise<ApiEnvelope<AttendanceReport>> {
+  const env = await callEnvelope<AttendanceReport>('fetch_attendance', {
+    threshold: options?.threshold,
+    forceRefresh: options?.forceRefresh,
+  });
+  env.data.courses = Array.isArray(env.data?.courses) ? env.data.courses : [];
+  env.data.atRisk = Array.isArray(env.data?.atRisk) ? env.data.atRisk : [];
+  env.data.warnings = Array.isArray(env.data?.warnings) ? env.data.warnings : [];
+  return env;
+}
+
+function normalizeFeed(env: ApiEnvelope<NotificationFeed>): ApiEnvelope<NotificationFeed> {
+  env.data.items = Array.isArray(env.data?.items) ? env.data.items : [];
+  env.data.newIds = Array.isArray(env.data?.newIds) ? env.data.newIds : [];
+  env.data.warnings = Array.isArray(env.data?.warnings) ? env.data.warnings : [];
+  return env;
+}
+
+export async function syncNotificationFeed(options?: { forceRefresh?: boolean }): Promise<ApiEnvelope<NotificationFeed>> {
+  return normalizeFeed(
+    await callEnvelope<NotificationFeed>('sync_notification_feed', { forceRefresh: options?.forceRefresh }),
+  );
+}
+
+export async function markNotificationsRead(ids?: string[]): Promise<ApiEnvelope<NotificationFeed>> {
+  return normalizeFeed(await callEnvelope<NotificationFeed>('mark_notifications_read', { ids }));
+}
+
 export async function fetchMaterials(): Promise<ApiEnvelope<MaterialsPayload>> {
   return normalizeMaterialsPayload(await callEnvelope<MaterialsPayload>('fetch_materials'));
 }
 
-export async function syncMaterialsIndex(): Promise<ApiEnvelope<MaterialsPayload>> {
-  return normalizeMaterialsPayload(await callEnvelope<MaterialsPayload>('sync_materials_index'));
+export async function syncMaterialsIndex(archiveTerm?: ArchiveTermKey): Promise<ApiEnvelope<MaterialsPayload>> {
+  return normalizeMaterialsPayload(await callEnvelope<MaterialsPayload>('sync_materials_index', { archiveTerm }));
+}
+
+export async function fetchCourseArchive(options?: { forceRefresh?: boolean }): Promise<ApiEnvelope<ArchiveTerm[]>> {
+  const env = await callEnvelope<ArchiveTerm[]>('fetch_course_archive', { forceRefresh: options?.forceRefresh });
+  env.data = Array.isArray(env.data) ? env.data : [];
+  return env;
 }
 
 export async function downloadMaterialAsset(input: DownloadMaterialInput): Promise<ApiEnvelope<{ item: unknown }>> {
@@ -108,6 +401,30 @@ export async function readMaterialText(relativePath: string, maxChars?: number):
   return callEnvelope<MaterialTextPayload>('read_material_text', { input: { relativePath, maxChars } });
 }
 
+export async function fetchLectureStreams(
+  courseId: number,
+  subId: number,
+): Promise<ApiEnvelope<{ streams: LectureStream[] }>> {
+  const env = await callEnvelope<{ streams: LectureStream[] }>('fetch_lecture_streams', { courseId, subId });
+  env.data.streams = Array.isArray(env.data?.streams) ? env.data.streams : [];
+  return env;
+}
+
+export async function downloadLectureReplay(
+  input: LectureDownloadInput,
+): Promise<ApiEnvelope<{ items: MaterialAsset[] }>> {
+  const env = await callEnvelope<{ items: MaterialAsset[] }>('download_lecture_replay', { input });
+  env.data.items = Array.isArray(env.data?.items) ? env.data.items : [];
+  return env;
+}
+
+/** Subscribes to progress of running lecture replay downloads. */
+export function onLectureDownloadProgress(
+  handler: (progress: LectureDownloadProgress) => void,
+): Promise<UnlistenFn> {
+  return listen<LectureDownloadProgress>('lecture-download-progress', (event) => handler(event.payload));
+}
+
 export async function openMaterialAsset(relativePath: string): Promise<ApiEnvelope<{ ok: boolean }>> {
   return callEnvelope<{ ok: boolean }>('open_material_asset', { input: { relativePath } });
 }
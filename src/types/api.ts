@@ -1,7 +1,7 @@
 import type { TermDescriptor } from '../utils/semester';
 
-export type MetaSource = 'network' | 'cache' | 'unknown';
-export type RetakePolicy = 'first' | 'highest';
+export type MetaSource = 'network' | 'cache' | 'import' | 'unknown';
+export type RetakePolicy = 'first' | 'highest' | 'latest' | 'average';
 export type MaterialsScope = 'current-week' | 'current-term' | 'all';
 export type MaterialSourceType = 'classroom' | 'activity' | 'homework';
 export type MaterialWeekBucket = 'current' | 'other' | 'unknown';
@@ -27,6 +27,59 @@ export interface GpaSummary {
   majorGpa: number;
   majorGpaLegacy: number;
   majorCredits: number;
+  scales?: ScaleGpa[];
+  retakeChoices?: RetakeChoice[];
+}
+
+export interface RetakeChoice {
+  retakeKey: string;
+  courseName: string;
+  policy: RetakePolicy;
+  attempts: string[];
+  chosen: string[];
+  excluded: string[];
+  reason: string;
+}
+
+export interface RetakeOverride {
+  pick?: string;
+  exclude?: string[];
+  policy?: RetakePolicy;
+}
+
+export interface ScaleGpa {
+  id: string;
+  name: string;
+  maxPoints: number;
+  gpa: number;
+  majorGpa: number;
+}
+
+export interface GpaScaleThreshold {
+  min: number;
+  points: number;
+}
+
+export interface GpaScale {
+  id: string;
+  name: string;
+  description: string;
+  maxPoints: number;
+  input: 'hundred' | 'fivePoint';
+  linear: boolean;
+  thresholds: GpaScaleThreshold[];
+  gradePoints: Record<string, number>;
+  origin: 'preset' | 'user';
+}
+
+export interface GpaScalesPayload {
+  scales: GpaScale[];
+  errors: string[];
+}
+
+export interface GpaScaleImportInput {
+  path?: string;
+  content?: string;
 }
 
 export interface ScholarSemester {
@@ -37,11 +90,30 @@ export interface ScholarSemester {
   gpaByPolicy: {
     first: GpaSummary;
     highest: GpaSummary;
+    latest?: GpaSummary;
+    average?: GpaSummary;
   };
   gpa?: number[];
   credits?: number;
 }
 
+export interface ActivityScore {
+  activityId?: number | null;
+  title: string;
+  kind: string;
+  score?: number | null;
+  fullScore: number;
+  weight?: number | null;
+}
+
+export interface CourseScoreProgress {
+  items: ActivityScore[];
+  graded: number;
+  estimate?: number | null;
+  method?: 'weighted' | 'points' | null;
+  weightCovered?: number | null;
+}
+
 export interface CurrentLearningCourse {
   id: string;
   courseId: string;
@@ -50,6 +122,7 @@ export interface CurrentLearningCourse {
   credit: number;
   teacher?: string;
   term: TermDescriptor;
+  scoreProgress?: CourseScoreProgress | null;
 }
 
 export interface ScholarPayload {
@@ -57,6 +130,8 @@ export interface ScholarPayload {
   gpaByPolicy: {
     first: GpaSummary;
     highest: GpaSummary;
+    latest?: GpaSummary;
+    average?: GpaSummary;
   };
   retakePolicySupported: RetakePolicy[];
   transcript: any[];
@@ -70,6 +145,23 @@ export interface ScholarPayload {
   };
   semesters: ScholarSemester[];
   currentCourses?: CurrentLearningCourse[];
+  /** Courses whose score estimates failed to load, as `课程名: 错误`. */
+  warnings?: string[];
+  /** Present when the payload was built from an imported 成绩单. */
+  import?: TranscriptImportInfo;
+}
+
+export type TranscriptImportFormat = 'xlsx' | 'csv' | 'pdf';
+
+export interface TranscriptImportInput {
+  path: string;
+  format?: TranscriptImportFormat;
+}
+
+export interface TranscriptImportInfo {
+  format: TranscriptImportFormat;
+  rows: number;
+  skipped: string[];
 }
 
 export interface SessionTimeSlot {
@@ -103,6 +195,19 @@ export interface NormalizedTimetableSession {
   secondHalf: boolean;
 }
 
+export type TimetableChangeKind = 'added' | 'removed' | 'moved' | 'relocated' | 'reweeked';
+
+export interface TimetableChange {
+  kind: TimetableChangeKind;
+  sessionId: string;
+  xkkh: string;
+  courseName: string;
+  before?: NormalizedTimetableSession | null;
+  after?: NormalizedTimetableSession | null;
+  affectedWeeks: number[];
+  summary: string;
+}
+
 export interface TimetablePayload {
   term: TermDescriptor;
   displayName: string;
@@ -111,9 +216,166 @@ export interface TimetablePayload {
   xqm: '3' | '12';
   timeConfig: TermTimeConfig;
   sessions: NormalizedTimetableSession[];
+  zdbkSessions: NormalizedTimetableSession[];
+  changes: TimetableChange[];
+  merge: TimetableMergeReport | null;
+  mergeError: string | null;
   timetable: any[];
 }
 
+export interface LearningScheduleEntry {
+  courseId: number;
+  courseCode: string;
+  courseName: string;
+  teacher: string;
+  dayOfWeek: number;
+  startPeriod: number;
+  endPeriod: number;
+  weekNumbers: number[];
+  location: string;
+}
+
+export interface ScheduleDisagreement {
+  sessionId: string;
+  courseName: string;
+  field: 'location' | 'time' | 'weeks';
+  zdbk: string;
+  courses: string;
+}
+
+export interface UnmatchedCourse {
+  source: 'zdbk' | 'courses';
+  courseCode: string;
+  courseName: string;
+}
+
+export interface TimetableMergeReport {
+  filledLocations: number;
+  filledTeachers: number;
+  disagreements: ScheduleDisagreement[];
+  onlyInZdbk: UnmatchedCourse[];
+  onlyInCourses: UnmatchedCourse[];
+  learningEntries: LearningScheduleEntry[];
+}
+
+export interface TimetableShareExportInput {
+  term: TermDescriptor;
+  sessions: NormalizedTimetableSession[];
+  label?: string;
+}
+
+export interface TimetableSharePayload {
+  code: string;
+  term: TermDescriptor;
+  blockCount: number;
+}
+
+export interface CommonFreeTimeInput {
+  codes: string[];
+  weeks?: number[];
+  includeWeekends?: boolean;
+}
+
+export interface FreeSlot {
+  startPeriod: number;
+  endPeriod: number;
+  start: string;
+  end: string;
+}
+
+export interface FreeDay {
+  week: number;
+  dayOfWeek: number;
+  date?: string | null;
+  slots: FreeSlot[];
+}
+
+export interface CommonFreeTimePayload {
+  term: TermDescriptor;
+  participants: string[];
+  sessionTimes: SessionTimeSlot[];
+  days: FreeDay[];
+}
+
+export interface ArchiveTermKey {
+  academicYearId: number;
+  semesterId: number;
+}
+
+export interface ArchivedCourse {
+  id: number;
+  name: string;
+  courseCode: string;
+  teacher: string;
+  startDate?: string | null;
+  endDate?: string | null;
+}
+
+export interface ArchiveTerm extends ArchiveTermKey {
+  term: TermDescriptor | null;
+  courses: ArchivedCourse[];
+}
+
+export type RollcallStatus = 'present' | 'late' | 'leave' | 'absent' | 'unknown';
+
+export interface RollcallRecord {
+  id?: number | null;
+  date: string;
+  method: string;
+  status: RollcallStatus;
+}
+
+export interface CourseAttendance {
+  courseId: number;
+  courseName: string;
+  records: RollcallRecord[];
+  present: number;
+  late: number;
+  leave: number;
+  absent: number;
+  rate?: number | null;
+}
+
+export interface AttendanceReport {
+  threshold: number;
+  courses: CourseAttendance[];
+  atRisk: number[];
+  warnings: string[];
+}
+
+export interface QuizActivity {
+  activityId: number;
+  courseId: number;
+  courseName: string;
+  title: string;
+  startTime?: string | null;
+  endTime?: string | null;
+  durationMinutes?: number | null;
+  attemptLimit?: number | null;
+  attemptsUsed: number;
+  completed: boolean;
+}
+
+export type WatchState = 'unwatched' | 'part' | 'full';
+
+export interface VideoActivity {
+  activityId: number;
+  courseId: number;
+  courseName: string;
+  title: string;
+  durationSeconds?: number | null;
+  watchState: WatchState;
+  /** Watched percentage; null when 学在浙大 only reports `part`. */
+  completion: number | null;
+  completed: boolean;
+  endTime?: string | null;
+}
+
+export interface VideoActivitiesPayload {
+  videos: VideoActivity[];
+  warnings: string[];
+}
+
 export interface TodoItem {
   id: string;
   title: string;
@@ -122,12 +384,40 @@ export interface TodoItem {
   endTime: string;
   end_time?: string;
   status: string;
+  /** `homework`, `exam`… from 学在浙大; merged quizzes and videos use `online_quiz` and `online_video`. */
+  type?: string;
   linkUrl?: string | null;
-  raw?: Record<string, unknown>;
+  raw?: Record<string, unknown> & { quiz?: QuizActivity; video?: VideoActivity };
+}
+
+export interface TodoSnapshot {
+  id: string;
+  title: string;
+  courseName: string;
+  courseId?: number | null;
+  type: string;
+  endTime: string;
+  completed: boolean;
+}
+
+export interface TodoDelta {
+  syncedAt: number;
+  added: TodoSnapshot[];
+  deadlineMoved: Array<{ todo: TodoSnapshot; previousEndTime: string }>;
+  completed: TodoSnapshot[];
+  removed: TodoSnapshot[];
+}
+
+export interface TodoHistory {
+  deltas: TodoDelta[];
+  lastSyncedAt?: number | null;
 }
 
 export interface TodosPayload {
   todo_list: TodoItem[];
+  warnings?: string[];
+  /** What changed since the previous sync; empty on the first one. */
+  changes?: TodoDelta;
 }
 
 export interface GpaPreviewInput {
@@ -135,8 +425,533 @@ export interface GpaPreviewInput {
   selectedIds?: string[];
   simulatedScores?: Record<string, number>;
   retakePolicy?: RetakePolicy;
+  retakeOverrides?: Record<string, RetakeOverride>;
   majorCourseIds?: string[];
   courseIdMappings?: Record<string, string>;
+  scaleIds?: string[];
+}
+
+export interface GpaScenario {
+  id: string;
+  name: string;
+  note: string;
+  createdAt: number;
+  updatedAt: number;
+  selectedIds: string[];
+  simulatedScores: Record<string, number>;
+  courseIdMappings: Record<string, string>;
+  retakePolicy: RetakePolicy | null;
+  retakeOverrides: Record<string, RetakeOverride>;
+  scaleIds: string[] | null;
+  plannedGrades: any[];
+  pendingKeys: string[];
+}
+
+export interface GpaScenarioInput {
+  name: string;
+  note?: string;
+  grades: any[];
+  selectedIds?: string[];
+  simulatedScores?: Record<string, number>;
+  courseIdMappings?: Record<string, string>;
+  retakePolicy?: RetakePolicy;
+  retakeOverrides?: Record<string, RetakeOverride>;
+  scaleIds?: string[];
+}
+
+export interface StaleSimulation {
+  key: string;
+  courseName: string;
+  simulated: number;
+  actual: string;
+}
+
+export interface GpaScenarioResult {
+  scenario: GpaScenario;
+  summary: GpaSummary;
+  stale: StaleSimulation[];
+  delta?: {
+    fivePoint: number;
+    fourPoint: number;
+    fourPointLegacy: number;
+    hundredPoint: number;
+    totalCredits: number;
+  };
+}
+
+export interface GpaScenarioCompareInput {
+  ids: string[];
+  grades?: any[];
+  majorCourseIds?: string[];
+  retakePolicy?: RetakePolicy;
+}
+
+export interface GpaScenarioComparison {
+  baseline: GpaSummary;
+  results: GpaScenarioResult[];
+}
+
+export type TranscriptAuditKind =
+  | 'unknownGrade'
+  | 'emptyGrade'
+  | 'scoreOutOfRange'
+  | 'credit'
+  | 'semester'
+  | 'jdMismatch'
+  | 'duplicateRow'
+  | 'duplicateCourse';
+
+export interface TranscriptAuditFinding {
+  index: number;
+  xkkh: string;
+  courseName: string;
+  kind: TranscriptAuditKind;
+  severity: 'error' | 'warning';
+  field: string;
+  value: string;
+  message: string;
+}
+
+export interface TranscriptAudit {
+  totalRows: number;
+  flaggedRows: number;
+  findings: TranscriptAuditFinding[];
+}
+
+export interface FeedItem {
+  id: string;
+  source: 'announcement' | 'notification';
+  courseId?: number | null;
+  courseName: string;
+  title: string;
+  contentHtml: string;
+  createdAt: string;
+  read: boolean;
+}
+
+export interface NotificationFeed {
+  items: FeedItem[];
+  unread: number;
+  newIds: string[];
+  lastSyncedAt?: number | null;
+  warnings: string[];
+}
+
+export interface HomeworkFile {
+  id: number;
+  referenceId?: number | null;
+  name: string;
+  sizeBytes: number;
+}
+
+export interface HomeworkSubmissionRecord {
+  id?: number | null;
+  isDraft: boolean;
+  submittedAt?: string | null;
+  comment: string;
+  files: HomeworkFile[];
+  score?: number | null;
+  feedback?: string | null;
+}
+
+export interface HomeworkDetail {
+  activityId: number;
+  courseId?: number | null;
+  courseName: string;
+  title: string;
+  descriptionHtml: string;
+  attachments: HomeworkFile[];
+  startTime?: string | null;
+  endTime?: string | null;
+  latePolicy: {
+    allowed: boolean;
+    endTime?: string | null;
+    penalty?: string | null;
+  };
+  submitted: boolean;
+  submissions: HomeworkSubmissionRecord[];
+  score?: number | null;
+  feedback?: string | null;
+}
+
+export type SubmissionAttachment =
+  | { kind: 'file'; path: string }
+  | { kind: 'material'; relativePath: string };
+
+export interface HomeworkSubmissionInput {
+  activityId: number;
+  attachments?: SubmissionAttachment[];
+  uploadIds?: number[];
+  comment?: string;
+  draft?: boolean;
+}
+
+export interface HomeworkSubmissionResult {
+  activityId: number;
+  submissionId?: number | null;
+  status: 'draft' | 'submitted';
+  submittedAt?: string | null;
+  uploads: Array<{ id: number; name: string }>;
+}
+
+export interface OfficialGpaSummary {
+  fivePoint?: number | null;
+  hundredPoint?: number | null;
+  credits?: number | null;
+}
+
+export interface GpaPolicyReconciliation {
+  policy: RetakePolicy;
+  fivePoint: number;
+  hundredPoint: number;
+  gpaCredits: number;
+  earnedCredits: number;
+  fivePointDelta?: number | null;
+  creditsDelta?: number | null;
+  matches: boolean;
+}
+
+export interface GpaDiscrepancy {
+  kind: 'jdMismatch' | 'retakeChoice' | 'notCounted';
+  xkkh: string;
+  courseName: string;
+  credit: number;
+  detail: string;
+  impact: Partial<Record<RetakePolicy, number>>;
+}
+
+export interface GpaReconciliation {
+  official?: OfficialGpaSummary | null;
+  officialError?: string | null;
+  reference: 'official' | 'courseJd';
+  referenceFivePoint: number;
+  referenceCredits?: number | null;
+  policies: GpaPolicyReconciliation[];
+  closestPolicy?: RetakePolicy | null;
+  discrepancies: GpaDiscrepancy[];
+}
+
+export interface LegacyImportInput {
+  path: string;
+  dryRun?: boolean;
+  existingMappings?: Record<string, string>;
+}
+
+export interface LocalTask {
+  id: string;
+  kind: 'deadline' | 'fixed';
+  title: string;
+  description: string;
+  location: string;
+  startAt?: string | null;
+  endAt?: string | null;
+  done: boolean;
+  repeat?: string | null;
+  repeatUntil?: string | null;
+  source: string;
+}
+
+export interface LegacyImportReport {
+  dryRun: boolean;
+  courseIdMappings: Record<string, string>;
+  mappingChanges: Array<{ from: string; to: string; previous?: string | null }>;
+  scenario?: {
+    name: string;
+    action: "created" | "updated";
+    selectedCourses: number;
+    simulatedScores: number;
+    id?: string | null;
+  } | null;
+  tasks: LocalTask[];
+  tasksAdded: number;
+  skipped: Array<{ section: string; key: string; reason: string }>;
+}
+
+export interface GpaAnalyticsInput {
+  grades: any[];
+  retakePolicy?: RetakePolicy;
+  retakeOverrides?: Record<string, RetakeOverride>;
+  majorCourseIds?: string[];
+  courseIdMappings?: Record<string, string>;
+  scaleIds?: string[];
+}
+
+export interface GpaTermAnalytics {
+  semesterName: string;
+  displayName: string;
+  term: GpaSummary;
+  cumulative: GpaSummary;
+  major: GpaSummary;
+  nonMajor: GpaSummary;
+  earnedCredits: number;
+  cumulativeCredits: number;
+}
+
+export interface GpaHistogramBucket {
+  label: string;
+  min: number;
+  max: number | null;
+  credits: number;
+  courses: number;
+}
+
+export interface GpaCourseDeviation {
+  xkkh: string;
+  courseName: string;
+  semesterName: string;
+  credit: number;
+  hundredPoint: number;
+  fivePoint: number;
+  delta: number;
+}
+
+export interface GpaAnalytics {
+  terms: GpaTermAnalytics[];
+  histogram: GpaHistogramBucket[];
+  meanFivePoint: number;
+  best: GpaCourseDeviation[];
+  worst: GpaCourseDeviation[];
+}
+
+export type CourseCategory = 'general' | 'foundation' | 'major' | 'personal' | 'other';
+
+export interface GpaCategoryInput {
+  grades: any[];
+  retakeOverrides?: Record<string, RetakeOverride>;
+  majorCourseIds?: string[];
+  courseIdMappings?: Record<string, string>;
+  categoryOverrides?: Record<string, CourseCategory>;
+  scaleIds?: string[];
+}
+
+export interface CourseCategoryAssignment {
+  xkkh: string;
+  kcdm: string;
+  courseName: string;
+  category: CourseCategory;
+  source: 'manual' | 'zdbk' | 'majorList' | 'fallback';
+  rawCategory: string;
+}
+
+export interface CourseCategorySummary {
+  category: CourseCategory;
+  label: string;
+  courseCount: number;
+  gpaByPolicy: Record<RetakePolicy, GpaSummary>;
+}
+
+export interface GpaCategoryPayload {
+  categories: CourseCategorySummary[];
+  assignments: CourseCategoryAssignment[];
+}
+
+export type TranscriptFormat = 'csv' | 'json' | 'html';
+
+export interface TranscriptExportInput {
+  grades: any[];
+  format?: TranscriptFormat;
+  scaleId?: string;
+  retakePolicy?: RetakePolicy;
+  retakeOverrides?: Record<string, RetakeOverride>;
+  courseIdMappings?: Record<string, string>;
+  studentName?: string;
+  studentId?: string;
+  program?: string;
+  outputPath?: string;
+}
+
+export interface TranscriptCourse {
+  code: string;
+  title: string;
+  titleZh: string;
+  translated: boolean;
+  credits: number;
+  grade: string;
+  points: number;
+  counted: boolean;
+}
+
+export interface TranscriptTerm {
+  term: string;
+  label: string;
+  courses: TranscriptCourse[];
+  termGpa: number;
+  termCredits: number;
+  cumulativeGpa: number;
+  cumulativeCredits: number;
+}
+
+export interface EnglishTranscript {
+  studentName: string;
+  studentId: string;
+  program: string;
+  scaleId: string;
+  scaleName: string;
+  maxPoints: number;
+  retakePolicy: RetakePolicy;
+  terms: TranscriptTerm[];
+  cumulativeGpa: number;
+  totalCredits: number;
+  untranslated: string[];
+}
+
+export interface TranscriptExportPayload {
+  format: TranscriptFormat;
+  fileName: string;
+  mimeType: string;
+  content: string;
+  savedPath: string | null;
+  untranslated: string[];
+  transcript: EnglishTranscript;
+}
+
+export interface EvaluationTermWindow {
+  terms?: string[];
+  from?: string | null;
+  to?: string | null;
+  recent?: number | null;
+}
+
+export interface EvaluationComponentRule {
+  key: string;
+  label: string;
+  weight: number;
+  cap?: number | null;
+  fullScore?: number | null;
+}
+
+export interface EvaluationTierRule {
+  id: string;
+  name: string;
+  minScore?: number | null;
+  minGpa?: number | null;
+  maxFailed?: number | null;
+}
+
+export type EvaluationAcademicScope = 'overall' | 'major';
+
+export interface EvaluationRule {
+  id: string;
+  name: string;
+  description: string;
+  termWindow: EvaluationTermWindow;
+  scaleId: string;
+  academic: { weight: number; scope: EvaluationAcademicScope };
+  components: EvaluationComponentRule[];
+  totalCap?: number | null;
+  minCredits: number;
+  excludeFailed: boolean;
+  tiers: EvaluationTierRule[];
+  origin: 'preset' | 'user';
+}
+
+export interface EvaluationRulesPayload {
+  rules: EvaluationRule[];
+  errors: string[];
+}
+
+export interface EvaluationRuleImportInput {
+  path?: string;
+  content?: string;
+}
+
+export interface EvaluationInput {
+  grades: any[];
+  ruleId?: string;
+  rule?: EvaluationRule;
+  termWindow?: EvaluationTermWindow;
+  practice?: { pt2: number; pt3: number; pt4: number };
+  bonuses?: Record<string, number>;
+  retakePolicy?: RetakePolicy;
+  retakeOverrides?: Record<string, RetakeOverride>;
+  majorCourseIds?: string[];
+  courseIdMappings?: Record<string, string>;
+}
+
+export interface EvaluationItem {
+  key: string;
+  label: string;
+  raw: number;
+  capped: number;
+  normalized: number;
+  weight: number;
+  score: number;
+  note: string;
+}
+
+export interface EvaluationTierResult {
+  id: string;
+  name: string;
+  eligible: boolean;
+  reasons: string[];
+}
+
+export interface EvaluationResult {
+  ruleId: string;
+  ruleName: string;
+  terms: string[];
+  scaleId: string;
+  scaleName: string;
+  gpa: number;
+  maxPoints: number;
+  credits: number;
+  failedCourses: string[];
+  items: EvaluationItem[];
+  total: number;
+  tiers: EvaluationTierResult[];
+  warnings: string[];
+}
+
+export type GpaPlanScope = 'overall' | 'major';
+export type GpaPlanMetric = 'fivePoint' | 'fourPoint' | 'fourPointLegacy' | 'hundredPoint';
+
+export interface PlannedCourseInput {
+  id: string;
+  courseCode?: string;
+  courseName?: string;
+  credit: number;
+  major?: boolean;
+}
+
+export interface GpaPlanInput {
+  grades: any[];
+  target: number;
+  scope?: GpaPlanScope;
+  metric?: GpaPlanMetric;
+  plannedIds?: string[];
+  plannedCourses?: PlannedCourseInput[];
+  retakePolicy?: RetakePolicy;
+  retakeOverrides?: Record<string, RetakeOverride>;
+  majorCourseIds?: string[];
+  courseIdMappings?: Record<string, string>;
+}
+
+export interface PlannedScore {
+  id: string;
+  courseName: string;
+  credit: number;
+  score: number;
+  fivePoint: number;
+}
+
+export interface GpaPlanProposal {
+  scores: PlannedScore[];
+  averageScore: number;
+  achieved: number;
+}
+
+export interface GpaPlan {
+  scope: GpaPlanScope;
+  metric: GpaPlanMetric;
+  target: number;
+  current: number;
+  minimumAchievable: number;
+  maximumAchievable: number;
+  reachable: boolean;
+  alreadyMet: boolean;
+  uniformScore: number | null;
+  proposals: GpaPlanProposal[];
+  message: string;
 }
 
 export interface MaterialAsset {
@@ -160,6 +975,32 @@ export interface CourseFilter {
   count: number;
 }
 
+export type LectureTrack = 'teacher' | 'screen';
+
+export interface LectureStream {
+  track: LectureTrack;
+  format: 'hls' | 'mp4';
+  url: string;
+}
+
+export interface LectureDownloadInput {
+  courseId: number;
+  subId: number;
+  courseName: string;
+  title: string;
+  tracks?: LectureTrack[];
+}
+
+export interface LectureDownloadProgress {
+  downloadId: string;
+  track: LectureTrack;
+  downloadedBytes: number;
+  totalBytes?: number | null;
+  segmentsDone?: number | null;
+  segmentsTotal?: number | null;
+  done: boolean;
+}
+
 export interface MaterialSourceSummary {
   sourceType: MaterialSourceType | string;
   label: string;